@@ -0,0 +1,32 @@
+//! Serves the fake SpaceTraders world until Ctrl-C, to try commands offline:
+//!
+//! ```sh
+//! cargo run --example fake_server -- --port 8080
+//! TEST_API_BASE_URL=http://127.0.0.1:8080 space_traders_rust register -u ALI
+//! ```
+
+use std::net::SocketAddr;
+
+use clap::Parser;
+use space_traders_rust::fake_server::{FakeServer, FakeServerOptions};
+
+#[derive(Parser)]
+struct Args {
+    #[arg(short, long, default_value_t = 8080)]
+    port: u16,
+    /// Multiplies travel times and cooldowns, zero makes them instant
+    #[arg(short, long, default_value_t = 0.0)]
+    time_scale: f64,
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let server = FakeServer::start_with(FakeServerOptions {
+        addr: SocketAddr::from(([127, 0, 0, 1], args.port)),
+        time_scale: args.time_scale,
+        ..FakeServerOptions::default()
+    })?;
+    eprintln!("fake SpaceTraders API listening on {}", server.url());
+    tokio::signal::ctrl_c().await
+}
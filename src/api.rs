@@ -1,213 +1,408 @@
 use std::{collections::HashMap, env};
 
-use reqwest::{Client, Error, Response};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
     domain::{
-        AcceptContractResponse, Agent, DeliverCargoResponse, ExtractResourceResponse,
-        FulfillContractResponse, Market, MyContractsResponse, PurchaseShipResponse,
-        RegisterResponse, SellCargoResponse, Ship, ShipCargo, ShipDockResponse, ShipNav,
-        ShipNavigateResponse, ShipOrbitResponse, ShipRefuelResponse, ShipType, Shipyard, Survey,
-        TradeSymbol, Waypoint, WaypointTraitSymbol, WaypointType,
+        AcceptContractResponse, Agent, Contract, Cooldown, CreateSurveyResponse,
+        DeliverCargoResponse, ExtractResourceResponse, FulfillContractResponse,
+        JettisonCargoResponse, JumpGate, Market, MyContractsResponse, PurchaseCargoResponse,
+        PurchaseShipResponse, RegisterResponse, SellCargoResponse, Ship, ShipCargo,
+        ShipDockResponse, ShipNav, ShipNavigateResponse, ShipOrbitResponse, ShipRefuelResponse,
+        ShipType, Shipyard, Survey, TradeSymbol, Waypoint, WaypointTraitSymbol, WaypointType,
     },
+    ledger::{Ledger, LedgerEntry},
+    market_history::MarketHistory,
+    notes,
+    rate_limit::RateLimiter,
+    recording::Recording,
+    symbol_cache::{SymbolCache, Symbols},
     UserInfo,
 };
 
 const API_BASE_URL: &str = "https://api.spacetraders.io/v2";
+const WAYPOINTS_PAGE_LIMIT: i32 = 20;
 
 pub struct Api<'a> {
     client: Client,
     api_base_url: String,
     user_info: &'a UserInfo,
+    rate_limiter: RateLimiter,
+    ledger: Option<Ledger>,
+    market_history: Option<MarketHistory>,
+    symbol_cache: Option<SymbolCache>,
+    recording: Option<Recording>,
 }
 
 impl<'a> Api<'a> {
     pub fn new(user_info: &'a UserInfo) -> Self {
-        let url = env::var("TEST_API_BASE_URL").unwrap_or(API_BASE_URL.to_owned());
         Api {
             client: Client::new(),
-            api_base_url: url,
+            api_base_url: api_base_url(),
             user_info,
+            rate_limiter: RateLimiter::default(),
+            ledger: None,
+            market_history: None,
+            symbol_cache: None,
+            recording: None,
+        }
+    }
+
+    /// Appends every transaction made through this `Api` to the ledger
+    pub fn with_ledger(mut self, ledger: Ledger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Records the prices and transactions of every market fetched through this `Api`
+    pub fn with_market_history(mut self, market_history: MarketHistory) -> Self {
+        self.market_history = Some(market_history);
+        self
+    }
+
+    /// Remembers the ship, waypoint and contract symbols in responses, for shell completion
+    pub fn with_symbol_cache(mut self, symbol_cache: SymbolCache) -> Self {
+        self.symbol_cache = Some(symbol_cache);
+        self
+    }
+
+    /// Records every request and response, or replays them instead of using the network
+    pub fn with_recording(mut self, recording: Recording) -> Self {
+        self.recording = Some(recording);
+        self
+    }
+
+    /// For calls made without an `Api`, like registering, to be recorded or replayed too
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, ApiError> {
+        send(
+            &self.client,
+            &self.api_base_url,
+            self.recording.as_ref(),
+            request,
+        )
+        .await
+    }
+
+    /// The transaction already went through, so a ledger that can't be written only warns
+    fn record(&self, entry: LedgerEntry) {
+        if let Some(ledger) = &self.ledger {
+            if let Err(e) = ledger.append(&entry) {
+                notes::note(format!(
+                    "warning: could not record transaction in the ledger: {e}"
+                ));
+            }
+        }
+    }
+
+    /// The market was fetched fine, so history that can't be written only warns
+    fn record_market(&self, market: &Market) {
+        if let Some(market_history) = &self.market_history {
+            if let Err(e) = market_history.record(market) {
+                notes::note(format!(
+                    "warning: could not record the market in its history: {e}"
+                ));
+            }
+        }
+    }
+
+    /// Completion is a convenience, so a cache that can't be written only warns
+    fn cache_symbols(&self, change: impl FnOnce(&mut Symbols)) {
+        if let Some(symbol_cache) = &self.symbol_cache {
+            if let Err(e) = symbol_cache.update(change) {
+                notes::note(format!("warning: could not update the symbol cache: {e}"));
+            }
         }
     }
 
     // ------------ SHIP ------------
 
     pub async fn extract_resource(
-        self: Self,
+        &self,
         ship_symbol: String,
-        resource_survey: Option<Survey>,
+        resource_survey: Option<&Survey>,
     ) -> ApiResult<ExtractResourceResponse> {
         let url = format!("{}/my/ships/{ship_symbol}/extract", self.api_base_url);
         let mut body = HashMap::new();
         if let Some(survey) = resource_survey {
-            body.insert("survey", serde_json::to_string(&survey).unwrap());
+            body.insert("survey", serde_json::to_value(survey).unwrap());
         }
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .json(&body)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
         handle_api_response(response).await
     }
 
     pub async fn refuel_ship(
-        self: Self,
+        &self,
         ship_symbol: String,
         maybe_units: Option<i32>,
     ) -> ApiResult<ShipRefuelResponse> {
         let url = format!("{}/my/ships/{ship_symbol}/refuel", self.api_base_url);
         let mut body = HashMap::new();
         if let Some(units_to_refuel) = maybe_units {
-            body.insert("units", units_to_refuel.to_string());
+            body.insert("units", Value::from(units_to_refuel));
         }
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .json(&body)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<ShipRefuelResponse>(response).await;
+        if let Ok(res) = &res {
+            self.record(LedgerEntry::from_market_transaction(&res.data.transaction));
+        }
+        res
     }
 
-    pub async fn get_ship_status(self: Self, ship_symbol: String) -> ApiResult<Ship> {
+    pub async fn get_ship_status(&self, ship_symbol: String) -> ApiResult<Ship> {
         let url = format!("{}/my/ships/{ship_symbol}", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<Ship>(response).await;
+        if let Ok(res) = &res {
+            self.cache_symbols(|symbols| {
+                symbols.ships.insert(res.data.symbol.clone());
+                symbols
+                    .waypoints
+                    .insert(res.data.nav.waypoint_symbol.clone());
+            });
+        }
+        res
     }
 
-    pub async fn dock_ship(self: Self, ship_symbol: String) -> ApiResult<ShipDockResponse> {
+    pub async fn dock_ship(&self, ship_symbol: String) -> ApiResult<ShipDockResponse> {
         let url = format!("{}/my/ships/{ship_symbol}/dock", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .header("Content-Length", 0)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .header("Content-Length", 0)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
         handle_api_response(response).await
     }
 
-    pub async fn get_ship_nav_status(self: Self, ship_symbol: String) -> ApiResult<ShipNav> {
+    pub async fn get_ship_nav_status(&self, ship_symbol: String) -> ApiResult<ShipNav> {
         let url = format!("{}/my/ships/{ship_symbol}/nav", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
         handle_api_response(response).await
     }
 
     pub async fn navigate_ship(
-        self: Self,
+        &self,
         ship_symbol: String,
         waypoint_symbol: String,
     ) -> ApiResult<ShipNavigateResponse> {
         let url = format!("{}/my/ships/{ship_symbol}/navigate", self.api_base_url);
         let mut body = HashMap::new();
         body.insert("waypointSymbol", waypoint_symbol);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .json(&body)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
         handle_api_response(response).await
     }
 
-    pub async fn orbit_ship(self: Self, ship_symbol: String) -> ApiResult<ShipOrbitResponse> {
+    pub async fn orbit_ship(&self, ship_symbol: String) -> ApiResult<ShipOrbitResponse> {
         let url = format!("{}/my/ships/{ship_symbol}/orbit", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .bearer_auth(&self.user_info.token)
-            .header("Content-Length", 0)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .bearer_auth(&self.user_info.token)
+                    .header("Content-Length", 0),
+            )
             .await;
         handle_api_response(response).await
     }
 
-    pub async fn list_ships(self: Self) -> ApiResult<Vec<Ship>> {
-        let url = self.api_base_url + "/my/ships";
+    pub async fn list_ships(&self) -> ApiResult<Vec<Ship>> {
+        let url = format!("{}/my/ships", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<Vec<Ship>>(response).await;
+        if let Ok(res) = &res {
+            self.cache_symbols(|symbols| {
+                for ship in res.data.iter() {
+                    symbols.ships.insert(ship.symbol.clone());
+                    symbols.waypoints.insert(ship.nav.waypoint_symbol.clone());
+                }
+            });
+        }
+        res
     }
 
     pub async fn purchase_ship(
-        self: Self,
+        &self,
         ship_type: ShipType,
         waypoint_symbol: String,
     ) -> ApiResult<PurchaseShipResponse> {
-        let url = self.api_base_url + "/my/ships";
+        let url = format!("{}/my/ships", self.api_base_url);
         let mut body = HashMap::new();
         body.insert("shipType", ship_type.to_string());
         body.insert("waypointSymbol", waypoint_symbol);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .json(&body)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<PurchaseShipResponse>(response).await;
+        if let Ok(res) = &res {
+            self.record(LedgerEntry::from_shipyard_transaction(
+                &res.data.transaction,
+                &res.data.ship.symbol,
+            ));
+            self.cache_symbols(|symbols| {
+                symbols.ships.insert(res.data.ship.symbol.clone());
+            });
+        }
+        res
     }
 
-    pub async fn create_survey(self: Self, ship_symbol: String) -> ApiResult<Survey> {
+    pub async fn create_survey(&self, ship_symbol: String) -> ApiResult<CreateSurveyResponse> {
         let url = format!("{}/my/ships/{ship_symbol}/survey", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .header("Content-Length", 0)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .header("Content-Length", 0)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
         handle_api_response(response).await
     }
 
+    /// The ship's active cooldown, or `None` when it has none
+    pub async fn get_ship_cooldown(&self, ship_symbol: String) -> ApiResult<Option<Cooldown>> {
+        let url = format!("{}/my/ships/{ship_symbol}/cooldown", self.api_base_url);
+        self.rate_limiter.acquire().await;
+        let response = self
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
+            .await;
+        match response {
+            Ok(res) if res.status() == StatusCode::NO_CONTENT => Ok(ApiSuccessResponse {
+                data: None,
+                meta: None,
+            }),
+            response => handle_api_response(response).await,
+        }
+    }
+
     pub async fn sell_ship_cargo(
-        self: Self,
+        &self,
         ship_symbol: String,
         good_type: TradeSymbol,
         units: u32,
     ) -> ApiResult<SellCargoResponse> {
         let url = format!("{}/my/ships/{ship_symbol}/sell", self.api_base_url);
         let mut body = HashMap::new();
-        body.insert("symbol", good_type.to_string());
-        body.insert("units", units.to_string());
+        body.insert("symbol", Value::from(good_type.to_string()));
+        body.insert("units", Value::from(units));
+        self.rate_limiter.acquire().await;
+        let response = self
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
+            .await;
+        let res = handle_api_response::<SellCargoResponse>(response).await;
+        if let Ok(res) = &res {
+            self.record(LedgerEntry::from_market_transaction(&res.data.transaction));
+        }
+        res
+    }
+
+    pub async fn purchase_cargo(
+        &self,
+        ship_symbol: String,
+        good_type: TradeSymbol,
+        units: u32,
+    ) -> ApiResult<PurchaseCargoResponse> {
+        let url = format!("{}/my/ships/{ship_symbol}/purchase", self.api_base_url);
+        let mut body = HashMap::new();
+        body.insert("symbol", Value::from(good_type.to_string()));
+        body.insert("units", Value::from(units));
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .json(&body)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
+            .await;
+        let res = handle_api_response::<PurchaseCargoResponse>(response).await;
+        if let Ok(res) = &res {
+            self.record(LedgerEntry::from_market_transaction(&res.data.transaction));
+        }
+        res
+    }
+
+    pub async fn jettison_cargo(
+        &self,
+        ship_symbol: String,
+        good_type: TradeSymbol,
+        units: u32,
+    ) -> ApiResult<JettisonCargoResponse> {
+        let url = format!("{}/my/ships/{ship_symbol}/jettison", self.api_base_url);
+        let mut body = HashMap::new();
+        body.insert("symbol", Value::from(good_type.to_string()));
+        body.insert("units", Value::from(units));
+        self.rate_limiter.acquire().await;
+        let response = self
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
         handle_api_response(response).await
     }
 
-    pub async fn get_ship_cargo(self: Self, ship_symbol: String) -> ApiResult<ShipCargo> {
+    pub async fn get_ship_cargo(&self, ship_symbol: String) -> ApiResult<ShipCargo> {
         let url = format!("{}/my/ships/{ship_symbol}/cargo", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
         handle_api_response(response).await
     }
@@ -215,21 +410,24 @@ impl<'a> Api<'a> {
     // ------------ CONTRACT ------------
 
     pub async fn fulfill_contract(
-        self: Self,
+        &self,
         contract_id: String,
     ) -> ApiResult<FulfillContractResponse> {
         let url = format!("{}/my/contracts/{contract_id}/fulfill", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .bearer_auth(&self.user_info.token)
+                    .header("Content-Length", 0),
+            )
             .await;
         handle_api_response(response).await
     }
 
     pub async fn deliver_contract_goods(
-        self: Self,
+        &self,
         ship_symbol: String,
         contract_id: String,
         trade_symbol: TradeSymbol,
@@ -237,71 +435,88 @@ impl<'a> Api<'a> {
     ) -> ApiResult<DeliverCargoResponse> {
         let url = format!("{}/my/contracts/{contract_id}/deliver", self.api_base_url);
         let mut body = HashMap::new();
-        body.insert("shipSymbol", ship_symbol.to_string());
-        body.insert("tradeSymbol", trade_symbol.to_string());
-        body.insert("units", units.to_string());
+        body.insert("shipSymbol", Value::from(ship_symbol));
+        body.insert("tradeSymbol", Value::from(trade_symbol.to_string()));
+        body.insert("units", Value::from(units));
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .json(&body)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .json(&body)
+                    .bearer_auth(&self.user_info.token),
+            )
             .await;
         handle_api_response(response).await
     }
 
     // ------------ WAYPOINT ------------
 
-    pub async fn get_market(self: Self, waypoint_symbol: String) -> ApiResult<Market> {
+    pub async fn get_market(&self, waypoint_symbol: String) -> ApiResult<Market> {
         let system_symbol = Waypoint::get_system_id(&waypoint_symbol);
         let url = format!(
             "{}/systems/{system_symbol}/waypoints/{waypoint_symbol}/market",
             self.api_base_url
         );
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<Market>(response).await;
+        if let Ok(res) = &res {
+            self.record_market(&res.data);
+        }
+        res
     }
 
-    pub async fn fetch_agent_info(self: Self) -> ApiResult<Agent> {
-        let url = self.api_base_url + "/my/agent";
+    pub async fn fetch_agent_info(&self) -> ApiResult<Agent> {
+        let url = format!("{}/my/agent", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
         handle_api_response(response).await
     }
 
     pub async fn list_waypoints(
-        self: Self,
+        &self,
         system_symbol: String,
         trait_filter: Option<WaypointTraitSymbol>,
         type_filter: Option<WaypointType>,
     ) -> ApiResult<Vec<Waypoint>> {
-        let url = format!("{API_BASE_URL}/systems/{system_symbol}/waypoints");
-        let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
-            .await;
-        let api_response = handle_api_response::<Vec<Waypoint>>(response)
-            .await
-            .unwrap();
+        let url = format!("{}/systems/{system_symbol}/waypoints", self.api_base_url);
+        let mut waypoints = Vec::new();
+        let mut page = 1;
+        let meta = loop {
+            self.rate_limiter.acquire().await;
+            let response = self
+                .send(
+                    self.client
+                        .get(&url)
+                        .query(&[("limit", WAYPOINTS_PAGE_LIMIT), ("page", page)])
+                        .bearer_auth(&self.user_info.token),
+                )
+                .await;
+            let api_response = handle_api_response::<Vec<Waypoint>>(response).await?;
+            let fetched = api_response.data.len();
+            waypoints.extend(api_response.data);
+            match api_response.meta {
+                Some(meta) if fetched > 0 && (waypoints.len() as i32) < meta.total => page += 1,
+                meta => break meta,
+            }
+        };
 
-        let new_data = Vec::into_iter(api_response.data)
+        self.cache_symbols(|symbols| {
+            symbols
+                .waypoints
+                .extend(waypoints.iter().map(|waypoint| waypoint.symbol.clone()));
+        });
+
+        let new_data = Vec::into_iter(waypoints)
             .filter(|wp| {
-                trait_filter.is_none()
-                    || wp
-                        .traits
-                        .iter()
-                        .any(|tr| tr.symbol == trait_filter.unwrap())
+                trait_filter
+                    .as_ref()
+                    .is_none_or(|symbol| wp.traits.iter().any(|tr| &tr.symbol == symbol))
             })
             .filter(move |w| {
                 type_filter.is_none() || w.waypoint_type == type_filter.clone().unwrap()
@@ -310,89 +525,150 @@ impl<'a> Api<'a> {
 
         Ok(ApiSuccessResponse {
             data: new_data,
-            ..api_response
+            meta,
         })
     }
 
-    pub async fn get_shipyard_for_waypoint(
-        self: Self,
-        waypoint_symbol: String,
-    ) -> ApiResult<Shipyard> {
+    pub async fn get_shipyard_for_waypoint(&self, waypoint_symbol: String) -> ApiResult<Shipyard> {
         let system_symbol = Waypoint::get_system_id(&waypoint_symbol);
         let url = format!(
             "{}/systems/{system_symbol}/waypoints/{waypoint_symbol}/shipyard",
             self.api_base_url
         );
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
         handle_api_response(response).await
     }
 
-    pub async fn get_waypoint(self: Self, waypoint_symbol: String) -> ApiResult<Waypoint> {
+    pub async fn get_jump_gate(&self, waypoint_symbol: String) -> ApiResult<JumpGate> {
         let system_symbol = Waypoint::get_system_id(&waypoint_symbol);
         let url = format!(
-            "{}/systems/{system_symbol}/waypoints/{waypoint_symbol}",
+            "{}/systems/{system_symbol}/waypoints/{waypoint_symbol}/jump-gate",
             self.api_base_url
         );
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
         handle_api_response(response).await
     }
 
-    pub async fn accept_contract(
-        self: Self,
-        contract_id: String,
-    ) -> ApiResult<AcceptContractResponse> {
-        let url = format!("{}/my/contracts/{}/accept", API_BASE_URL, contract_id);
+    pub async fn get_waypoint(&self, waypoint_symbol: String) -> ApiResult<Waypoint> {
+        let system_symbol = Waypoint::get_system_id(&waypoint_symbol);
+        let url = format!(
+            "{}/systems/{system_symbol}/waypoints/{waypoint_symbol}",
+            self.api_base_url
+        );
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(url)
-            .bearer_auth(&self.user_info.token)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .header("Content-Length", 0)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<Waypoint>(response).await;
+        if let Ok(res) = &res {
+            self.cache_symbols(|symbols| {
+                symbols.waypoints.insert(res.data.symbol.clone());
+            });
+        }
+        res
     }
 
-    pub async fn fetch_contracts(self: Self) -> ApiResult<MyContractsResponse> {
-        let url = API_BASE_URL.to_owned() + "/my/contracts";
+    pub async fn accept_contract(&self, contract_id: String) -> ApiResult<AcceptContractResponse> {
+        let url = format!("{}/my/contracts/{contract_id}/accept", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .get(url)
-            .bearer_auth(&self.user_info.token)
-            .send()
+            .send(
+                self.client
+                    .post(url)
+                    .bearer_auth(&self.user_info.token)
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .header("Content-Length", 0),
+            )
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<AcceptContractResponse>(response).await;
+        if let Ok(res) = &res {
+            self.cache_symbols(|symbols| {
+                symbols.contracts.insert(res.data.contract.id.clone());
+            });
+        }
+        res
     }
 
-    // ------------ AUTH ----------
+    pub async fn fetch_contracts(&self) -> ApiResult<MyContractsResponse> {
+        let url = format!("{}/my/contracts", self.api_base_url);
+        self.rate_limiter.acquire().await;
+        let response = self
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
+            .await;
+        let res = handle_api_response::<MyContractsResponse>(response).await;
+        if let Ok(res) = &res {
+            self.cache_symbols(|symbols| {
+                symbols
+                    .contracts
+                    .extend(res.data.iter().map(|contract| contract.id.clone()));
+            });
+        }
+        res
+    }
 
-    pub async fn register_player(
-        self: Self,
-        username: String,
-        faction: String,
-    ) -> ApiResult<RegisterResponse> {
-        println!("registering...");
-        let mut body = HashMap::new();
-        body.insert("symbol", username);
-        body.insert("faction", faction);
+    pub async fn get_contract(&self, contract_id: String) -> ApiResult<Contract> {
+        let url = format!("{}/my/contracts/{contract_id}", self.api_base_url);
+        self.rate_limiter.acquire().await;
         let response = self
-            .client
-            .post(API_BASE_URL.to_owned() + "/register")
-            .json(&body)
-            .send()
+            .send(self.client.get(url).bearer_auth(&self.user_info.token))
             .await;
-        handle_api_response(response).await
+        let res = handle_api_response::<Contract>(response).await;
+        if let Ok(res) = &res {
+            self.cache_symbols(|symbols| {
+                symbols.contracts.insert(res.data.id.clone());
+            });
+        }
+        res
+    }
+}
+
+fn api_base_url() -> String {
+    env::var("TEST_API_BASE_URL").unwrap_or(API_BASE_URL.to_owned())
+}
+
+// ------------ AUTH ----------
+
+/// Registering is how a token is obtained in the first place, so it doesn't need an `Api`
+pub async fn register_player(
+    username: String,
+    faction: String,
+    recording: Option<&Recording>,
+) -> ApiResult<RegisterResponse> {
+    eprintln!("registering...");
+    let mut body = HashMap::new();
+    body.insert("symbol", username);
+    body.insert("faction", faction);
+    let client = Client::new();
+    let base_url = api_base_url();
+    let request = client.post(format!("{base_url}/register")).json(&body);
+    let response = send(&client, &base_url, recording, request).await;
+    handle_api_response(response).await
+}
+
+async fn send(
+    client: &Client,
+    base_url: &str,
+    recording: Option<&Recording>,
+    request: RequestBuilder,
+) -> Result<Response, ApiError> {
+    let request = request.build().map_err(|e| ApiError::NetworkError {
+        message: e.to_string(),
+    })?;
+    match recording {
+        Some(recording) => recording.send(client, base_url, request).await,
+        None => client
+            .execute(request)
+            .await
+            .map_err(|e| ApiError::NetworkError {
+                message: e.to_string(),
+            }),
     }
 }
 
@@ -418,34 +694,32 @@ pub struct ApiErrorObj {
 
 #[derive(thiserror::Error, Debug)]
 pub enum ApiError {
-    #[error("HTTP API error")]
+    #[error("HTTP API error {status} (code {code}): {message}")]
     ServiceError {
         status: u16,
         message: String,
         code: i32,
         data: Option<HashMap<String, Value>>,
     },
-    #[error("Error parsing JSON response from API")]
+    #[error("Error parsing JSON response from API: {message}")]
     ParseError { message: String },
 
-    #[error("Unknown error")]
-    UnknownError { message: String },
+    #[error("Network error: {message}")]
+    NetworkError { message: String },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Meta {
-    total: i32,
-    page: i32,
-    limit: i32,
+    pub total: i32,
+    pub page: i32,
+    pub limit: i32,
 }
 
 pub async fn handle_api_response<T: DeserializeOwned>(
-    response: Result<Response, Error>,
+    response: Result<Response, ApiError>,
 ) -> ApiResult<T> {
     match response {
-        Err(e) => Err(ApiError::UnknownError {
-            message: e.to_string(),
-        }),
+        Err(e) => Err(e),
         Ok(api_response) => {
             let status_code = api_response.status();
 
@@ -457,13 +731,21 @@ pub async fn handle_api_response<T: DeserializeOwned>(
                     }),
                 }
             } else {
-                let service_response = api_response.json::<ApiErrorResponse>().await.unwrap();
-                Err(ApiError::ServiceError {
-                    message: service_response.error.message,
-                    code: service_response.error.code,
-                    status: status_code.as_u16(),
-                    data: service_response.error.data,
-                })
+                let body = api_response.text().await.unwrap_or_default();
+                match serde_json::from_str::<ApiErrorResponse>(&body) {
+                    Ok(service_response) => Err(ApiError::ServiceError {
+                        message: service_response.error.message,
+                        code: service_response.error.code,
+                        status: status_code.as_u16(),
+                        data: service_response.error.data,
+                    }),
+                    Err(_) => Err(ApiError::ServiceError {
+                        message: body,
+                        code: 0,
+                        status: status_code.as_u16(),
+                        data: None,
+                    }),
+                }
             }
         }
     }
@@ -0,0 +1,138 @@
+//! Shell completion scripts. Beyond clap's static completions, the values of symbol options
+//! like `--ship-symbol` complete from the symbol cache: the scripts call back into
+//! `completions -- <words>` with the command line so far, which prints the matching symbols,
+//! or fails to fall back to the static completions.
+
+use std::io::Write;
+
+use clap::{Command, ValueEnum};
+use clap_complete::{generate, Shell};
+
+use crate::{
+    error::{AppError, AppResult},
+    symbol_cache::Symbols,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Ship,
+    Waypoint,
+    Contract,
+}
+
+impl SymbolKind {
+    /// The kind of symbol an argument takes, by its name
+    fn of_arg(id: &str) -> Option<SymbolKind> {
+        match id {
+            "ship_symbol" => Some(SymbolKind::Ship),
+            "contract_id" => Some(SymbolKind::Contract),
+            "waypoint_symbol" | "destination" | "buy_at" | "mine_at" | "asteroid_field"
+            | "market" | "waypoints" => Some(SymbolKind::Waypoint),
+            _ => None,
+        }
+    }
+
+    pub fn symbols(self, symbols: &Symbols) -> impl Iterator<Item = &String> {
+        match self {
+            SymbolKind::Ship => symbols.ships.iter(),
+            SymbolKind::Waypoint => symbols.waypoints.iter(),
+            SymbolKind::Contract => symbols.contracts.iter(),
+        }
+    }
+}
+
+const BASH_HOOK: &str = r#"
+_BIN_symbols() {
+    local symbols
+    if symbols=$(BIN completions -- "${COMP_WORDS[@]:0:COMP_CWORD+1}" 2>/dev/null); then
+        COMPREPLY=($(compgen -W "$symbols" -- "${COMP_WORDS[COMP_CWORD]}"))
+    else
+        _BIN "$@"
+    fi
+}
+complete -F _BIN_symbols -o nosort -o bashdefault -o default BIN
+"#;
+
+const ZSH_HOOK: &str = r#"
+_BIN_symbols() {
+    local -a symbols
+    if symbols=(${(f)"$(BIN completions -- ${words[1,CURRENT]} 2>/dev/null)"}); then
+        compadd -a symbols
+    else
+        _BIN "$@"
+    fi
+}
+compdef _BIN_symbols BIN
+"#;
+
+const FISH_HOOK: &str = r#"
+function __BIN_symbols
+    BIN completions -- (commandline -opc) (commandline -ct) 2>/dev/null
+end
+complete -c BIN -n '__BIN_symbols >/dev/null' -f -a '(__BIN_symbols)'
+"#;
+
+/// Writes the static completions followed by the hook completing symbols from the cache
+pub fn write_script(
+    shell: CompletionShell,
+    cmd: &mut Command,
+    out: &mut impl Write,
+) -> AppResult<()> {
+    let bin = cmd.get_name().to_owned();
+    let (static_shell, hook) = match shell {
+        CompletionShell::Bash => (Shell::Bash, BASH_HOOK),
+        CompletionShell::Zsh => (Shell::Zsh, ZSH_HOOK),
+        CompletionShell::Fish => (Shell::Fish, FISH_HOOK),
+    };
+    generate(static_shell, cmd, &bin, out);
+    out.write_all(hook.replace("BIN", &bin).as_bytes())?;
+    Ok(())
+}
+
+/// The kind of symbol the last word is the value of. `words` is the command line up to and
+/// including the word being completed, starting with the program name.
+pub fn symbol_kind(cmd: &mut Command, words: &[String]) -> Option<SymbolKind> {
+    cmd.build();
+    let [rest @ .., option, _current] = words.get(1..)? else {
+        return None;
+    };
+    let mut cmd = &*cmd;
+    for word in rest.iter() {
+        if let Some(subcommand) = cmd.find_subcommand(word) {
+            cmd = subcommand;
+        }
+    }
+    let arg = cmd
+        .get_arguments()
+        .find(|arg| match option.strip_prefix("--") {
+            Some(long) => {
+                arg.get_long() == Some(long)
+                    || arg
+                        .get_all_aliases()
+                        .is_some_and(|aliases| aliases.contains(&long))
+            }
+            None => {
+                option.len() == 2
+                    && option.starts_with('-')
+                    && option.chars().nth(1) == arg.get_short()
+            }
+        })?;
+    SymbolKind::of_arg(arg.get_id().as_str())
+}
+
+/// Prints the cached symbols the last word may be completed with
+pub fn complete(cmd: &mut Command, words: &[String], symbols: &Symbols) -> AppResult<()> {
+    let kind = symbol_kind(cmd, words)
+        .ok_or_else(|| AppError::Usage(String::from("no symbols to complete here")))?;
+    for symbol in kind.symbols(symbols) {
+        println!("{symbol}");
+    }
+    Ok(())
+}
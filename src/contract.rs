@@ -0,0 +1,608 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::Api,
+    domain::{
+        format_duration, Contract, DeliverTerms, ShipCargo, ShipNavFlightMode, ShipNavStatus,
+        TradeSymbol, Waypoint,
+    },
+    error::{AppError, AppResult},
+    estimator::{distance, fuel_cost, travel_seconds},
+    market_history::{MarketHistory, PriceRecord},
+    mining, navigation,
+    notes::Progress,
+    ship_state,
+    signal::StopSignal,
+    trade::TradeShip,
+};
+
+const CONTRACT_RUNS_FILE: &str = "contract_runs.json";
+
+/// What a contract run was started with, kept until the contract is fulfilled so an
+/// interrupted run can pick up where it left off
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractRun {
+    pub contract_id: String,
+    pub ship_symbol: String,
+    /// Market to buy the goods at. Without it or `mine_at`, the cheapest market in the
+    /// market history is used.
+    pub buy_at: Option<String>,
+    /// Asteroid field to mine the goods at
+    pub mine_at: Option<String>,
+}
+
+pub struct ContractRuns {
+    path: PathBuf,
+}
+
+impl ContractRuns {
+    pub fn open(dir: &Path) -> Self {
+        ContractRuns {
+            path: dir.join(CONTRACT_RUNS_FILE),
+        }
+    }
+
+    pub fn all(&self) -> std::io::Result<Vec<ContractRun>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn get(&self, contract_id: &str) -> std::io::Result<Option<ContractRun>> {
+        Ok(self
+            .all()?
+            .into_iter()
+            .find(|run| run.contract_id == contract_id))
+    }
+
+    pub fn save(&self, run: &ContractRun) -> std::io::Result<()> {
+        let mut runs = self.all()?;
+        runs.retain(|r| r.contract_id != run.contract_id);
+        runs.push(run.clone());
+        self.write(&runs)
+    }
+
+    pub fn remove(&self, contract_id: &str) -> std::io::Result<()> {
+        let mut runs = self.all()?;
+        runs.retain(|r| r.contract_id != contract_id);
+        self.write(&runs)
+    }
+
+    fn write(&self, runs: &[ContractRun]) -> std::io::Result<()> {
+        let file = File::create(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, runs)?;
+        writer.flush()
+    }
+}
+
+/// Works out the run to do from the command line and any saved run for the contract. Sourcing
+/// options given on the command line replace the saved ones.
+pub fn resolve_run(
+    runs: &ContractRuns,
+    contract_id: String,
+    ship_symbol: Option<String>,
+    buy_at: Option<String>,
+    mine_at: Option<String>,
+) -> AppResult<ContractRun> {
+    let saved = runs.get(&contract_id)?;
+    if let Some(saved) = &saved {
+        eprintln!(
+            "resuming contract run {} with {}",
+            saved.contract_id, saved.ship_symbol
+        );
+    }
+    let ship_symbol = ship_symbol
+        .or_else(|| saved.as_ref().map(|s| s.ship_symbol.clone()))
+        .ok_or_else(|| {
+            AppError::Usage(format!(
+                "no saved run for contract {contract_id}, --ship-symbol is required"
+            ))
+        })?;
+    let (buy_at, mine_at) = match (buy_at, mine_at, saved) {
+        (None, None, Some(saved)) => (saved.buy_at, saved.mine_at),
+        (buy_at, mine_at, _) => (buy_at, mine_at),
+    };
+    Ok(ContractRun {
+        contract_id,
+        ship_symbol,
+        buy_at,
+        mine_at,
+    })
+}
+
+/// Sources and delivers every outstanding good of the contract, then fulfills it. Progress is
+/// read back from the server on every step, so a run can be stopped and started again at any
+/// point. Returns false if a stop request ended the run early.
+pub async fn run(
+    api: &Api<'_>,
+    runs: &ContractRuns,
+    market_history: &MarketHistory,
+    contract_run: &ContractRun,
+    mut stop: StopSignal,
+    progress: Progress<'_>,
+) -> AppResult<bool> {
+    let contract_id = &contract_run.contract_id;
+    let ship_symbol = &contract_run.ship_symbol;
+    let contract = api.get_contract(contract_id.clone()).await?.data;
+    if contract.fulfilled {
+        progress(format!("contract {contract_id} is already fulfilled"));
+        runs.remove(contract_id)?;
+        return Ok(true);
+    }
+    if !contract.accepted {
+        return Err(AppError::Usage(format!(
+            "contract {contract_id} has not been accepted yet"
+        )));
+    }
+    runs.save(contract_run)?;
+
+    for terms in contract.terms.deliver.iter() {
+        let good = TradeSymbol::from_symbol(&terms.trade_symbol);
+        let mut units_fulfilled = terms.units_fulfilled;
+        report_delivery(terms, units_fulfilled, progress);
+
+        while units_fulfilled < terms.units_required {
+            if stop.is_stopped() {
+                return Ok(false);
+            }
+            let remaining = terms.units_required - units_fulfilled;
+            let cargo = api.get_ship_cargo(ship_symbol.clone()).await?.data;
+            let held = units_held(&cargo, &terms.trade_symbol);
+            let free = cargo.capacity - cargo.units;
+
+            if held < remaining && free > 0 {
+                let wanted = (remaining - held).min(free) as u32;
+                let sourced = match (&contract_run.buy_at, &contract_run.mine_at) {
+                    (Some(market), _) => {
+                        buy(api, ship_symbol, market, &good, wanted, &mut stop, progress).await?
+                    }
+                    (None, Some(asteroid_field)) => {
+                        mine(api, ship_symbol, asteroid_field, &good, &mut stop, progress).await?
+                    }
+                    (None, None) => {
+                        let market = cheapest_market(market_history, &terms.trade_symbol)?;
+                        buy(
+                            api,
+                            ship_symbol,
+                            &market,
+                            &good,
+                            wanted,
+                            &mut stop,
+                            progress,
+                        )
+                        .await?
+                    }
+                };
+                if !sourced {
+                    return Ok(false);
+                }
+                continue;
+            }
+            if held == 0 {
+                return Err(AppError::InvalidShipState(format!(
+                    "{ship_symbol} has no room left for {}",
+                    terms.trade_symbol
+                )));
+            }
+
+            if !navigation::travel_to(api, ship_symbol, &terms.destination_symbol, &mut stop)
+                .await?
+            {
+                return Ok(false);
+            }
+            ship_state::ensure_state(api, ship_symbol, ShipNavStatus::DOCKED).await?;
+            let res = api
+                .deliver_contract_goods(
+                    ship_symbol.clone(),
+                    contract_id.clone(),
+                    good.clone(),
+                    held.min(remaining) as u32,
+                )
+                .await?
+                .data;
+            units_fulfilled = res
+                .contract
+                .terms
+                .deliver
+                .iter()
+                .find(|t| {
+                    t.trade_symbol == terms.trade_symbol
+                        && t.destination_symbol == terms.destination_symbol
+                })
+                .map_or(units_fulfilled + held.min(remaining), |t| t.units_fulfilled);
+            report_delivery(terms, units_fulfilled, progress);
+        }
+    }
+
+    let res = api.fulfill_contract(contract_id.clone()).await?.data;
+    progress(format!(
+        "fulfilled contract {contract_id}, paid {} (credits {})",
+        res.contract.terms.payment.on_fulfilled, res.agent.credits
+    ));
+    runs.remove(contract_id)?;
+    Ok(true)
+}
+
+fn report_delivery(terms: &DeliverTerms, units_fulfilled: i32, progress: Progress<'_>) {
+    progress(format!(
+        "{} -> {}: {}/{} delivered",
+        terms.trade_symbol, terms.destination_symbol, units_fulfilled, terms.units_required
+    ));
+}
+
+fn units_held(cargo: &ShipCargo, trade_symbol: &str) -> i32 {
+    cargo
+        .inventory
+        .iter()
+        .filter(|item| item.symbol == trade_symbol)
+        .map(|item| item.units)
+        .sum()
+}
+
+/// The market with the lowest recorded purchase price for the good
+fn cheapest_market(market_history: &MarketHistory, trade_symbol: &str) -> AppResult<String> {
+    market_history
+        .latest()?
+        .into_iter()
+        .filter(|record| record.trade_symbol == trade_symbol)
+        .min_by_key(|record| record.purchase_price)
+        .map(|record| record.waypoint_symbol)
+        .ok_or_else(|| {
+            AppError::Usage(format!(
+                "no known market sells {trade_symbol}, pass --buy-at or --mine-at"
+            ))
+        })
+}
+
+async fn buy(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    market_symbol: &str,
+    good: &TradeSymbol,
+    units: u32,
+    stop: &mut StopSignal,
+    progress: Progress<'_>,
+) -> AppResult<bool> {
+    if !navigation::travel_to(api, ship_symbol, market_symbol, stop).await? {
+        return Ok(false);
+    }
+    ship_state::ensure_state(api, ship_symbol, ShipNavStatus::DOCKED).await?;
+    let market = api.get_market(market_symbol.to_owned()).await?.data;
+    let trade_good = market
+        .trade_goods
+        .iter()
+        .find(|g| g.symbol == good.to_string())
+        .ok_or_else(|| AppError::Usage(format!("{market_symbol} does not sell {good}")))?;
+
+    // markets only sell up to their trade volume per transaction
+    let mut remaining = units;
+    while remaining > 0 {
+        let batch = remaining.min(trade_good.trade_volume.max(1));
+        let res = api
+            .purchase_cargo(ship_symbol.to_owned(), good.clone(), batch)
+            .await?
+            .data;
+        progress(format!(
+            "bought {} {} at {} for {}",
+            batch, good, market_symbol, res.transaction.total_price
+        ));
+        remaining -= batch;
+    }
+    Ok(true)
+}
+
+/// Extracts once, jettisoning anything that isn't the contract good
+async fn mine(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    asteroid_field: &str,
+    good: &TradeSymbol,
+    stop: &mut StopSignal,
+    progress: Progress<'_>,
+) -> AppResult<bool> {
+    if !navigation::travel_to(api, ship_symbol, asteroid_field, stop).await? {
+        return Ok(false);
+    }
+    ship_state::ensure_state(api, ship_symbol, ShipNavStatus::IN_ORBIT).await?;
+    let ready_at = api
+        .get_ship_cooldown(ship_symbol.to_owned())
+        .await?
+        .data
+        .and_then(|cooldown| cooldown.expiration);
+    if !mining::wait_until(ready_at, stop, progress).await {
+        return Ok(false);
+    }
+
+    let res = api
+        .extract_resource(ship_symbol.to_owned(), None)
+        .await?
+        .data;
+    let extracted = res.extraction.extraction_yield;
+    if extracted.symbol == *good {
+        progress(format!(
+            "extracted {} {}",
+            extracted.units, extracted.symbol
+        ));
+    } else {
+        api.jettison_cargo(
+            ship_symbol.to_owned(),
+            extracted.symbol.clone(),
+            extracted.units as u32,
+        )
+        .await?;
+        progress(format!(
+            "jettisoned {} {}",
+            extracted.units, extracted.symbol
+        ));
+    }
+    Ok(true)
+}
+
+/// Sourcing and delivering the outstanding units of one good
+#[derive(Debug, Clone)]
+pub struct DeliveryPlan {
+    pub trade_symbol: String,
+    pub destination_symbol: String,
+    pub units: i32,
+    /// Cheapest market in the market history and its purchase price, if any sells the good
+    pub buy_at: Option<(String, u32)>,
+    pub trips: i32,
+}
+
+impl DeliveryPlan {
+    pub fn purchase_cost(&self) -> Option<i64> {
+        self.buy_at
+            .as_ref()
+            .map(|(_, price)| *price as i64 * self.units as i64)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractEvaluation {
+    pub contract_id: String,
+    pub payment: i64,
+    pub deliveries: Vec<DeliveryPlan>,
+    /// The ship that would finish soonest, none if no ship can carry cargo
+    pub ship_symbol: Option<String>,
+    pub travel_seconds: i64,
+    pub fuel: i32,
+    /// Cheapest recorded price of a market unit of fuel, which fills 100 units of a tank
+    pub fuel_price: Option<u32>,
+    pub time_left: Duration,
+}
+
+impl ContractEvaluation {
+    /// None when a good has no recorded price
+    pub fn purchase_cost(&self) -> Option<i64> {
+        self.deliveries
+            .iter()
+            .map(DeliveryPlan::purchase_cost)
+            .sum()
+    }
+
+    pub fn fuel_cost(&self) -> Option<i64> {
+        match (self.fuel, self.fuel_price) {
+            (0, _) => Some(0),
+            (fuel, Some(price)) => Some((fuel as i64 + 99) / 100 * price as i64),
+            (_, None) => None,
+        }
+    }
+
+    pub fn net_profit(&self) -> Option<i64> {
+        Some(self.payment - self.purchase_cost()? - self.fuel_cost()?)
+    }
+
+    pub fn trips(&self) -> i32 {
+        self.deliveries.iter().map(|delivery| delivery.trips).sum()
+    }
+
+    pub fn meets_deadline(&self) -> bool {
+        self.ship_symbol.is_some() && self.travel_seconds <= self.time_left.num_seconds()
+    }
+}
+
+impl fmt::Display for ContractEvaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "contract {}", self.contract_id)?;
+        for delivery in self.deliveries.iter() {
+            write!(
+                f,
+                "  {} {} -> {}: ",
+                delivery.units, delivery.trade_symbol, delivery.destination_symbol
+            )?;
+            match (&delivery.buy_at, delivery.purchase_cost()) {
+                (Some((market, price)), Some(cost)) => {
+                    write!(f, "buy at {market} for {price} ({cost})")?
+                }
+                _ => write!(f, "no recorded market sells it")?,
+            }
+            writeln!(f, ", {} trip(s)", delivery.trips)?;
+        }
+        writeln!(f, "  payment     {:>8}", self.payment)?;
+        match self.purchase_cost() {
+            Some(cost) => writeln!(f, "  purchases   {:>8}", -cost)?,
+            None => writeln!(f, "  purchases    unknown")?,
+        }
+        match self.fuel_cost() {
+            Some(cost) => writeln!(f, "  fuel        {:>8}  ({} fuel)", -cost, self.fuel)?,
+            None => writeln!(
+                f,
+                "  fuel         unknown  ({} fuel, no recorded price)",
+                self.fuel
+            )?,
+        }
+        match self.net_profit() {
+            Some(profit) => writeln!(f, "  net profit  {profit:>8}")?,
+            None => writeln!(f, "  net profit   unknown")?,
+        }
+        match &self.ship_symbol {
+            Some(ship_symbol) => write!(
+                f,
+                "  {ship_symbol} needs {} trip(s) and {} of travel, deadline in {}: {}",
+                self.trips(),
+                format_duration(Duration::seconds(self.travel_seconds)),
+                format_duration(self.time_left),
+                if self.meets_deadline() {
+                    "realistic"
+                } else {
+                    "not realistic"
+                }
+            ),
+            None => write!(f, "  no ship can carry the goods"),
+        }
+    }
+}
+
+/// Estimates a contract with each of our cargo ships sourcing its goods at the cheapest
+/// recorded markets, and keeps the ship that finishes soonest. Every trip flies in cruise from
+/// wherever the ship is to the market, then to the destination with a full hold. Goods no
+/// market is known to sell are assumed to be at hand, and legs to waypoints without known
+/// coordinates are left out.
+pub fn evaluate_contract(
+    contract: &Contract,
+    prices: &[PriceRecord],
+    coordinates: &HashMap<String, (i32, i32)>,
+    ships: &[(String, TradeShip)],
+) -> ContractEvaluation {
+    let cheapest = |trade_symbol: &str| {
+        prices
+            .iter()
+            .filter(|record| record.trade_symbol == trade_symbol)
+            .min_by_key(|record| record.purchase_price)
+            .map(|record| (record.waypoint_symbol.clone(), record.purchase_price))
+    };
+    let outstanding: Vec<_> = contract
+        .terms
+        .deliver
+        .iter()
+        .filter(|terms| terms.units_fulfilled < terms.units_required)
+        .map(|terms| (terms, cheapest(&terms.trade_symbol)))
+        .collect();
+
+    let mut best: Option<(String, Vec<DeliveryPlan>, i64, i32)> = None;
+    for (ship_symbol, ship) in ships.iter().filter(|(_, ship)| ship.cargo_capacity > 0) {
+        let mut location = Some(ship.location);
+        let mut seconds = 0;
+        let mut fuel = 0;
+        let mut fly = |from: Option<(i32, i32)>, to: Option<(i32, i32)>| {
+            if let (Some(from), Some(to)) = (from, to) {
+                let d = distance(from, to);
+                seconds += travel_seconds(ShipNavFlightMode::CRUISE, d, ship.speed);
+                fuel += fuel_cost(ShipNavFlightMode::CRUISE, d);
+            }
+            to
+        };
+        let mut deliveries = Vec::new();
+        for (terms, buy_at) in outstanding.iter() {
+            let units = terms.units_required - terms.units_fulfilled;
+            let trips = (units + ship.cargo_capacity - 1) / ship.cargo_capacity;
+            let market = buy_at
+                .as_ref()
+                .and_then(|(market, _)| coordinates.get(market).copied());
+            let destination = coordinates.get(&terms.destination_symbol).copied();
+            for _ in 0..trips {
+                if market.is_some() {
+                    location = fly(location, market);
+                }
+                location = fly(location, destination).or(location);
+            }
+            deliveries.push(DeliveryPlan {
+                trade_symbol: terms.trade_symbol.clone(),
+                destination_symbol: terms.destination_symbol.clone(),
+                units,
+                buy_at: buy_at.clone(),
+                trips,
+            });
+        }
+        if best
+            .as_ref()
+            .is_none_or(|(_, _, best_seconds, _)| seconds < *best_seconds)
+        {
+            best = Some((ship_symbol.clone(), deliveries, seconds, fuel));
+        }
+    }
+
+    let (ship_symbol, deliveries, travel_seconds, fuel) = match best {
+        Some((ship_symbol, deliveries, seconds, fuel)) => {
+            (Some(ship_symbol), deliveries, seconds, fuel)
+        }
+        None => {
+            let deliveries = outstanding
+                .iter()
+                .map(|(terms, buy_at)| DeliveryPlan {
+                    trade_symbol: terms.trade_symbol.clone(),
+                    destination_symbol: terms.destination_symbol.clone(),
+                    units: terms.units_required - terms.units_fulfilled,
+                    buy_at: buy_at.clone(),
+                    trips: 0,
+                })
+                .collect();
+            (None, deliveries, 0, 0)
+        }
+    };
+    let payment = &contract.terms.payment;
+    ContractEvaluation {
+        contract_id: contract.id.clone(),
+        payment: payment.on_accepted as i64 + payment.on_fulfilled as i64,
+        deliveries,
+        ship_symbol,
+        travel_seconds,
+        fuel,
+        fuel_price: cheapest("FUEL").map(|(_, price)| price),
+        time_left: contract.time_until_deadline(),
+    }
+}
+
+pub async fn evaluate(
+    api: &Api<'_>,
+    market_history: &MarketHistory,
+    contract_id: String,
+) -> AppResult<ContractEvaluation> {
+    let contract = api.get_contract(contract_id).await?.data;
+    let ships: Vec<_> = api
+        .list_ships()
+        .await?
+        .data
+        .into_iter()
+        .map(|ship| {
+            let here = &ship.nav.route.destination;
+            let trade_ship = TradeShip {
+                location: (here.x, here.y),
+                cargo_capacity: ship.cargo.capacity,
+                speed: ship.engine.speed,
+            };
+            (ship.symbol, trade_ship)
+        })
+        .collect();
+    let systems: BTreeSet<_> = contract
+        .terms
+        .deliver
+        .iter()
+        .map(|terms| Waypoint::get_system_id(&terms.destination_symbol))
+        .collect();
+    let mut coordinates = HashMap::new();
+    for system_symbol in systems {
+        for waypoint in api.list_waypoints(system_symbol, None, None).await?.data {
+            coordinates.insert(waypoint.symbol, (waypoint.x, waypoint.y));
+        }
+    }
+    Ok(evaluate_contract(
+        &contract,
+        &market_history.latest()?,
+        &coordinates,
+        &ships,
+    ))
+}
@@ -0,0 +1,246 @@
+//! Local HTTP/JSON API to steer a running daemon, and the client the `daemon` commands use to
+//! talk to it.
+//!
+//! - `GET /ships` lists each ship with its behaviour and what it's doing
+//! - `PUT /ships/{symbol}/behaviour` with a behaviour as in `daemon.json` reassigns a ship
+//! - `POST /pause`, `POST /resume` and their `/ships/{symbol}/...` forms pause and resume
+//! - `GET /events?after={id}&wait={seconds}` returns recent events, waiting for new ones
+
+use std::{
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+    time::Duration as StdDuration,
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use reqwest::{Client, RequestBuilder};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{
+    daemon::{Behaviour, Event, Fleet, ShipStatus},
+    error::{AppError, AppResult},
+    signal::StopSignal,
+};
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+/// Longest a request for events is held open waiting for new ones
+const MAX_EVENTS_WAIT: StdDuration = StdDuration::from_secs(60);
+
+/// Serves the control API until the stop signal fires
+pub async fn serve(
+    listener: TcpListener,
+    fleet: Arc<Fleet>,
+    mut stop: StopSignal,
+) -> AppResult<()> {
+    listener.set_nonblocking(true)?;
+    let app = Router::new()
+        .route("/ships", get(list_ships))
+        .route("/ships/:ship_symbol/behaviour", put(assign))
+        .route("/ships/:ship_symbol/pause", post(pause_ship))
+        .route("/ships/:ship_symbol/resume", post(resume_ship))
+        .route("/pause", post(pause_all))
+        .route("/resume", post(resume_all))
+        .route("/events", get(events))
+        .with_state(fleet);
+    axum::Server::from_tcp(listener)
+        .map_err(std::io::Error::other)?
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(async move { stop.stopped().await })
+        .await
+        .map_err(std::io::Error::other)?;
+    Ok(())
+}
+
+struct ControlError(AppError);
+
+impl From<AppError> for ControlError {
+    fn from(e: AppError) -> Self {
+        ControlError(e)
+    }
+}
+
+impl IntoResponse for ControlError {
+    fn into_response(self) -> Response {
+        // the fleet's only usage error is a ship it doesn't drive
+        let status = match self.0 {
+            AppError::Usage(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = serde_json::json!({ "error": self.0.to_string() });
+        (status, Json(body)).into_response()
+    }
+}
+
+async fn list_ships(State(fleet): State<Arc<Fleet>>) -> Json<Vec<ShipStatus>> {
+    Json(fleet.status())
+}
+
+async fn assign(
+    State(fleet): State<Arc<Fleet>>,
+    Path(ship_symbol): Path<String>,
+    Json(behaviour): Json<Behaviour>,
+) -> Result<StatusCode, ControlError> {
+    fleet.assign(&ship_symbol, behaviour)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn pause_ship(
+    State(fleet): State<Arc<Fleet>>,
+    Path(ship_symbol): Path<String>,
+) -> Result<StatusCode, ControlError> {
+    fleet.set_paused(Some(&ship_symbol), true)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn resume_ship(
+    State(fleet): State<Arc<Fleet>>,
+    Path(ship_symbol): Path<String>,
+) -> Result<StatusCode, ControlError> {
+    fleet.set_paused(Some(&ship_symbol), false)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn pause_all(State(fleet): State<Arc<Fleet>>) -> Result<StatusCode, ControlError> {
+    fleet.set_paused(None, true)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn resume_all(State(fleet): State<Arc<Fleet>>) -> Result<StatusCode, ControlError> {
+    fleet.set_paused(None, false)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    #[serde(default)]
+    after: u64,
+    /// Seconds to wait for a new event when there are none after `after`
+    #[serde(default)]
+    wait: u64,
+}
+
+async fn events(
+    State(fleet): State<Arc<Fleet>>,
+    Query(query): Query<EventsQuery>,
+) -> Json<Vec<Event>> {
+    let wait = StdDuration::from_secs(query.wait).min(MAX_EVENTS_WAIT);
+    Json(fleet.events_after(query.after, wait).await)
+}
+
+/// Prints the recent events, then with `follow` keeps printing new ones until Ctrl-C
+pub async fn print_events(client: &ControlClient, follow: bool) -> AppResult<()> {
+    let mut stop = StopSignal::on_ctrl_c();
+    let mut after = 0;
+    let mut wait = StdDuration::ZERO;
+    loop {
+        let events = tokio::select! {
+            events = client.events(after, wait) => events?,
+            _ = stop.stopped() => return Ok(()),
+        };
+        for event in events.iter() {
+            println!("{}", event);
+        }
+        if !follow {
+            return Ok(());
+        }
+        after = events.last().map_or(after, |event| event.id);
+        wait = MAX_EVENTS_WAIT;
+    }
+}
+
+pub struct ControlClient {
+    client: Client,
+    addr: SocketAddr,
+}
+
+impl ControlClient {
+    pub fn new(addr: SocketAddr) -> Self {
+        ControlClient {
+            client: Client::new(),
+            addr,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{path}", self.addr)
+    }
+
+    pub async fn ships(&self) -> AppResult<Vec<ShipStatus>> {
+        self.fetch(self.client.get(self.url("/ships"))).await
+    }
+
+    pub async fn assign(&self, ship_symbol: &str, behaviour: &Behaviour) -> AppResult<()> {
+        let url = self.url(&format!("/ships/{ship_symbol}/behaviour"));
+        self.send(self.client.put(url).json(behaviour)).await
+    }
+
+    /// Pauses one ship, or every ship without a symbol
+    pub async fn pause(&self, ship_symbol: Option<&str>) -> AppResult<()> {
+        self.send(self.client.post(self.ship_url(ship_symbol, "pause")))
+            .await
+    }
+
+    pub async fn resume(&self, ship_symbol: Option<&str>) -> AppResult<()> {
+        self.send(self.client.post(self.ship_url(ship_symbol, "resume")))
+            .await
+    }
+
+    pub async fn events(&self, after: u64, wait: StdDuration) -> AppResult<Vec<Event>> {
+        let request = self
+            .client
+            .get(self.url("/events"))
+            .query(&[("after", after), ("wait", wait.as_secs())]);
+        self.fetch(request).await
+    }
+
+    fn ship_url(&self, ship_symbol: Option<&str>, action: &str) -> String {
+        match ship_symbol {
+            Some(ship_symbol) => self.url(&format!("/ships/{ship_symbol}/{action}")),
+            None => self.url(&format!("/{action}")),
+        }
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, request: RequestBuilder) -> AppResult<T> {
+        let response = self.request(request).await?;
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::Daemon(e.to_string()))
+    }
+
+    async fn send(&self, request: RequestBuilder) -> AppResult<()> {
+        self.request(request).await.map(|_| ())
+    }
+
+    async fn request(&self, request: RequestBuilder) -> AppResult<reqwest::Response> {
+        let response = request.send().await.map_err(|e| {
+            if e.is_connect() {
+                AppError::Daemon(format!(
+                    "no daemon is listening on {}, start one with `daemon run`",
+                    self.addr
+                ))
+            } else {
+                AppError::Daemon(e.to_string())
+            }
+        })?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|value| value["error"].as_str().map(str::to_owned))
+            .unwrap_or(body);
+        Err(match status {
+            StatusCode::NOT_FOUND => AppError::Usage(message),
+            _ => AppError::Daemon(message),
+        })
+    }
+}
@@ -0,0 +1,746 @@
+//! Long-running mode driving every assigned ship concurrently through one `Api`, so they all
+//! share the rate limiter. Behaviours read their progress back from the server, so the only
+//! state kept locally is which ship does what.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    net::{SocketAddr, TcpListener},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration as StdDuration,
+};
+
+use chrono::{DateTime, Utc};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::{
+    api::Api,
+    contract::{self, ContractRun, ContractRuns},
+    control,
+    domain::{Inventory, ShipNavStatus, TradeSymbol, WaypointTraitSymbol},
+    error::{AppError, AppResult},
+    market_history::MarketHistory,
+    mining::{self, MiningOptions},
+    navigation,
+    notes::Progress,
+    selling, ship_state,
+    signal::StopSignal,
+    trade::{self, TradeRouteSort},
+    Context,
+};
+
+const ASSIGNMENTS_FILE: &str = "daemon.json";
+/// How long a ship backs off after an error before trying its behaviour again
+const RETRY_DELAY: StdDuration = StdDuration::from_secs(30);
+/// How long a trader waits for the market history to turn up a profitable route
+const NO_ROUTE_DELAY: StdDuration = StdDuration::from_secs(300);
+/// How long a scout rests after visiting every market once
+const SCOUT_ROUND_DELAY: StdDuration = StdDuration::from_secs(60);
+/// How many events are kept for `daemon events`
+const EVENT_LOG_SIZE: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "behaviour",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Behaviour {
+    /// Mine at the asteroid field and sell the haul at the market, cycle after cycle
+    Mine {
+        asteroid_field: String,
+        market: String,
+        /// Goods to hold on to instead of selling
+        #[serde(default)]
+        keep: Vec<TradeSymbol>,
+    },
+    /// Run the most profitable trade route in the market history, trip after trip
+    Trade,
+    /// Deliver a contract's goods and fulfill it, then go idle
+    Haul {
+        contract_id: String,
+        buy_at: Option<String>,
+        mine_at: Option<String>,
+    },
+    /// Tour markets to keep the market history fresh, every marketplace in the ship's
+    /// system unless waypoints are given
+    Scout {
+        #[serde(default)]
+        waypoints: Vec<String>,
+    },
+    /// Stay where it is
+    Idle,
+}
+
+impl fmt::Display for Behaviour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Behaviour::Mine {
+                asteroid_field,
+                market,
+                ..
+            } => write!(f, "mine at {asteroid_field}, sell at {market}"),
+            Behaviour::Trade => write!(f, "trade"),
+            Behaviour::Haul {
+                contract_id,
+                buy_at,
+                mine_at,
+            } => {
+                write!(f, "haul contract {contract_id}")?;
+                match (buy_at, mine_at) {
+                    (Some(market), _) => write!(f, ", buying at {market}"),
+                    (None, Some(asteroid_field)) => write!(f, ", mining at {asteroid_field}"),
+                    (None, None) => Ok(()),
+                }
+            }
+            Behaviour::Scout { waypoints } if waypoints.is_empty() => {
+                write!(f, "scout the system's markets")
+            }
+            Behaviour::Scout { waypoints } => write!(f, "scout {}", waypoints.join(", ")),
+            Behaviour::Idle => write!(f, "idle"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BehaviourKind {
+    Mine,
+    Trade,
+    Haul,
+    Scout,
+    Idle,
+}
+
+/// A [`Behaviour`] as given on the command line
+#[derive(Debug, Args)]
+pub struct BehaviourArgs {
+    #[arg(short = 'b', long)]
+    pub behaviour: BehaviourKind,
+    /// Asteroid field to mine at (mine)
+    #[arg(long, required_if_eq("behaviour", "mine"))]
+    pub asteroid_field: Option<String>,
+    /// Market to sell the haul at (mine)
+    #[arg(long, required_if_eq("behaviour", "mine"))]
+    pub market: Option<String>,
+    /// Goods to keep in the hold instead of selling, may be repeated (mine)
+    #[arg(short = 'k', long)]
+    pub keep: Vec<TradeSymbol>,
+    /// Contract to deliver (haul)
+    #[arg(long, required_if_eq("behaviour", "haul"))]
+    pub contract_id: Option<String>,
+    /// Market to buy the contract goods at (haul)
+    #[arg(long, conflicts_with = "mine_at")]
+    pub buy_at: Option<String>,
+    /// Asteroid field to mine the contract goods at (haul)
+    #[arg(long)]
+    pub mine_at: Option<String>,
+    /// Market to visit, may be repeated, every marketplace in the system by default (scout)
+    #[arg(short = 'w', long = "waypoint")]
+    pub waypoints: Vec<String>,
+}
+
+/// Options clap requires for the behaviour are always there, the others are ignored
+impl From<BehaviourArgs> for Behaviour {
+    fn from(args: BehaviourArgs) -> Self {
+        match args.behaviour {
+            BehaviourKind::Mine => Behaviour::Mine {
+                asteroid_field: args.asteroid_field.unwrap_or_default(),
+                market: args.market.unwrap_or_default(),
+                keep: args.keep,
+            },
+            BehaviourKind::Trade => Behaviour::Trade,
+            BehaviourKind::Haul => Behaviour::Haul {
+                contract_id: args.contract_id.unwrap_or_default(),
+                buy_at: args.buy_at,
+                mine_at: args.mine_at,
+            },
+            BehaviourKind::Scout => Behaviour::Scout {
+                waypoints: args.waypoints,
+            },
+            BehaviourKind::Idle => Behaviour::Idle,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    /// Behaviour of each ship by symbol, ships left out aren't touched
+    #[serde(default)]
+    pub ships: BTreeMap<String, Behaviour>,
+}
+
+/// The daemon's config file. Behaviours that finish are written back to it, so a restarted
+/// daemon carries on where the last one left off.
+pub struct Assignments {
+    path: PathBuf,
+}
+
+impl Assignments {
+    pub fn open(dir: &Path) -> Self {
+        Assignments::at(dir.join(ASSIGNMENTS_FILE))
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Assignments { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self) -> std::io::Result<DaemonConfig> {
+        if !self.path.exists() {
+            return Ok(DaemonConfig::default());
+        }
+        let contents = fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, config: &DaemonConfig) -> std::io::Result<()> {
+        let file = File::create(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, config)?;
+        writer.flush()
+    }
+
+    pub fn assign(&self, ship_symbol: &str, behaviour: Behaviour) -> std::io::Result<()> {
+        let mut config = self.load()?;
+        config.ships.insert(ship_symbol.to_owned(), behaviour);
+        self.save(&config)
+    }
+}
+
+/// What a ship has been told to do, changed through the control API while the daemon runs
+#[derive(Debug, Clone, PartialEq)]
+struct ShipControl {
+    behaviour: Behaviour,
+    paused: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipStatus {
+    pub ship_symbol: String,
+    pub behaviour: Behaviour,
+    pub paused: bool,
+    /// What the ship's driver is up to: running, paused, retrying or stopped
+    pub activity: String,
+}
+
+impl fmt::Display for ShipStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<14} {:<9} {}",
+            self.ship_symbol, self.activity, self.behaviour
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    /// Increases by one per event, so clients can ask for everything after the last they saw
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Unset for events about the whole fleet
+    pub ship_symbol: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.timestamp.format("%H:%M:%S"))?;
+        if let Some(ship_symbol) = &self.ship_symbol {
+            write!(f, "{ship_symbol}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+struct EventLog {
+    recent: VecDeque<Event>,
+    last_id: u64,
+}
+
+/// The daemon's shared state, driven by the ships and steered through the control API
+pub struct Fleet {
+    assignments: Assignments,
+    controls: BTreeMap<String, watch::Sender<ShipControl>>,
+    activities: Mutex<BTreeMap<String, String>>,
+    events: watch::Sender<EventLog>,
+}
+
+impl Fleet {
+    fn new(assignments: Assignments, ships: BTreeMap<String, Behaviour>) -> Self {
+        let controls = ships
+            .into_iter()
+            .map(|(ship_symbol, behaviour)| {
+                let control = ShipControl {
+                    behaviour,
+                    paused: false,
+                };
+                (ship_symbol, watch::channel(control).0)
+            })
+            .collect();
+        Fleet {
+            assignments,
+            controls,
+            activities: Mutex::default(),
+            events: watch::channel(EventLog::default()).0,
+        }
+    }
+
+    fn control(&self, ship_symbol: &str) -> AppResult<&watch::Sender<ShipControl>> {
+        self.controls.get(ship_symbol).ok_or_else(|| {
+            AppError::Usage(format!("{ship_symbol} isn't one of the daemon's ships"))
+        })
+    }
+
+    pub fn status(&self) -> Vec<ShipStatus> {
+        let activities = self.activities.lock().unwrap();
+        self.controls
+            .iter()
+            .map(|(ship_symbol, control)| {
+                let control = control.borrow();
+                ShipStatus {
+                    ship_symbol: ship_symbol.clone(),
+                    behaviour: control.behaviour.clone(),
+                    paused: control.paused,
+                    activity: activities
+                        .get(ship_symbol)
+                        .cloned()
+                        .unwrap_or_else(|| String::from("starting")),
+                }
+            })
+            .collect()
+    }
+
+    /// Switches the ship over once its current step is done and saves the assignment
+    pub fn assign(&self, ship_symbol: &str, behaviour: Behaviour) -> AppResult<()> {
+        let control = self.control(ship_symbol)?;
+        self.assignments.assign(ship_symbol, behaviour.clone())?;
+        control.send_modify(|control| control.behaviour = behaviour);
+        Ok(())
+    }
+
+    /// Pauses or resumes one ship, or every ship without a symbol
+    pub fn set_paused(&self, ship_symbol: Option<&str>, paused: bool) -> AppResult<()> {
+        let controls = match ship_symbol {
+            Some(ship_symbol) => vec![self.control(ship_symbol)?],
+            None => self.controls.values().collect(),
+        };
+        for control in controls {
+            control.send_if_modified(|control| {
+                let changed = control.paused != paused;
+                control.paused = paused;
+                changed
+            });
+        }
+        Ok(())
+    }
+
+    fn set_activity(&self, ship_symbol: &str, activity: &str) {
+        self.activities
+            .lock()
+            .unwrap()
+            .insert(ship_symbol.to_owned(), activity.to_owned());
+    }
+
+    /// Prints the event and keeps it for clients following along
+    pub fn log(&self, ship_symbol: Option<&str>, message: String) {
+        self.events.send_modify(|log| {
+            log.last_id += 1;
+            let event = Event {
+                id: log.last_id,
+                timestamp: Utc::now(),
+                ship_symbol: ship_symbol.map(str::to_owned),
+                message,
+            };
+            println!("{event}");
+            if log.recent.len() == EVENT_LOG_SIZE {
+                log.recent.pop_front();
+            }
+            log.recent.push_back(event);
+        });
+    }
+
+    /// Recent events after `after`, waiting up to `wait` for one if there are none yet
+    pub async fn events_after(&self, after: u64, wait: StdDuration) -> Vec<Event> {
+        let mut events = self.events.subscribe();
+        let _ = tokio::time::timeout(wait, events.wait_for(|log| log.last_id > after)).await;
+        let log = self.events.borrow();
+        log.recent
+            .iter()
+            .filter(|event| event.id > after)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Drives every assigned ship until Ctrl-C or SIGTERM, letting each finish its current step,
+/// and serves the control API alongside
+pub async fn run(
+    ctx: &Context<'_>,
+    assignments: Assignments,
+    control_addr: SocketAddr,
+) -> AppResult<()> {
+    let ships = assignments.load()?.ships;
+    if ships.is_empty() {
+        return Err(AppError::Usage(format!(
+            "no ships assigned, add them to {}",
+            assignments.path().display()
+        )));
+    }
+    let listener = TcpListener::bind(control_addr)?;
+
+    let stop = StopSignal::on_shutdown();
+    eprintln!(
+        "daemon running {} ships, Ctrl-C or SIGTERM to stop",
+        ships.len()
+    );
+    eprintln!("control API listening on http://{}", listener.local_addr()?);
+    let fleet = Arc::new(Fleet::new(assignments, ships));
+    let drivers = fleet.controls.iter().map(|(ship_symbol, control)| {
+        drive(
+            ctx,
+            &fleet,
+            ship_symbol.clone(),
+            control.subscribe(),
+            stop.clone(),
+        )
+    });
+    let (_, served) = tokio::join!(
+        futures::future::join_all(drivers),
+        control::serve(listener, fleet.clone(), stop.clone())
+    );
+    served?;
+    eprintln!("daemon stopped");
+    Ok(())
+}
+
+/// Runs one ship's behaviour until shut down. A new assignment or a pause stops the current
+/// behaviour at its next safe point, just like a shutdown. Errors are logged and retried after
+/// a delay so one ship's trouble doesn't take the others down.
+async fn drive(
+    ctx: &Context<'_>,
+    fleet: &Fleet,
+    ship_symbol: String,
+    mut control: watch::Receiver<ShipControl>,
+    mut shutdown: StopSignal,
+) {
+    let ship = Some(ship_symbol.as_str());
+    let mut logged: Option<ShipControl> = None;
+    while !shutdown.is_stopped() {
+        let current = control.borrow_and_update().clone();
+        if logged.as_ref() != Some(&current) {
+            match current.paused {
+                true => fleet.log(ship, String::from("paused")),
+                false => fleet.log(ship, current.behaviour.to_string()),
+            }
+            logged = Some(current.clone());
+        }
+        if current.paused {
+            fleet.set_activity(&ship_symbol, "paused");
+            interrupted(&mut shutdown, &mut control).await;
+            continue;
+        }
+
+        fleet.set_activity(&ship_symbol, "running");
+        let (handle, mut stop) = StopSignal::new();
+        let progress = |message: String| fleet.log(ship, message);
+        let run = run_behaviour(ctx, &ship_symbol, &current.behaviour, &mut stop, &progress);
+        tokio::pin!(run);
+        let result = tokio::select! {
+            result = &mut run => result,
+            _ = interrupted(&mut shutdown, &mut control) => {
+                handle.stop();
+                run.await
+            }
+        };
+        match result {
+            Ok(Some(next)) => {
+                if let Err(e) = fleet.assign(&ship_symbol, next) {
+                    fleet.log(ship, format!("couldn't save the new assignment: {e}"));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                fleet.set_activity(&ship_symbol, "retrying");
+                fleet.log(
+                    ship,
+                    format!("error: {e}, retrying in {}s", RETRY_DELAY.as_secs()),
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(RETRY_DELAY) => {}
+                    _ = interrupted(&mut shutdown, &mut control) => {}
+                }
+            }
+        }
+    }
+    fleet.set_activity(&ship_symbol, "stopped");
+    fleet.log(ship, String::from("stopped"));
+}
+
+/// Resolves on shutdown or when the ship's control changes
+async fn interrupted(shutdown: &mut StopSignal, control: &mut watch::Receiver<ShipControl>) {
+    tokio::select! {
+        _ = shutdown.stopped() => {}
+        changed = control.changed() => {
+            if changed.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+/// Returns the behaviour to switch to once this one is done, or `None` if it was stopped. What
+/// the ship does along the way goes to the event log through `progress`.
+async fn run_behaviour(
+    ctx: &Context<'_>,
+    ship_symbol: &str,
+    behaviour: &Behaviour,
+    stop: &mut StopSignal,
+    progress: Progress<'_>,
+) -> AppResult<Option<Behaviour>> {
+    let api = &ctx.api;
+    let market_history = &ctx.market_history;
+    match behaviour {
+        Behaviour::Mine {
+            asteroid_field,
+            market,
+            keep,
+        } => {
+            let options = MiningOptions {
+                ship_symbol: ship_symbol.to_owned(),
+                asteroid_field: asteroid_field.clone(),
+                market: market.clone(),
+                keep: keep.clone(),
+                cycles: None,
+            };
+            mining::mine(api, &options, stop.clone(), progress).await?;
+        }
+        Behaviour::Trade => trade(api, market_history, ship_symbol, stop, progress).await?,
+        Behaviour::Haul {
+            contract_id,
+            buy_at,
+            mine_at,
+        } => {
+            let run = ContractRun {
+                contract_id: contract_id.clone(),
+                ship_symbol: ship_symbol.to_owned(),
+                buy_at: buy_at.clone(),
+                mine_at: mine_at.clone(),
+            };
+            let runs = ContractRuns::open(&ctx.config.current_user_dir);
+            if contract::run(api, &runs, market_history, &run, stop.clone(), progress).await? {
+                return Ok(Some(Behaviour::Idle));
+            }
+        }
+        Behaviour::Scout { waypoints } => {
+            scout(api, ship_symbol, waypoints, stop, progress).await?
+        }
+        Behaviour::Idle => stop.stopped().await,
+    }
+    Ok(None)
+}
+
+/// Buys along the best known route and sells at the best known market. Goods already in the
+/// hold are sold first, which is also how an interrupted trip is picked up again.
+async fn trade(
+    api: &Api<'_>,
+    market_history: &MarketHistory,
+    ship_symbol: &str,
+    stop: &mut StopSignal,
+    progress: Progress<'_>,
+) -> AppResult<()> {
+    while !stop.is_stopped() {
+        let cargo = api.get_ship_cargo(ship_symbol.to_owned()).await?.data;
+        if let Some(item) = cargo.inventory.first() {
+            let Some(market) = best_sell_market(market_history, &item.symbol)? else {
+                jettison(api, ship_symbol, item, "no known market buys it", progress).await?;
+                continue;
+            };
+            if !navigation::travel_to(api, ship_symbol, &market, stop).await? {
+                return Ok(());
+            }
+            // anything else left over heads for its own best market next time round, but this
+            // was already the best one for this good
+            let unsold = sell_all(api, ship_symbol, &market, progress).await?;
+            if let Some(item) = unsold.iter().find(|unsold| unsold.symbol == item.symbol) {
+                let reason = format!("{market} doesn't buy it");
+                jettison(api, ship_symbol, item, &reason, progress).await?;
+            }
+            continue;
+        }
+
+        let route = trade::routes(
+            api,
+            market_history,
+            ship_symbol.to_owned(),
+            TradeRouteSort::Minute,
+        )
+        .await?
+        .into_iter()
+        .next();
+        let Some(route) = route else {
+            progress(format!(
+                "no profitable route in the market history, checking again in {}s",
+                NO_ROUTE_DELAY.as_secs()
+            ));
+            stop.sleep(NO_ROUTE_DELAY).await;
+            continue;
+        };
+        progress(route.to_string());
+        if !navigation::travel_to(api, ship_symbol, &route.buy_waypoint, stop).await? {
+            return Ok(());
+        }
+        ship_state::ensure_state(api, ship_symbol, ShipNavStatus::DOCKED).await?;
+        let market = api.get_market(route.buy_waypoint.clone()).await?.data;
+        let good = TradeSymbol::from_symbol(&route.trade_symbol);
+        let trade_volume = market
+            .trade_goods
+            .iter()
+            .find(|g| g.symbol == route.trade_symbol)
+            .map_or(1, |g| g.trade_volume.max(1));
+
+        // markets only sell up to their trade volume per transaction
+        let mut remaining = route
+            .units_per_trip
+            .min(cargo.capacity - cargo.units)
+            .max(0) as u32;
+        while remaining > 0 {
+            let units = remaining.min(trade_volume);
+            let res = api
+                .purchase_cargo(ship_symbol.to_owned(), good.clone(), units)
+                .await?
+                .data;
+            progress(format!(
+                "bought {units} {good} for {}",
+                res.transaction.total_price
+            ));
+            remaining -= units;
+        }
+    }
+    Ok(())
+}
+
+/// The market with the highest recorded sell price for the good. Prices are only recorded for
+/// goods a market trades, so `sell_all` can sell there.
+fn best_sell_market(
+    market_history: &MarketHistory,
+    trade_symbol: &str,
+) -> AppResult<Option<String>> {
+    Ok(market_history
+        .latest()?
+        .into_iter()
+        .filter(|record| record.trade_symbol == trade_symbol)
+        .max_by_key(|record| record.sell_price)
+        .map(|record| record.waypoint_symbol))
+}
+
+/// Dumps cargo there's no market for, so it doesn't hold up trading for good
+async fn jettison(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    item: &Inventory,
+    reason: &str,
+    progress: Progress<'_>,
+) -> AppResult<()> {
+    selling::jettison(api, ship_symbol, item).await?;
+    progress(format!(
+        "jettisoned {} {}, {reason}",
+        item.units, item.symbol
+    ));
+    Ok(())
+}
+
+/// Sells everything the market trades and tops up the tank if fuel is sold there, returning
+/// the cargo it doesn't buy
+async fn sell_all(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    market_symbol: &str,
+    progress: Progress<'_>,
+) -> AppResult<Vec<Inventory>> {
+    ship_state::ensure_state(api, ship_symbol, ShipNavStatus::DOCKED).await?;
+    let market = api.get_market(market_symbol.to_owned()).await?.data;
+
+    let sale = selling::sell_hold(api, ship_symbol, &market, &[]).await?;
+    for transaction in sale.transactions.iter() {
+        progress(format!(
+            "sold {} {} for {}",
+            transaction.units, transaction.trade_symbol, transaction.total_price
+        ));
+    }
+    for item in sale.unsold.iter() {
+        progress(format!(
+            "keeping {} {}, {market_symbol} doesn't buy it",
+            item.units, item.symbol
+        ));
+    }
+
+    let sells_fuel = market.trade_goods.iter().any(|g| g.symbol == "FUEL");
+    let ship = api.get_ship_status(ship_symbol.to_owned()).await?.data;
+    if sells_fuel && ship.fuel.current < ship.fuel.capacity {
+        api.refuel_ship(ship_symbol.to_owned(), None).await?;
+    }
+    Ok(sale.unsold)
+}
+
+/// Visits the markets in turn, starting from wherever the ship is so a restart doesn't send
+/// it back to the first one
+async fn scout(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    waypoints: &[String],
+    stop: &mut StopSignal,
+    progress: Progress<'_>,
+) -> AppResult<()> {
+    let nav = api.get_ship_nav_status(ship_symbol.to_owned()).await?.data;
+    let waypoints = if waypoints.is_empty() {
+        api.list_waypoints(
+            nav.system_symbol.clone(),
+            Some(WaypointTraitSymbol::MARKETPLACE),
+            None,
+        )
+        .await?
+        .data
+        .into_iter()
+        .map(|waypoint| waypoint.symbol)
+        .collect()
+    } else {
+        waypoints.to_vec()
+    };
+    if waypoints.is_empty() {
+        return Err(AppError::Usage(format!(
+            "there are no markets to scout in {}",
+            nav.system_symbol
+        )));
+    }
+
+    let start = waypoints
+        .iter()
+        .position(|waypoint| *waypoint == nav.route.destination.symbol)
+        .unwrap_or(0);
+    for (visited, waypoint) in waypoints.iter().cycle().skip(start).enumerate() {
+        if !navigation::travel_to(api, ship_symbol, waypoint, stop).await? {
+            break;
+        }
+        let market = api.get_market(waypoint.clone()).await?.data;
+        progress(format!(
+            "recorded {} prices at {waypoint}",
+            market.trade_goods.len()
+        ));
+        if (visited + 1) % waypoints.len() == 0 && !stop.sleep(SCOUT_ROUND_DELAY).await {
+            break;
+        }
+    }
+    Ok(())
+}
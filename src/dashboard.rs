@@ -0,0 +1,451 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Stdout},
+    time::{Duration as StdDuration, Instant},
+};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    api::Api,
+    domain::{format_duration, Agent, Contract, Cooldown, MarketTransaction, Ship, ShipNavStatus},
+    error::AppResult,
+    ledger::{Ledger, LedgerEntry},
+    notes, selling, ship_state,
+};
+
+/// Refreshing costs three requests plus one per ship, so this stays well inside the rate limit
+/// for fleets of a dozen or so
+const REFRESH_INTERVAL: StdDuration = StdDuration::from_secs(15);
+const INPUT_POLL: StdDuration = StdDuration::from_millis(250);
+const LOG_LINES: usize = 50;
+const BAR_WIDTH: usize = 10;
+
+#[derive(Default)]
+pub struct DashboardState {
+    pub agent: Option<Agent>,
+    pub ships: Vec<Ship>,
+    pub cooldowns: HashMap<String, Cooldown>,
+    pub contracts: Vec<Contract>,
+    /// Most recent first
+    pub log: VecDeque<String>,
+    pub selected: TableState,
+    pub status: String,
+}
+
+impl DashboardState {
+    pub fn selected_ship(&self) -> Option<&Ship> {
+        self.selected.selected().and_then(|i| self.ships.get(i))
+    }
+
+    pub fn push_log(&mut self, line: String) {
+        self.log.push_front(line);
+        self.log.truncate(LOG_LINES);
+    }
+
+    /// Fills the log with the latest ledger entries, so it doesn't start out empty
+    pub fn seed_log(&mut self, entries: &[LedgerEntry]) {
+        let start = entries.len().saturating_sub(LOG_LINES);
+        for entry in &entries[start..] {
+            self.log_entry(entry);
+        }
+    }
+
+    fn log_entry(&mut self, entry: &LedgerEntry) {
+        self.push_log(format!(
+            "{} {} {:?} {} x{} = {}",
+            entry.timestamp.format("%H:%M:%S"),
+            entry.ship_symbol,
+            entry.kind,
+            entry.item,
+            entry.units,
+            entry.total_price
+        ));
+    }
+
+    fn log_transaction(&mut self, transaction: &MarketTransaction) {
+        self.log_entry(&LedgerEntry::from_market_transaction(transaction));
+    }
+
+    fn select_next(&mut self, step: isize) {
+        if self.ships.is_empty() {
+            self.selected.select(None);
+            return;
+        }
+        let len = self.ships.len() as isize;
+        let current = self.selected.selected().unwrap_or(0) as isize;
+        self.selected
+            .select(Some((current + step).rem_euclid(len) as usize));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Dock,
+    Orbit,
+    Refuel,
+    Sell,
+}
+
+/// Puts the terminal back the way it was, even if the dashboard bails out with an error
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// Reads keys on a blocking thread, so waiting for input doesn't hold up a runtime worker.
+/// Stops once the receiver is dropped.
+fn spawn_key_reader() -> mpsc::UnboundedReceiver<io::Result<KeyEvent>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::task::spawn_blocking(move || {
+        while !tx.is_closed() {
+            let key = match event::poll(INPUT_POLL) {
+                Ok(false) => continue,
+                Ok(true) => match event::read() {
+                    Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => Ok(key),
+                    Ok(_) => continue,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            let failed = key.is_err();
+            if tx.send(key).is_err() || failed {
+                return;
+            }
+        }
+    });
+    rx
+}
+
+pub async fn run(api: &Api<'_>, ledger: &Ledger, auto_state: bool) -> AppResult<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let _guard = TerminalGuard;
+    let mut notes = notes::capture();
+    let mut terminal: Terminal<CrosstermBackend<Stdout>> =
+        Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let mut state = DashboardState::default();
+    match ledger.all() {
+        Ok(entries) => state.seed_log(&entries),
+        Err(e) => state.push_log(format!("could not read the ledger: {e}")),
+    }
+    let mut keys = spawn_key_reader();
+    let mut last_refresh: Option<Instant> = None;
+    loop {
+        if last_refresh.is_none_or(|at| at.elapsed() >= REFRESH_INTERVAL) {
+            state.status = String::from("refreshing...");
+            terminal.draw(|frame| draw(frame, &mut state))?;
+            refresh(api, &mut state).await;
+            last_refresh = Some(Instant::now());
+        }
+        for note in notes.drain() {
+            state.push_log(note);
+        }
+        terminal.draw(|frame| draw(frame, &mut state))?;
+
+        let key = match tokio::time::timeout(INPUT_POLL, keys.recv()).await {
+            Err(_) => continue,
+            Ok(None) => break,
+            Ok(Some(key)) => key?,
+        };
+        let action = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Down | KeyCode::Char('j') => {
+                state.select_next(1);
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.select_next(-1);
+                None
+            }
+            KeyCode::Char('r') => {
+                last_refresh = None;
+                None
+            }
+            KeyCode::Char('d') => Some(Action::Dock),
+            KeyCode::Char('o') => Some(Action::Orbit),
+            KeyCode::Char('f') => Some(Action::Refuel),
+            KeyCode::Char('s') => Some(Action::Sell),
+            _ => None,
+        };
+        if let Some(action) = action {
+            state.status = format!("{action:?}...");
+            terminal.draw(|frame| draw(frame, &mut state))?;
+            perform(api, &mut state, action, auto_state).await;
+        }
+    }
+    Ok(())
+}
+
+/// Reloads everything shown. Failures end up in the status line rather than closing the
+/// dashboard.
+async fn refresh(api: &Api<'_>, state: &mut DashboardState) {
+    let result: AppResult<()> = async {
+        state.agent = Some(api.fetch_agent_info().await?.data);
+        state.ships = api.list_ships().await?.data;
+        state.contracts = api
+            .fetch_contracts()
+            .await?
+            .data
+            .into_iter()
+            .filter(|contract| contract.accepted && !contract.fulfilled)
+            .collect();
+        state.cooldowns.clear();
+        for ship in state.ships.iter() {
+            if let Some(cooldown) = api.get_ship_cooldown(ship.symbol.clone()).await?.data {
+                state.cooldowns.insert(ship.symbol.clone(), cooldown);
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    if state
+        .selected
+        .selected()
+        .is_none_or(|i| i >= state.ships.len())
+    {
+        state
+            .selected
+            .select((!state.ships.is_empty()).then_some(0));
+    }
+    state.status = match result {
+        Ok(()) => format!("refreshed at {}", chrono::Local::now().format("%H:%M:%S")),
+        Err(e) => format!("refresh failed: {e}"),
+    };
+}
+
+async fn perform(api: &Api<'_>, state: &mut DashboardState, action: Action, auto_state: bool) {
+    let Some(ship_symbol) = state.selected_ship().map(|ship| ship.symbol.clone()) else {
+        state.status = String::from("no ship selected");
+        return;
+    };
+    let result: AppResult<()> = async {
+        match action {
+            Action::Dock => {
+                api.dock_ship(ship_symbol.clone()).await?;
+            }
+            Action::Orbit => {
+                api.orbit_ship(ship_symbol.clone()).await?;
+            }
+            Action::Refuel => {
+                if auto_state {
+                    ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::DOCKED).await?;
+                }
+                let res = api.refuel_ship(ship_symbol.clone(), None).await?;
+                state.log_transaction(&res.data.transaction);
+            }
+            Action::Sell => {
+                if auto_state {
+                    ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::DOCKED).await?;
+                }
+                let nav = api.get_ship_nav_status(ship_symbol.clone()).await?.data;
+                let market = api.get_market(nav.waypoint_symbol).await?.data;
+                let sale = selling::sell_hold(api, &ship_symbol, &market, &[]).await?;
+                for transaction in sale.transactions.iter() {
+                    state.log_transaction(transaction);
+                }
+                for item in sale.unsold {
+                    state.push_log(format!(
+                        "kept {} {}, {} doesn't buy it",
+                        item.units, item.symbol, market.symbol
+                    ));
+                }
+            }
+        }
+        // only the acted-on ship changed, no need for a full refresh
+        let ship = api.get_ship_status(ship_symbol.clone()).await?.data;
+        if let Some(slot) = state.ships.iter_mut().find(|s| s.symbol == ship.symbol) {
+            *slot = ship;
+        }
+        Ok(())
+    }
+    .await;
+
+    state.status = match result {
+        Ok(()) => format!("{action:?} {ship_symbol} done"),
+        Err(e) => {
+            state.push_log(format!("{action:?} {ship_symbol} failed: {e}"));
+            format!("{action:?} {ship_symbol} failed")
+        }
+    };
+}
+
+/// A text gauge like `[#####-----]`
+pub fn bar(current: i32, capacity: i32, width: usize) -> String {
+    let filled = if capacity > 0 {
+        ((current.clamp(0, capacity) as f64 / capacity as f64) * width as f64).round() as usize
+    } else {
+        0
+    };
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+pub fn draw(frame: &mut Frame, state: &mut DashboardState) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(10),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[2]);
+
+    draw_agent(frame, state, rows[0]);
+    draw_ships(frame, state, rows[1]);
+    draw_contracts(frame, state, bottom[0]);
+    draw_log(frame, state, bottom[1]);
+    frame.render_widget(
+        Paragraph::new(format!(
+            "q quit  j/k select  r refresh  d dock  o orbit  f refuel  s sell  | {}",
+            state.status
+        )),
+        rows[3],
+    );
+}
+
+fn draw_agent(frame: &mut Frame, state: &DashboardState, area: Rect) {
+    let text = match &state.agent {
+        Some(agent) => format!(
+            "{}  credits {}  ships {}  hq {}",
+            agent.symbol,
+            agent.credits,
+            state.ships.len(),
+            agent.headquarters
+        ),
+        None => String::from("loading..."),
+    };
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Agent")),
+        area,
+    );
+}
+
+fn draw_ships(frame: &mut Frame, state: &mut DashboardState, area: Rect) {
+    let rows: Vec<Row> = state
+        .ships
+        .iter()
+        .map(|ship| {
+            let nav = match &ship.nav.status {
+                ShipNavStatus::IN_TRANSIT if !ship.nav.route.has_arrived() => format!(
+                    "-> {} {}",
+                    ship.nav.route.destination.symbol,
+                    ship.nav.route.arrival_description()
+                ),
+                status => format!("{:?} {}", status, ship.nav.waypoint_symbol),
+            };
+            let cooldown = match state.cooldowns.get(&ship.symbol) {
+                Some(cooldown) if !cooldown.is_expired() => {
+                    format_duration(cooldown.time_remaining())
+                }
+                _ => String::from("ready"),
+            };
+            Row::new(vec![
+                Cell::from(ship.symbol.clone()),
+                Cell::from(nav),
+                Cell::from(format!(
+                    "{} {}/{}",
+                    bar(ship.fuel.current, ship.fuel.capacity, BAR_WIDTH),
+                    ship.fuel.current,
+                    ship.fuel.capacity
+                )),
+                Cell::from(format!(
+                    "{} {}/{}",
+                    bar(ship.cargo.units, ship.cargo.capacity, BAR_WIDTH),
+                    ship.cargo.units,
+                    ship.cargo.capacity
+                )),
+                Cell::from(cooldown),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Min(20),
+            Constraint::Length(22),
+            Constraint::Length(20),
+            Constraint::Length(9),
+        ],
+    )
+    .header(
+        Row::new(vec!["Ship", "Nav", "Fuel", "Cargo", "Cooldown"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .block(Block::default().borders(Borders::ALL).title("Ships"));
+    frame.render_stateful_widget(table, area, &mut state.selected);
+}
+
+fn draw_contracts(frame: &mut Frame, state: &DashboardState, area: Rect) {
+    let mut lines = Vec::new();
+    for contract in state.contracts.iter() {
+        lines.push(Line::from(format!(
+            "{} due in {}",
+            contract.id,
+            format_duration(contract.time_until_deadline())
+        )));
+        for terms in contract.terms.deliver.iter() {
+            lines.push(Line::from(format!(
+                "  {} {} {}/{} -> {}",
+                terms.trade_symbol,
+                bar(terms.units_fulfilled, terms.units_required, BAR_WIDTH),
+                terms.units_fulfilled,
+                terms.units_required,
+                terms.destination_symbol
+            )));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from("no active contracts"));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Contracts")),
+        area,
+    );
+}
+
+fn draw_log(frame: &mut Frame, state: &DashboardState, area: Rect) {
+    let lines: Vec<Line> = state.log.iter().map(|l| Line::from(l.as_str())).collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Recent transactions"),
+        ),
+        area,
+    );
+}
+
+/// Draws a single frame onto any backend, used to check the layout without a real terminal
+pub fn render<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut DashboardState,
+) -> io::Result<()> {
+    terminal.draw(|frame| draw(frame, state))?;
+    Ok(())
+}
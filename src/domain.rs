@@ -1,13 +1,41 @@
+use std::{collections::BTreeSet, sync::Mutex};
+
+use chrono::{DateTime, Duration, Utc};
 use clap::ValueEnum;
-use serde::{Deserialize, Serialize};
-use strum_macros::Display;
+use serde::{Deserialize, Deserializer, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::notes;
+
+/// Deserializes the `Unknown(String)` variant every API enum ends with, so a value the
+/// server added doesn't fail the whole response. Warns once per value.
+fn unknown_variant<'de, T, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+    let value = String::deserialize(deserializer)?;
+    let type_name = std::any::type_name::<T>()
+        .rsplit("::")
+        .next()
+        .unwrap_or_default();
+    if WARNED
+        .lock()
+        .unwrap()
+        .insert(format!("{type_name}::{value}"))
+    {
+        notes::note(format!(
+            "warning: unknown {type_name} `{value}` from the API, update to handle it"
+        ));
+    }
+    Ok(value)
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Agent {
+    /// Only sent for our own agent
+    pub account_id: Option<String>,
     pub symbol: String,
     pub headquarters: String,
-    pub credits: i32,
+    pub credits: i64,
     pub starting_faction: String,
     pub ship_count: Option<i32>,
 }
@@ -29,7 +57,7 @@ impl Agent {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RegisterResponse {
     pub token: String,
     pub agent: Agent,
@@ -37,49 +65,49 @@ pub struct RegisterResponse {
 
 pub type MyContractsResponse = Vec<Contract>;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AcceptContractResponse {
     pub agent: Agent,
     pub contract: Contract,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PurchaseShipResponse {
     pub agent: Agent,
     pub ship: Ship,
-    pub transaction: Transaction,
+    pub transaction: ShipyardTransaction,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipOrbitResponse {
     pub nav: ShipNav,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipNavigateResponse {
     pub fuel: ShipFuel,
     pub nav: ShipNav,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipDockResponse {
     pub nav: ShipNav,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipRefuelResponse {
-    agent: Agent,
-    fuel: ShipFuel,
-    transaction: MarketTransaction,
+    pub agent: Agent,
+    pub fuel: ShipFuel,
+    pub transaction: MarketTransaction,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExtractResourceResponse {
     pub cooldown: Cooldown,
@@ -87,7 +115,14 @@ pub struct ExtractResourceResponse {
     pub cargo: ShipCargo,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSurveyResponse {
+    pub cooldown: Cooldown,
+    pub surveys: Vec<Survey>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SellCargoResponse {
     pub agent: Agent,
@@ -95,14 +130,23 @@ pub struct SellCargoResponse {
     pub transaction: MarketTransaction,
 }
 
-#[derive(Debug, Deserialize)]
+/// Purchases come back in the same shape as sales
+pub type PurchaseCargoResponse = SellCargoResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JettisonCargoResponse {
+    pub cargo: ShipCargo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeliverCargoResponse {
     pub cargo: ShipCargo,
     pub contract: Contract,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FulfillContractResponse {
     pub agent: Agent,
@@ -111,7 +155,7 @@ pub struct FulfillContractResponse {
 
 // ---------------------------------------------
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Contract {
     pub id: String,
@@ -121,26 +165,34 @@ pub struct Contract {
     pub terms: ContractTerms,
     pub accepted: bool,
     pub fulfilled: bool,
-    pub expiration: String,
-    pub deadline_to_accept: String,
+    /// Deprecated in the spec, the same as `deadline_to_accept`
+    pub expiration: DateTime<Utc>,
+    pub deadline_to_accept: Option<DateTime<Utc>>,
+}
+
+impl Contract {
+    pub fn time_until_deadline(&self) -> Duration {
+        time_until(self.terms.deadline)
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ContractTerms {
-    pub deadline: String,
+    pub deadline: DateTime<Utc>,
     pub payment: PaymentTerms,
+    #[serde(default)]
     pub deliver: Vec<DeliverTerms>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentTerms {
     pub on_accepted: i32,
     pub on_fulfilled: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeliverTerms {
     pub trade_symbol: String,
@@ -159,9 +211,17 @@ pub struct Waypoint {
     pub x: i32,
     pub y: i32,
     pub orbitals: Vec<Orbital>,
-    pub faction: Faction,
+    /// The waypoint this one orbits, if any
+    pub orbits: Option<String>,
+    /// Unclaimed waypoints have no faction
+    pub faction: Option<Faction>,
     pub traits: Vec<WaypointTrait>,
-    pub chart: Chart,
+    #[serde(default)]
+    pub modifiers: Vec<WaypointModifier>,
+    /// Missing until someone charts the waypoint
+    pub chart: Option<Chart>,
+    #[serde(default)]
+    pub is_under_construction: bool,
 }
 
 impl Waypoint {
@@ -179,9 +239,18 @@ pub enum WaypointType {
     ORBITAL_STATION,
     JUMP_GATE,
     ASTEROID_FIELD,
+    ASTEROID,
+    ENGINEERED_ASTEROID,
+    ASTEROID_BASE,
     NEBULA,
     DEBRIS_FIELD,
     GRAVITY_WELL,
+    ARTIFICIAL_GRAVITY_WELL,
+    FUEL_STATION,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<WaypointType, _>")]
+    #[value(skip)]
+    Unknown(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -191,16 +260,42 @@ pub struct Orbital {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Faction {
-    // TODO: convert this to an enum
-    pub symbol: String,
+    pub symbol: FactionSymbol,
+}
+
+#[derive(Display, PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub enum FactionSymbol {
+    COSMIC,
+    VOID,
+    GALACTIC,
+    QUANTUM,
+    DOMINION,
+    ASTRO,
+    CORSAIRS,
+    OBSIDIAN,
+    AEGIS,
+    UNITED,
+    SOLITARY,
+    COBALT,
+    OMEGA,
+    ECHO,
+    LORDS,
+    CULT,
+    ANCIENTS,
+    SHADOW,
+    ETHEREAL,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<FactionSymbol, _>")]
+    #[strum(default)]
+    Unknown(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Chart {
     pub waypoint_symbol: Option<String>,
-    pub submitted_by: String,
-    pub submitted_on: String,
+    pub submitted_by: Option<String>,
+    pub submitted_on: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -211,32 +306,46 @@ pub struct WaypointTrait {
     pub description: String,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Deserialize, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WaypointModifier {
+    pub symbol: WaypointModifierSymbol,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub enum WaypointModifierSymbol {
+    STRIPPED,
+    UNSTABLE,
+    RADIATION_LEAK,
+    CRITICAL_LIMIT,
+    CIVIL_UNREST,
+    /// A value added on the server since, kept as sent
+    #[serde(
+        untagged,
+        deserialize_with = "unknown_variant::<WaypointModifierSymbol, _>"
+    )]
+    Unknown(String),
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Deserialize, Serialize)]
 pub enum WaypointTraitSymbol {
     UNCHARTED,
+    UNDER_CONSTRUCTION,
     MARKETPLACE,
     SHIPYARD,
     OUTPOST,
-    OVERCROWDED,
-    CORRUPT,
-    BUREAUCRATIC,
-    INDUSTRIAL,
-    JOVIAN,
-    ROCKY,
-    VOLCANIC,
-    FROZEN,
-    SWAMP,
-    BARREN,
-    TEMPERATE,
-    JUNGLE,
-    OCEAN,
-    STRIPPED,
-    TOXIC_ATMOSPHERE,
     SCATTERED_SETTLEMENTS,
     SPRAWLING_CITIES,
     MEGA_STRUCTURES,
+    PIRATE_BASE,
+    OVERCROWDED,
     HIGH_TECH,
+    CORRUPT,
+    BUREAUCRATIC,
     TRADING_HUB,
+    INDUSTRIAL,
     BLACK_MARKET,
     RESEARCH_FACILITY,
     MILITARY_BASE,
@@ -270,14 +379,41 @@ pub enum WaypointTraitSymbol {
     WEAK_GRAVITY,
     STRONG_GRAVITY,
     CRUSHING_GRAVITY,
+    TOXIC_ATMOSPHERE,
     CORROSIVE_ATMOSPHERE,
     BREATHABLE_ATMOSPHERE,
+    THIN_ATMOSPHERE,
+    JOVIAN,
+    ROCKY,
+    VOLCANIC,
+    FROZEN,
+    SWAMP,
+    BARREN,
+    TEMPERATE,
+    JUNGLE,
+    OCEAN,
+    RADIOACTIVE,
+    MICRO_GRAVITY_ANOMALIES,
+    DEBRIS_CLUSTER,
+    DEEP_CRATERS,
+    SHALLOW_CRATERS,
+    UNSTABLE_COMPOSITION,
+    HOLLOWED_INTERIOR,
+    STRIPPED,
+    /// A value added on the server since, kept as sent
+    #[serde(
+        untagged,
+        deserialize_with = "unknown_variant::<WaypointTraitSymbol, _>"
+    )]
+    #[value(skip)]
+    Unknown(String),
 }
 
-#[derive(Display, Debug, Serialize, Deserialize, ValueEnum, Clone)]
+#[derive(Display, Debug, Serialize, Deserialize, ValueEnum, Clone, PartialEq)]
 pub enum ShipType {
     SHIP_PROBE,
     SHIP_MINING_DRONE,
+    SHIP_SIPHON_DRONE,
     SHIP_INTERCEPTOR,
     SHIP_LIGHT_HAULER,
     SHIP_COMMAND_FRIGATE,
@@ -286,9 +422,16 @@ pub enum ShipType {
     SHIP_LIGHT_SHUTTLE,
     SHIP_ORE_HOUND,
     SHIP_REFINING_FREIGHTER,
+    SHIP_SURVEYOR,
+    SHIP_BULK_FREIGHTER,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipType, _>")]
+    #[value(skip)]
+    #[strum(default)]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Ship {
     pub symbol: String,
@@ -298,13 +441,15 @@ pub struct Ship {
     pub frame: ShipFrame,
     pub reactor: ShipReactor,
     pub engine: ShipEngine,
+    /// As of when the ship was fetched
+    pub cooldown: Cooldown,
     pub modules: Vec<ShipModule>,
     pub mounts: Vec<ShipMount>,
     pub cargo: ShipCargo,
     pub fuel: ShipFuel,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipRegistration {
     pub name: String,
@@ -312,10 +457,10 @@ pub struct ShipRegistration {
     pub role: ShipRole,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ShipRole {
     FABRICATOR,
-    HARVESTOR,
+    HARVESTER,
     HAULER,
     INTERCEPTOR,
     EXCAVATOR,
@@ -328,9 +473,12 @@ pub enum ShipRole {
     SATELLITE,
     EXPLORER,
     REFINERY,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipRole, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipNav {
     pub system_symbol: String,
@@ -340,31 +488,60 @@ pub struct ShipNav {
     pub flight_mode: ShipNavFlightMode,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipNavRoute {
     pub destination: ShipNavRouteWaypoint,
-    pub departure: ShipNavRouteWaypoint,
-    pub departure_time: String,
-    pub arrival: String,
+    /// Called `departure` before it was renamed
+    #[serde(alias = "departure")]
+    pub origin: ShipNavRouteWaypoint,
+    pub departure_time: DateTime<Utc>,
+    pub arrival: DateTime<Utc>,
+}
+
+impl ShipNavRoute {
+    /// Time left until the ship arrives, zero once it has arrived
+    pub fn time_remaining(&self) -> Duration {
+        time_until(self.arrival)
+    }
+
+    pub fn has_arrived(&self) -> bool {
+        self.arrival <= Utc::now()
+    }
+
+    pub fn arrival_description(&self) -> String {
+        if self.has_arrived() {
+            format!("arrived {} ago", format_duration(Utc::now() - self.arrival))
+        } else {
+            format!("arrives in {}", format_duration(self.time_remaining()))
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub enum ShipNavStatus {
     IN_TRANSIT,
     IN_ORBIT,
     DOCKED,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipNavStatus, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Display, Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, ValueEnum)]
 pub enum ShipNavFlightMode {
     DRIFT,
     STEALTH,
     CRUISE,
     BURN,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipNavFlightMode, _>")]
+    #[value(skip)]
+    #[strum(default)]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipNavRouteWaypoint {
     pub symbol: String,
@@ -375,7 +552,7 @@ pub struct ShipNavRouteWaypoint {
     pub y: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipCrew {
     pub current: i32,
@@ -386,26 +563,32 @@ pub struct ShipCrew {
     pub wages: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum CrewRotation {
     STRICT,
     RELAXED,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<CrewRotation, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipFrame {
     pub symbol: ShipFrameSymbol,
     pub name: String,
     pub description: String,
-    pub condition: Option<i32>,
+    /// Wear, from 1 when new down to 0
+    pub condition: Option<f64>,
+    /// Permanent damage, from 1 when new down to 0
+    pub integrity: Option<f64>,
     pub module_slots: i32,
     pub mounting_points: i32,
     pub fuel_capacity: i32,
     pub requirements: ShipRequirements,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ShipFrameSymbol {
     FRAME_PROBE,
     FRAME_DRONE,
@@ -418,13 +601,17 @@ pub enum ShipFrameSymbol {
     FRAME_MINER,
     FRAME_LIGHT_FREIGHTER,
     FRAME_HEAVY_FREIGHTER,
+    FRAME_BULK_FREIGHTER,
     FRAME_TRANSPORT,
     FRAME_DESTROYER,
     FRAME_CRUISER,
     FRAME_CARRIER,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipFrameSymbol, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipRequirements {
     pub power: Option<i32>,
@@ -432,46 +619,54 @@ pub struct ShipRequirements {
     pub slots: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipReactor {
     pub symbol: ShipReactorSymbol,
     pub name: String,
     pub description: String,
-    pub condition: Option<i32>,
+    pub condition: Option<f64>,
+    pub integrity: Option<f64>,
     pub power_output: i32,
     pub requirements: ShipRequirements,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ShipReactorSymbol {
     REACTOR_SOLAR_I,
     REACTOR_FUSION_I,
     REACTOR_FISSION_I,
     REACTOR_CHEMICAL_I,
     REACTOR_ANTIMATTER_I,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipReactorSymbol, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipEngine {
     pub symbol: ShipEngineSymbol,
     pub name: String,
     pub description: String,
-    pub condition: Option<i32>,
+    pub condition: Option<f64>,
+    pub integrity: Option<f64>,
     pub speed: i32,
     pub requirements: ShipRequirements,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ShipEngineSymbol {
     ENGINE_IMPULSE_DRIVE_I,
     ENGINE_ION_DRIVE_I,
     ENGINE_ION_DRIVE_II,
     ENGINE_HYPER_DRIVE_I,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipEngineSymbol, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipModule {
     pub symbol: ShipModuleSymbol,
@@ -482,10 +677,13 @@ pub struct ShipModule {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ShipModuleSymbol {
     MODULE_MINERAL_PROCESSOR_I,
+    MODULE_GAS_PROCESSOR_I,
     MODULE_CARGO_HOLD_I,
+    MODULE_CARGO_HOLD_II,
+    MODULE_CARGO_HOLD_III,
     MODULE_CREW_QUARTERS_I,
     MODULE_ENVOY_QUARTERS_I,
     MODULE_PASSENGER_CABIN_I,
@@ -501,9 +699,12 @@ pub enum ShipModuleSymbol {
     MODULE_WARP_DRIVE_III,
     MODULE_SHIELD_GENERATOR_I,
     MODULE_SHIELD_GENERATOR_II,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipModuleSymbol, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipMount {
     pub symbol: ShipMountSymbol,
@@ -514,7 +715,7 @@ pub struct ShipMount {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ShipMountSymbol {
     MOUNT_GAS_SIPHON_I,
     MOUNT_GAS_SIPHON_II,
@@ -531,9 +732,12 @@ pub enum ShipMountSymbol {
     MOUNT_LASER_CANNON_I,
     MOUNT_MISSILE_LAUNCHER_I,
     MOUNT_TURRET_I,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ShipMountSymbol, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Deposit {
     QUARTZ_SAND,
     SILICON_CRYSTALS,
@@ -549,9 +753,12 @@ pub enum Deposit {
     DIAMONDS,
     URANITE_ORE,
     MERITIUM_ORE,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<Deposit, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipCargo {
     pub capacity: i32,
@@ -559,7 +766,7 @@ pub struct ShipCargo {
     pub inventory: Vec<Inventory>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Inventory {
     pub symbol: String,
@@ -568,7 +775,7 @@ pub struct Inventory {
     pub units: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipFuel {
     pub current: i32,
@@ -576,24 +783,24 @@ pub struct ShipFuel {
     pub consumed: Option<Consumed>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Consumed {
     pub amount: i32,
-    pub timestamp: String,
+    pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
     pub waypoint_symbol: String,
     pub ship_symbol: String,
     pub price: i32,
     pub agent_symbol: String,
-    pub timestamp: String,
+    pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MarketTransaction {
     pub waypoint_symbol: String,
@@ -604,25 +811,60 @@ pub struct MarketTransaction {
     pub units: i32,
     pub price_per_unit: i32,
     pub total_price: i32,
-    pub timestamp: String,
+    pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Display, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MarketTransactionType {
     PURCHASE,
     SELL,
+    /// A value added on the server since, kept as sent
+    #[serde(
+        untagged,
+        deserialize_with = "unknown_variant::<MarketTransactionType, _>"
+    )]
+    #[strum(default)]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Cooldown {
     pub ship_symbol: String,
     pub total_seconds: i32,
     pub remaining_seconds: i32,
-    pub expiration: String,
+    pub expiration: Option<DateTime<Utc>>,
+}
+
+impl Cooldown {
+    pub fn is_expired(&self) -> bool {
+        match self.expiration {
+            Some(expiration) => expiration <= Utc::now(),
+            None => true,
+        }
+    }
+
+    /// Time left until the cooldown expires, zero once it has expired
+    pub fn time_remaining(&self) -> Duration {
+        match self.expiration {
+            Some(expiration) => time_until(expiration),
+            None => Duration::zero(),
+        }
+    }
+
+    pub fn expiration_description(&self) -> String {
+        if self.is_expired() {
+            String::from("cooldown expired")
+        } else {
+            format!(
+                "cooldown expires in {}",
+                format_duration(self.time_remaining())
+            )
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Extraction {
     pub ship_symbol: String,
@@ -630,14 +872,16 @@ pub struct Extraction {
     pub extraction_yield: ExtractionYield,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExtractionYield {
     pub symbol: TradeSymbol,
     pub units: i32,
 }
 
-#[derive(Display, Debug, Serialize, Deserialize, ValueEnum, Clone)]
+#[derive(
+    Display, EnumString, Debug, Serialize, Deserialize, ValueEnum, Clone, PartialEq, Eq, Hash,
+)]
 pub enum TradeSymbol {
     PRECIOUS_STONES,
     QUARTZ_SAND,
@@ -749,43 +993,69 @@ pub enum TradeSymbol {
     MOUNT_LASER_CANNON_I,
     MOUNT_MISSILE_LAUNCHER_I,
     MOUNT_TURRET_I,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<TradeSymbol, _>")]
+    #[value(skip)]
+    #[strum(default)]
+    Unknown(String),
+}
+
+impl TradeSymbol {
+    /// Never fails, goods the server added since come back as `Unknown`
+    pub fn from_symbol(symbol: &str) -> Self {
+        symbol
+            .parse()
+            .unwrap_or_else(|_| TradeSymbol::Unknown(symbol.to_owned()))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Survey {
     pub signature: String,
     pub symbol: String,
     pub deposits: Vec<SurveyDeposit>,
-    pub expiration: String,
+    pub expiration: DateTime<Utc>,
     pub size: DepositSize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Survey {
+    pub fn is_expired(&self) -> bool {
+        self.expiration <= Utc::now()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum DepositSize {
     SMALL,
     MODERATE,
     LARGE,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<DepositSize, _>")]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SurveyDeposit {
-    symbol: Deposit,
+    pub symbol: Deposit,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Market {
     pub symbol: String,
     pub exports: Vec<TradeGood>,
     pub imports: Vec<TradeGood>,
     pub exchange: Vec<TradeGood>,
+    // only present when one of our ships is at the market
+    #[serde(default)]
     pub transactions: Vec<MarketTransaction>,
+    #[serde(default)]
     pub trade_goods: Vec<MarketTradeGood>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TradeGood {
     pub symbol: TradeSymbol,
@@ -793,61 +1063,159 @@ pub struct TradeGood {
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct MarketTradeGood {
     pub symbol: String,
+    #[serde(rename = "type")]
+    pub trade_type: MarketTradeGoodType,
     pub trade_volume: u32,
     pub supply: Supply,
+    /// How busy production or consumption is, not sent for exchanged goods
+    pub activity: Option<ActivityLevel>,
     pub purchase_price: u32,
     pub sell_price: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Display, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum MarketTradeGoodType {
+    EXPORT,
+    IMPORT,
+    EXCHANGE,
+    /// A value added on the server since, kept as sent
+    #[serde(
+        untagged,
+        deserialize_with = "unknown_variant::<MarketTradeGoodType, _>"
+    )]
+    #[strum(default)]
+    Unknown(String),
+}
+
+#[derive(Display, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ActivityLevel {
+    WEAK,
+    GROWING,
+    STRONG,
+    RESTRICTED,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<ActivityLevel, _>")]
+    #[strum(default)]
+    Unknown(String),
+}
+
+#[derive(Display, Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum Supply {
     SCARCE,
     LIMITED,
     MODERATE,
+    HIGH,
     ABUNDANT,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<Supply, _>")]
+    #[strum(default)]
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JumpGate {
+    pub symbol: String,
+    /// The jump gates, in other systems, this one leads to
+    pub connections: Vec<String>,
+}
+
+#[derive(Display, PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub enum SystemType {
+    NEUTRON_STAR,
+    RED_STAR,
+    ORANGE_STAR,
+    BLUE_STAR,
+    YOUNG_STAR,
+    WHITE_DWARF,
+    BLACK_HOLE,
+    HYPERGIANT,
+    NEBULA,
+    UNSTABLE,
+    /// A value added on the server since, kept as sent
+    #[serde(untagged, deserialize_with = "unknown_variant::<SystemType, _>")]
+    #[strum(default)]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Shipyard {
     pub symbol: String,
     pub ship_types: Vec<ShipyardShipTypes>,
+    // only present when one of our ships is at the shipyard
+    #[serde(default)]
     pub transactions: Vec<ShipyardTransaction>,
+    #[serde(default)]
     pub ships: Vec<ShipyardShip>,
+    pub modifications_fee: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipyardTransaction {
     pub waypoint_symbol: String,
+    /// Deprecated in the spec, `ship_type` says what was bought
     pub ship_symbol: String,
+    pub ship_type: ShipType,
     pub price: u32,
     pub agent_symbol: String,
-    pub timestamp: String,
+    pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipyardShip {
     #[serde(rename = "type")]
     pub ship_type: ShipType,
     pub name: String,
     pub description: String,
+    pub supply: Supply,
+    pub activity: Option<ActivityLevel>,
     pub purchase_price: u32,
     pub frame: ShipFrame,
     pub reactor: ShipReactor,
     pub engine: ShipEngine,
     pub modules: Vec<ShipModule>,
     pub mounts: Vec<ShipMount>,
+    pub crew: ShipyardShipCrew,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipyardShipCrew {
+    pub required: i32,
+    pub capacity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShipyardShipTypes {
     #[serde(rename = "type")]
     pub ship_type: ShipType,
 }
+
+// ---- TIME ----
+
+fn time_until(instant: DateTime<Utc>) -> Duration {
+    (instant - Utc::now()).max(Duration::zero())
+}
+
+/// Formats a duration compactly, e.g. `2m13s` or `1h05m00s`
+pub fn format_duration(duration: Duration) -> String {
+    let total_seconds = duration.num_seconds().max(0);
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    if hours > 0 {
+        format!("{hours}h{minutes:02}m{seconds:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m{seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
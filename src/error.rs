@@ -0,0 +1,51 @@
+use crate::api::ApiError;
+
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_AUTH_MISSING: i32 = 3;
+pub const EXIT_SERVICE_ERROR: i32 = 4;
+pub const EXIT_NETWORK_ERROR: i32 = 5;
+pub const EXIT_PARSE_ERROR: i32 = 6;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AppError {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("Not logged in, please register first by typing `space_traders_rust register --username <USERNAME>`")]
+    AuthMissing,
+
+    #[error("{0}")]
+    InvalidShipState(String),
+
+    #[error(transparent)]
+    Api(#[from] ApiError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("script error: {0}")]
+    Script(String),
+
+    #[error("daemon: {0}")]
+    Daemon(String),
+}
+
+impl AppError {
+    /// The process exit code scripts can use to tell failures apart
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Usage(_) => EXIT_USAGE,
+            AppError::AuthMissing => EXIT_AUTH_MISSING,
+            AppError::InvalidShipState(_) => EXIT_FAILURE,
+            AppError::Api(ApiError::ServiceError { .. }) => EXIT_SERVICE_ERROR,
+            AppError::Api(ApiError::NetworkError { .. }) => EXIT_NETWORK_ERROR,
+            AppError::Api(ApiError::ParseError { .. }) => EXIT_PARSE_ERROR,
+            AppError::Io(_) => EXIT_FAILURE,
+            AppError::Script(_) => EXIT_FAILURE,
+            AppError::Daemon(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,141 @@
+use std::fmt;
+
+use crate::{
+    api::Api,
+    domain::{format_duration, Ship, ShipNavFlightMode, ShipNavRouteWaypoint, Waypoint},
+    error::AppResult,
+};
+
+pub const FLIGHT_MODES: [ShipNavFlightMode; 4] = [
+    ShipNavFlightMode::BURN,
+    ShipNavFlightMode::CRUISE,
+    ShipNavFlightMode::STEALTH,
+    ShipNavFlightMode::DRIFT,
+];
+
+#[derive(Debug, Clone)]
+pub struct HopEstimate {
+    pub flight_mode: ShipNavFlightMode,
+    pub distance: f64,
+    pub fuel_cost: i32,
+    pub travel_seconds: i64,
+}
+
+impl HopEstimate {
+    pub fn new(flight_mode: ShipNavFlightMode, distance: f64, speed: i32) -> Self {
+        HopEstimate {
+            fuel_cost: fuel_cost(flight_mode.clone(), distance),
+            travel_seconds: travel_seconds(flight_mode.clone(), distance, speed),
+            flight_mode,
+            distance,
+        }
+    }
+}
+
+impl fmt::Display for HopEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<8} fuel {:>4}  time {}",
+            self.flight_mode.to_string(),
+            self.fuel_cost,
+            format_duration(chrono::Duration::seconds(self.travel_seconds))
+        )
+    }
+}
+
+pub fn distance(from: (i32, i32), to: (i32, i32)) -> f64 {
+    let dx = (to.0 - from.0) as f64;
+    let dy = (to.1 - from.1) as f64;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Fuel burned flying `distance` units. Hops between orbitals at the same coordinates are free.
+pub fn fuel_cost(flight_mode: ShipNavFlightMode, distance: f64) -> i32 {
+    let distance = distance.round() as i32;
+    if distance == 0 {
+        return 0;
+    }
+    match flight_mode {
+        ShipNavFlightMode::DRIFT => 1,
+        ShipNavFlightMode::BURN => 2 * distance,
+        // CRUISE is the default mode, the best guess for one we don't know
+        ShipNavFlightMode::STEALTH | ShipNavFlightMode::CRUISE | ShipNavFlightMode::Unknown(_) => {
+            distance
+        }
+    }
+}
+
+pub fn travel_seconds(flight_mode: ShipNavFlightMode, distance: f64, speed: i32) -> i64 {
+    let multiplier = match flight_mode {
+        ShipNavFlightMode::CRUISE | ShipNavFlightMode::Unknown(_) => 25.0,
+        ShipNavFlightMode::DRIFT => 250.0,
+        ShipNavFlightMode::BURN => 12.5,
+        ShipNavFlightMode::STEALTH => 30.0,
+    };
+    let distance = distance.round().max(1.0);
+    (distance * (multiplier / speed.max(1) as f64) + 15.0).round() as i64
+}
+
+/// Estimates the hop between two route waypoints in every flight mode
+pub fn estimate_hop(from: &ShipNavRouteWaypoint, to: (i32, i32), speed: i32) -> Vec<HopEstimate> {
+    let distance = distance((from.x, from.y), to);
+    FLIGHT_MODES
+        .iter()
+        .map(|flight_mode| HopEstimate::new(flight_mode.clone(), distance, speed))
+        .collect()
+}
+
+pub async fn estimate(api: &Api<'_>, ship_symbol: String, destination: String) -> AppResult<()> {
+    let ship = api.get_ship_status(ship_symbol).await?.data;
+    let waypoint = api.get_waypoint(destination).await?.data;
+    let here = &ship.nav.route.destination;
+    let estimates = estimate_hop(here, (waypoint.x, waypoint.y), ship.engine.speed);
+
+    println!(
+        "{} at {} -> {}, distance {:.1} (fuel {}/{}, speed {})",
+        ship.symbol,
+        here.symbol,
+        waypoint.symbol,
+        distance((here.x, here.y), (waypoint.x, waypoint.y)),
+        ship.fuel.current,
+        ship.fuel.capacity,
+        ship.engine.speed
+    );
+    for estimate in estimates {
+        let exceeds_fuel = ship.fuel.capacity > 0 && estimate.fuel_cost > ship.fuel.current;
+        println!(
+            "  {estimate}{}",
+            if exceeds_fuel {
+                "  (exceeds current fuel)"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Warns on stderr when flying to `destination` in the ship's current flight mode would take
+/// more fuel than is in the tank
+pub fn warn_if_out_of_fuel(ship: &Ship, destination: &Waypoint) {
+    if ship.fuel.capacity == 0 {
+        return;
+    }
+    let here = &ship.nav.route.destination;
+    let estimate = HopEstimate::new(
+        ship.nav.flight_mode.clone(),
+        distance((here.x, here.y), (destination.x, destination.y)),
+        ship.engine.speed,
+    );
+    if estimate.fuel_cost > ship.fuel.current {
+        eprintln!(
+            "warning: {} to {} needs ~{} fuel but {} only has {}",
+            estimate.flight_mode,
+            destination.symbol,
+            estimate.fuel_cost,
+            ship.symbol,
+            ship.fuel.current
+        );
+    }
+}
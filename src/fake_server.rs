@@ -0,0 +1,1271 @@
+//! An in-process stand-in for the SpaceTraders API with a small, consistent game world, so
+//! every command can be run end to end offline by pointing `TEST_API_BASE_URL` at it.
+//!
+//! The rules are simplified: any token is accepted, prices don't move, and travel and
+//! cooldowns take real time multiplied by `time_scale`, which is zero by default so ships
+//! arrive and cool down instantly.
+
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex, MutexGuard},
+    thread::JoinHandle,
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{
+    api::{ApiSuccessResponse, Meta},
+    domain::*,
+    estimator::{distance, fuel_cost, travel_seconds},
+};
+
+const WORLD: &str = include_str!("fake_server/world.json");
+const EXTRACTION_COOLDOWN_SECONDS: f64 = 70.0;
+const SURVEY_COOLDOWN_SECONDS: f64 = 60.0;
+/// Goods an asteroid field yields, in turn
+const ASTEROID_YIELDS: [&str; 3] = ["IRON_ORE", "ICE_WATER", "COPPER_ORE"];
+const MAX_PAGE_LIMIT: usize = 20;
+
+/// Everything the fake server knows about, shared by every request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct World {
+    pub agent: Agent,
+    pub ships: Vec<Ship>,
+    pub waypoints: Vec<Waypoint>,
+    pub markets: Vec<Market>,
+    pub shipyards: Vec<Shipyard>,
+    pub contracts: Vec<Contract>,
+    /// Jump gates by waypoint symbol
+    #[serde(default)]
+    pub jump_gates: HashMap<String, JumpGate>,
+    /// Active cooldowns by ship symbol
+    #[serde(default)]
+    pub cooldowns: HashMap<String, Cooldown>,
+    #[serde(default)]
+    pub extractions: usize,
+}
+
+impl Default for World {
+    /// One system with a planet market and shipyard, a moon market, an orbital station, an
+    /// asteroid field and a jump gate, a command ship docked at the planet and an open contract
+    fn default() -> Self {
+        serde_json::from_str(WORLD).expect("the bundled fake world is valid")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeServerOptions {
+    pub addr: SocketAddr,
+    pub world: World,
+    /// Multiplies travel times and cooldowns, zero makes them instant
+    pub time_scale: f64,
+}
+
+impl Default for FakeServerOptions {
+    fn default() -> Self {
+        FakeServerOptions {
+            addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            world: World::default(),
+            time_scale: 0.0,
+        }
+    }
+}
+
+/// Runs on its own thread and runtime, so it works from plain `#[test]`s too, and shuts down
+/// when dropped
+pub struct FakeServer {
+    addr: SocketAddr,
+    state: Arc<FakeState>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeServer {
+    /// Starts on a free local port with the default world
+    pub fn start() -> std::io::Result<FakeServer> {
+        FakeServer::start_with(FakeServerOptions::default())
+    }
+
+    pub fn start_with(options: FakeServerOptions) -> std::io::Result<FakeServer> {
+        let listener = TcpListener::bind(options.addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(FakeState {
+            world: Mutex::new(options.world),
+            time_scale: options.time_scale,
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let server = {
+            // the listener registers with the runtime it's adopted in
+            let _runtime = runtime.enter();
+            axum::Server::from_tcp(listener)
+                .map_err(std::io::Error::other)?
+                .serve(router(state.clone()).into_make_service())
+        };
+        let (shutdown, stopped) = oneshot::channel();
+        let thread = std::thread::spawn(move || {
+            let server = server.with_graceful_shutdown(async {
+                let _ = stopped.await;
+            });
+            if let Err(e) = runtime.block_on(server) {
+                eprintln!("fake server error: {e}");
+            }
+        });
+        Ok(FakeServer {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        })
+    }
+
+    /// Base URL to set `TEST_API_BASE_URL` to
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// The current game state, to check on or change between requests
+    pub fn world(&self) -> MutexGuard<'_, World> {
+        self.state.world.lock().unwrap()
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct FakeState {
+    world: Mutex<World>,
+    time_scale: f64,
+}
+
+impl FakeState {
+    fn world(&self) -> MutexGuard<'_, World> {
+        let mut world = self.world.lock().unwrap();
+        let World {
+            ships, cooldowns, ..
+        } = &mut *world;
+        for ship in ships.iter_mut() {
+            if ship.nav.status == ShipNavStatus::IN_TRANSIT && ship.nav.route.has_arrived() {
+                ship.nav.status = ShipNavStatus::IN_ORBIT;
+            }
+            if let Some(cooldown) = cooldowns.get(&ship.symbol) {
+                ship.cooldown = cooldown.clone();
+                ship.cooldown.remaining_seconds = cooldown.time_remaining().num_seconds() as i32;
+            }
+        }
+        world
+    }
+
+    fn scaled(&self, seconds: f64) -> Duration {
+        Duration::milliseconds((seconds * self.time_scale * 1000.0) as i64)
+    }
+}
+
+fn router(state: Arc<FakeState>) -> Router {
+    Router::new()
+        .route("/register", post(register))
+        .route("/my/agent", get(agent))
+        .route("/my/ships", get(list_ships).post(purchase_ship))
+        .route("/my/ships/:ship_symbol", get(ship))
+        .route("/my/ships/:ship_symbol/nav", get(nav))
+        .route("/my/ships/:ship_symbol/cargo", get(cargo))
+        .route("/my/ships/:ship_symbol/cooldown", get(cooldown))
+        .route("/my/ships/:ship_symbol/dock", post(dock))
+        .route("/my/ships/:ship_symbol/orbit", post(orbit))
+        .route("/my/ships/:ship_symbol/navigate", post(navigate))
+        .route("/my/ships/:ship_symbol/refuel", post(refuel))
+        .route("/my/ships/:ship_symbol/extract", post(extract))
+        .route("/my/ships/:ship_symbol/survey", post(survey))
+        .route("/my/ships/:ship_symbol/sell", post(sell))
+        .route("/my/ships/:ship_symbol/purchase", post(purchase))
+        .route("/my/ships/:ship_symbol/jettison", post(jettison))
+        .route("/my/contracts", get(contracts))
+        .route("/my/contracts/:contract_id", get(contract))
+        .route("/my/contracts/:contract_id/accept", post(accept_contract))
+        .route("/my/contracts/:contract_id/deliver", post(deliver_contract))
+        .route("/my/contracts/:contract_id/fulfill", post(fulfill_contract))
+        .route("/systems/:system_symbol/waypoints", get(waypoints))
+        .route(
+            "/systems/:system_symbol/waypoints/:waypoint_symbol",
+            get(waypoint),
+        )
+        .route(
+            "/systems/:system_symbol/waypoints/:waypoint_symbol/market",
+            get(market),
+        )
+        .route(
+            "/systems/:system_symbol/waypoints/:waypoint_symbol/shipyard",
+            get(shipyard),
+        )
+        .route(
+            "/systems/:system_symbol/waypoints/:waypoint_symbol/jump-gate",
+            get(jump_gate),
+        )
+        .with_state(state)
+}
+
+// ---- RESPONSES ----
+
+/// Errors in the API's own shape, so `Api` reports them as service errors
+struct FakeError {
+    status: StatusCode,
+    code: i32,
+    message: String,
+}
+
+impl IntoResponse for FakeError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "error": { "message": self.message, "code": self.code }
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+fn not_found(what: &str) -> FakeError {
+    FakeError {
+        status: StatusCode::NOT_FOUND,
+        code: 404,
+        message: format!("{what} not found"),
+    }
+}
+
+fn rejected(code: i32, message: String) -> FakeError {
+    FakeError {
+        status: StatusCode::BAD_REQUEST,
+        code,
+        message,
+    }
+}
+
+type FakeResult<T> = Result<Json<ApiSuccessResponse<T>>, FakeError>;
+
+fn data<T>(data: T) -> FakeResult<T> {
+    Ok(Json(ApiSuccessResponse { data, meta: None }))
+}
+
+// ---- LOOKUPS ----
+
+fn find_ship<'a>(ships: &'a mut [Ship], ship_symbol: &str) -> Result<&'a mut Ship, FakeError> {
+    ships
+        .iter_mut()
+        .find(|ship| ship.symbol == ship_symbol)
+        .ok_or_else(|| not_found(&format!("ship {ship_symbol}")))
+}
+
+fn find_waypoint<'a>(
+    waypoints: &'a [Waypoint],
+    waypoint_symbol: &str,
+) -> Result<&'a Waypoint, FakeError> {
+    waypoints
+        .iter()
+        .find(|waypoint| waypoint.symbol == waypoint_symbol)
+        .ok_or_else(|| not_found(&format!("waypoint {waypoint_symbol}")))
+}
+
+fn find_contract<'a>(
+    contracts: &'a mut [Contract],
+    contract_id: &str,
+) -> Result<&'a mut Contract, FakeError> {
+    contracts
+        .iter_mut()
+        .find(|contract| contract.id == contract_id)
+        .ok_or_else(|| not_found(&format!("contract {contract_id}")))
+}
+
+/// The market the ship is docked at, and the price of the good there
+fn docked_market<'a>(
+    markets: &'a mut [Market],
+    ship: &Ship,
+    trade_symbol: &str,
+) -> Result<(&'a mut Market, MarketTradeGood), FakeError> {
+    ensure_status(ship, ShipNavStatus::DOCKED)?;
+    let waypoint_symbol = &ship.nav.waypoint_symbol;
+    let market = markets
+        .iter_mut()
+        .find(|market| market.symbol == *waypoint_symbol)
+        .ok_or_else(|| rejected(4601, format!("there is no market at {waypoint_symbol}")))?;
+    let good = market
+        .trade_goods
+        .iter()
+        .find(|good| good.symbol == trade_symbol)
+        .cloned()
+        .ok_or_else(|| {
+            rejected(
+                4602,
+                format!("{waypoint_symbol} doesn't trade {trade_symbol}"),
+            )
+        })?;
+    Ok((market, good))
+}
+
+fn ensure_status(ship: &Ship, status: ShipNavStatus) -> Result<(), FakeError> {
+    match &ship.nav.status {
+        ShipNavStatus::IN_TRANSIT if status != ShipNavStatus::IN_TRANSIT => Err(rejected(
+            4214,
+            format!("{} is still in transit", ship.symbol),
+        )),
+        current if *current != status => Err(rejected(
+            if status == ShipNavStatus::DOCKED {
+                4244
+            } else {
+                4236
+            },
+            format!("{} must be {:?}, it is {:?}", ship.symbol, status, current),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn route_waypoint(waypoint: &Waypoint) -> ShipNavRouteWaypoint {
+    ShipNavRouteWaypoint {
+        symbol: waypoint.symbol.clone(),
+        waypoint_type: waypoint.waypoint_type.clone(),
+        system_symbol: waypoint.system_symbol.clone(),
+        x: waypoint.x,
+        y: waypoint.y,
+    }
+}
+
+fn add_cargo(cargo: &mut ShipCargo, trade_symbol: &str, units: i32) -> Result<(), FakeError> {
+    if cargo.units + units > cargo.capacity {
+        return Err(rejected(
+            4228,
+            format!(
+                "{units} {trade_symbol} don't fit, {} of {} units are free",
+                cargo.capacity - cargo.units,
+                cargo.capacity
+            ),
+        ));
+    }
+    match cargo
+        .inventory
+        .iter_mut()
+        .find(|item| item.symbol == trade_symbol)
+    {
+        Some(item) => item.units += units,
+        None => cargo.inventory.push(Inventory {
+            symbol: trade_symbol.to_owned(),
+            name: good_name(trade_symbol),
+            description: String::new(),
+            units,
+        }),
+    }
+    cargo.units += units;
+    Ok(())
+}
+
+fn remove_cargo(cargo: &mut ShipCargo, trade_symbol: &str, units: i32) -> Result<(), FakeError> {
+    let held = cargo
+        .inventory
+        .iter()
+        .find(|item| item.symbol == trade_symbol)
+        .map_or(0, |item| item.units);
+    if held < units {
+        return Err(rejected(
+            4219,
+            format!("the ship holds {held} {trade_symbol}, not {units}"),
+        ));
+    }
+    for item in cargo.inventory.iter_mut() {
+        if item.symbol == trade_symbol {
+            item.units -= units;
+        }
+    }
+    cargo.inventory.retain(|item| item.units > 0);
+    cargo.units -= units;
+    Ok(())
+}
+
+/// `IRON_ORE` -> `Iron Ore`
+fn good_name(trade_symbol: &str) -> String {
+    trade_symbol
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => format!("{first}{}", chars.as_str().to_lowercase()),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn ensure_affordable(agent: &Agent, credits: i64) -> Result<(), FakeError> {
+    if agent.credits < credits {
+        return Err(rejected(
+            4600,
+            format!("{credits} credits needed, {} available", agent.credits),
+        ));
+    }
+    Ok(())
+}
+
+fn spend(agent: &mut Agent, credits: i64) -> Result<(), FakeError> {
+    ensure_affordable(agent, credits)?;
+    agent.credits -= credits;
+    Ok(())
+}
+
+fn record_transaction(market: &mut Market, transaction: &MarketTransaction) {
+    market.transactions.push(transaction.clone());
+}
+
+// ---- AGENT ----
+
+#[derive(Deserialize)]
+struct RegisterBody {
+    symbol: String,
+    faction: String,
+}
+
+async fn register(
+    State(state): State<Arc<FakeState>>,
+    Json(body): Json<RegisterBody>,
+) -> FakeResult<RegisterResponse> {
+    let mut world = state.world();
+    world.agent.symbol = body.symbol.clone();
+    world.agent.starting_faction = body.faction;
+    data(RegisterResponse {
+        token: format!("fake-token-{}", body.symbol),
+        agent: world.agent.clone(),
+    })
+}
+
+async fn agent(State(state): State<Arc<FakeState>>) -> FakeResult<Agent> {
+    data(state.world().agent.clone())
+}
+
+// ---- SHIPS ----
+
+async fn list_ships(State(state): State<Arc<FakeState>>) -> FakeResult<Vec<Ship>> {
+    data(state.world().ships.clone())
+}
+
+async fn ship(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+) -> FakeResult<Ship> {
+    data(find_ship(&mut state.world().ships, &ship_symbol)?.clone())
+}
+
+async fn nav(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+) -> FakeResult<ShipNav> {
+    data(
+        find_ship(&mut state.world().ships, &ship_symbol)?
+            .nav
+            .clone(),
+    )
+}
+
+async fn cargo(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+) -> FakeResult<ShipCargo> {
+    data(
+        find_ship(&mut state.world().ships, &ship_symbol)?
+            .cargo
+            .clone(),
+    )
+}
+
+/// No content unless a cooldown is running, like the real API
+async fn cooldown(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+) -> Result<Response, FakeError> {
+    let mut world = state.world();
+    find_ship(&mut world.ships, &ship_symbol)?;
+    match world.cooldowns.get(&ship_symbol) {
+        Some(cooldown) if !cooldown.is_expired() => {
+            let mut cooldown = cooldown.clone();
+            cooldown.remaining_seconds = cooldown.time_remaining().num_seconds() as i32;
+            Ok(data(cooldown)?.into_response())
+        }
+        _ => Ok(StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+async fn dock(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+) -> FakeResult<ShipDockResponse> {
+    let mut world = state.world();
+    let ship = find_ship(&mut world.ships, &ship_symbol)?;
+    if ship.nav.status == ShipNavStatus::IN_TRANSIT {
+        ensure_status(ship, ShipNavStatus::DOCKED)?;
+    }
+    ship.nav.status = ShipNavStatus::DOCKED;
+    data(ShipDockResponse {
+        nav: ship.nav.clone(),
+    })
+}
+
+async fn orbit(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+) -> FakeResult<ShipOrbitResponse> {
+    let mut world = state.world();
+    let ship = find_ship(&mut world.ships, &ship_symbol)?;
+    if ship.nav.status == ShipNavStatus::IN_TRANSIT {
+        ensure_status(ship, ShipNavStatus::IN_ORBIT)?;
+    }
+    ship.nav.status = ShipNavStatus::IN_ORBIT;
+    data(ShipOrbitResponse {
+        nav: ship.nav.clone(),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NavigateBody {
+    waypoint_symbol: String,
+}
+
+async fn navigate(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+    Json(body): Json<NavigateBody>,
+) -> FakeResult<ShipNavigateResponse> {
+    let mut world = state.world();
+    let World {
+        ships, waypoints, ..
+    } = &mut *world;
+    let destination = find_waypoint(waypoints, &body.waypoint_symbol)?;
+    let ship = find_ship(ships, &ship_symbol)?;
+    ensure_status(ship, ShipNavStatus::IN_ORBIT)?;
+    if ship.nav.waypoint_symbol == destination.symbol {
+        return Err(rejected(
+            4204,
+            format!("{ship_symbol} is already at {}", destination.symbol),
+        ));
+    }
+
+    let here = &ship.nav.route.destination;
+    let distance = distance((here.x, here.y), (destination.x, destination.y));
+    let fuel = match ship.fuel.capacity {
+        0 => 0,
+        _ => fuel_cost(ship.nav.flight_mode.clone(), distance),
+    };
+    if fuel > ship.fuel.current {
+        return Err(rejected(
+            4203,
+            format!(
+                "{fuel} fuel needed to reach {}, {ship_symbol} has {}",
+                destination.symbol, ship.fuel.current
+            ),
+        ));
+    }
+    let now = Utc::now();
+    ship.fuel.current -= fuel;
+    ship.fuel.consumed = Some(Consumed {
+        amount: fuel,
+        timestamp: now,
+    });
+    let seconds = travel_seconds(ship.nav.flight_mode.clone(), distance, ship.engine.speed);
+    ship.nav.route = ShipNavRoute {
+        origin: ship.nav.route.destination.clone(),
+        destination: route_waypoint(destination),
+        departure_time: now,
+        arrival: now + state.scaled(seconds as f64),
+    };
+    ship.nav.waypoint_symbol = destination.symbol.clone();
+    ship.nav.system_symbol = destination.system_symbol.clone();
+    ship.nav.status = ShipNavStatus::IN_TRANSIT;
+    data(ShipNavigateResponse {
+        fuel: ship.fuel.clone(),
+        nav: ship.nav.clone(),
+    })
+}
+
+#[derive(Deserialize, Default)]
+struct RefuelBody {
+    units: Option<i32>,
+}
+
+/// Fuel is sold by the market unit, each of which fills 100 units of a tank
+async fn refuel(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+    body: Option<Json<RefuelBody>>,
+) -> FakeResult<ShipRefuelResponse> {
+    let Json(body) = body.unwrap_or_default();
+    let mut world = state.world();
+    let World {
+        agent,
+        ships,
+        markets,
+        ..
+    } = &mut *world;
+    let ship = find_ship(ships, &ship_symbol)?;
+    let (market, fuel) = docked_market(markets, ship, "FUEL")?;
+    let missing = ship.fuel.capacity - ship.fuel.current;
+    let units = body.units.unwrap_or(missing).clamp(0, missing);
+    let total_price = (units + 99) / 100 * fuel.purchase_price as i32;
+    spend(agent, total_price.into())?;
+    ship.fuel.current += units;
+    let transaction = MarketTransaction {
+        waypoint_symbol: market.symbol.clone(),
+        ship_symbol: ship_symbol.clone(),
+        trade_symbol: String::from("FUEL"),
+        transaction_type: MarketTransactionType::PURCHASE,
+        units,
+        price_per_unit: fuel.purchase_price as i32,
+        total_price,
+        timestamp: Utc::now(),
+    };
+    record_transaction(market, &transaction);
+    data(ShipRefuelResponse {
+        agent: agent.clone(),
+        fuel: ship.fuel.clone(),
+        transaction,
+    })
+}
+
+fn ensure_cooled_down(world: &World, ship_symbol: &str) -> Result<(), FakeError> {
+    match world.cooldowns.get(ship_symbol) {
+        Some(cooldown) if !cooldown.is_expired() => Err(rejected(
+            4000,
+            format!(
+                "{ship_symbol} is on cooldown for {}s",
+                cooldown.time_remaining().num_seconds()
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn start_cooldown(state: &FakeState, ship_symbol: &str, seconds: f64) -> Cooldown {
+    let length = state.scaled(seconds);
+    Cooldown {
+        ship_symbol: ship_symbol.to_owned(),
+        total_seconds: length.num_seconds() as i32,
+        remaining_seconds: length.num_seconds() as i32,
+        expiration: Some(Utc::now() + length),
+    }
+}
+
+/// The ship must orbit an asteroid field
+fn ensure_at_asteroid_field(waypoints: &[Waypoint], ship: &Ship) -> Result<(), FakeError> {
+    ensure_status(ship, ShipNavStatus::IN_ORBIT)?;
+    let waypoint = find_waypoint(waypoints, &ship.nav.waypoint_symbol)?;
+    if waypoint.waypoint_type != WaypointType::ASTEROID_FIELD {
+        return Err(rejected(
+            4205,
+            format!("{} is not an asteroid field", waypoint.symbol),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Deserialize, Default)]
+struct ExtractBody {
+    survey: Option<Survey>,
+}
+
+async fn extract(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+    body: Option<Json<ExtractBody>>,
+) -> FakeResult<ExtractResourceResponse> {
+    let Json(body) = body.unwrap_or_default();
+    let mut world = state.world();
+    ensure_cooled_down(&world, &ship_symbol)?;
+    let extractions = world.extractions;
+    let World {
+        ships, waypoints, ..
+    } = &mut *world;
+    let ship = find_ship(ships, &ship_symbol)?;
+    ensure_at_asteroid_field(waypoints, ship)?;
+    let strength: i32 = ship
+        .mounts
+        .iter()
+        .filter(|mount| {
+            matches!(
+                mount.symbol,
+                ShipMountSymbol::MOUNT_MINING_LASER_I
+                    | ShipMountSymbol::MOUNT_MINING_LASER_II
+                    | ShipMountSymbol::MOUNT_MINING_LASER_III
+            )
+        })
+        .map(|mount| mount.strength.unwrap_or(1))
+        .sum();
+    if strength == 0 {
+        return Err(rejected(4243, format!("{ship_symbol} has no mining laser")));
+    }
+    let free = ship.cargo.capacity - ship.cargo.units;
+    if free == 0 {
+        return Err(rejected(
+            4228,
+            format!("{ship_symbol}'s cargo hold is full"),
+        ));
+    }
+
+    let yielded = match &body.survey {
+        Some(survey) if !survey.deposits.is_empty() => {
+            let deposit = &survey.deposits[extractions % survey.deposits.len()];
+            serde_json::to_value(&deposit.symbol)
+                .ok()
+                .and_then(|value| value.as_str().map(str::to_owned))
+                .unwrap_or_else(|| ASTEROID_YIELDS[0].to_owned())
+        }
+        _ => ASTEROID_YIELDS[extractions % ASTEROID_YIELDS.len()].to_owned(),
+    };
+    let units = strength.min(free);
+    add_cargo(&mut ship.cargo, &yielded, units)?;
+    let extraction_yield: ExtractionYield = serde_json::from_value(serde_json::json!({
+        "symbol": yielded,
+        "units": units,
+    }))
+    .map_err(|e| rejected(4000, e.to_string()))?;
+    let cargo = ship.cargo.clone();
+    let cooldown = start_cooldown(&state, &ship_symbol, EXTRACTION_COOLDOWN_SECONDS);
+    world.extractions += 1;
+    world
+        .cooldowns
+        .insert(ship_symbol.clone(), cooldown.clone());
+    data(ExtractResourceResponse {
+        cooldown,
+        extraction: Extraction {
+            ship_symbol,
+            extraction_yield,
+        },
+        cargo,
+    })
+}
+
+async fn survey(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+) -> FakeResult<CreateSurveyResponse> {
+    let mut world = state.world();
+    ensure_cooled_down(&world, &ship_symbol)?;
+    let World {
+        ships, waypoints, ..
+    } = &mut *world;
+    let ship = find_ship(ships, &ship_symbol)?;
+    ensure_at_asteroid_field(waypoints, ship)?;
+    let has_surveyor = ship.mounts.iter().any(|mount| {
+        matches!(
+            mount.symbol,
+            ShipMountSymbol::MOUNT_SURVEYOR_I
+                | ShipMountSymbol::MOUNT_SURVEYOR_II
+                | ShipMountSymbol::MOUNT_SURVEYOR_III
+        )
+    });
+    if !has_surveyor {
+        return Err(rejected(4223, format!("{ship_symbol} has no surveyor")));
+    }
+    let waypoint_symbol = ship.nav.waypoint_symbol.clone();
+    let survey = Survey {
+        signature: format!("{waypoint_symbol}-{}", Utc::now().timestamp_millis()),
+        symbol: waypoint_symbol,
+        deposits: vec![
+            SurveyDeposit {
+                symbol: Deposit::IRON_ORE,
+            },
+            SurveyDeposit {
+                symbol: Deposit::IRON_ORE,
+            },
+            SurveyDeposit {
+                symbol: Deposit::COPPER_ORE,
+            },
+        ],
+        expiration: Utc::now() + Duration::minutes(15),
+        size: DepositSize::MODERATE,
+    };
+    let cooldown = start_cooldown(&state, &ship_symbol, SURVEY_COOLDOWN_SECONDS);
+    world.cooldowns.insert(ship_symbol, cooldown.clone());
+    data(CreateSurveyResponse {
+        cooldown,
+        surveys: vec![survey],
+    })
+}
+
+#[derive(Deserialize)]
+struct TradeBody {
+    symbol: String,
+    units: i32,
+}
+
+fn within_trade_volume(good: &MarketTradeGood, units: i32) -> Result<(), FakeError> {
+    if units > good.trade_volume as i32 {
+        return Err(rejected(
+            4604,
+            format!(
+                "at most {} {} can be traded at once",
+                good.trade_volume, good.symbol
+            ),
+        ));
+    }
+    Ok(())
+}
+
+async fn sell(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+    Json(body): Json<TradeBody>,
+) -> FakeResult<SellCargoResponse> {
+    let mut world = state.world();
+    let World {
+        agent,
+        ships,
+        markets,
+        ..
+    } = &mut *world;
+    let ship = find_ship(ships, &ship_symbol)?;
+    let (market, good) = docked_market(markets, ship, &body.symbol)?;
+    within_trade_volume(&good, body.units)?;
+    remove_cargo(&mut ship.cargo, &body.symbol, body.units)?;
+    let total_price = good.sell_price as i32 * body.units;
+    agent.credits += i64::from(total_price);
+    let transaction = MarketTransaction {
+        waypoint_symbol: market.symbol.clone(),
+        ship_symbol,
+        trade_symbol: body.symbol,
+        transaction_type: MarketTransactionType::SELL,
+        units: body.units,
+        price_per_unit: good.sell_price as i32,
+        total_price,
+        timestamp: Utc::now(),
+    };
+    record_transaction(market, &transaction);
+    data(SellCargoResponse {
+        agent: agent.clone(),
+        cargo: ship.cargo.clone(),
+        transaction,
+    })
+}
+
+async fn purchase(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+    Json(body): Json<TradeBody>,
+) -> FakeResult<PurchaseCargoResponse> {
+    let mut world = state.world();
+    let World {
+        agent,
+        ships,
+        markets,
+        ..
+    } = &mut *world;
+    let ship = find_ship(ships, &ship_symbol)?;
+    let (market, good) = docked_market(markets, ship, &body.symbol)?;
+    within_trade_volume(&good, body.units)?;
+    let total_price = good.purchase_price as i32 * body.units;
+    ensure_affordable(agent, total_price.into())?;
+    add_cargo(&mut ship.cargo, &body.symbol, body.units)?;
+    agent.credits -= i64::from(total_price);
+    let transaction = MarketTransaction {
+        waypoint_symbol: market.symbol.clone(),
+        ship_symbol,
+        trade_symbol: body.symbol,
+        transaction_type: MarketTransactionType::PURCHASE,
+        units: body.units,
+        price_per_unit: good.purchase_price as i32,
+        total_price,
+        timestamp: Utc::now(),
+    };
+    record_transaction(market, &transaction);
+    data(PurchaseCargoResponse {
+        agent: agent.clone(),
+        cargo: ship.cargo.clone(),
+        transaction,
+    })
+}
+
+async fn jettison(
+    State(state): State<Arc<FakeState>>,
+    Path(ship_symbol): Path<String>,
+    Json(body): Json<TradeBody>,
+) -> FakeResult<JettisonCargoResponse> {
+    let mut world = state.world();
+    let ship = find_ship(&mut world.ships, &ship_symbol)?;
+    if ship.nav.status == ShipNavStatus::IN_TRANSIT {
+        ensure_status(ship, ShipNavStatus::IN_ORBIT)?;
+    }
+    remove_cargo(&mut ship.cargo, &body.symbol, body.units)?;
+    data(JettisonCargoResponse {
+        cargo: ship.cargo.clone(),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PurchaseShipBody {
+    ship_type: ShipType,
+    waypoint_symbol: String,
+}
+
+async fn purchase_ship(
+    State(state): State<Arc<FakeState>>,
+    Json(body): Json<PurchaseShipBody>,
+) -> FakeResult<PurchaseShipResponse> {
+    let mut world = state.world();
+    let World {
+        agent,
+        ships,
+        waypoints,
+        shipyards,
+        ..
+    } = &mut *world;
+    let waypoint = find_waypoint(waypoints, &body.waypoint_symbol)?;
+    let shipyard = shipyards
+        .iter_mut()
+        .find(|shipyard| shipyard.symbol == body.waypoint_symbol)
+        .ok_or_else(|| not_found(&format!("shipyard at {}", body.waypoint_symbol)))?;
+    let ship_type = body.ship_type.to_string();
+    let listing = shipyard
+        .ships
+        .iter()
+        .find(|ship| ship.ship_type.to_string() == ship_type)
+        .cloned()
+        .ok_or_else(|| {
+            rejected(
+                4601,
+                format!("{} doesn't sell {ship_type}", body.waypoint_symbol),
+            )
+        })?;
+    let present = ships.iter().any(|ship| {
+        ship.nav.waypoint_symbol == body.waypoint_symbol
+            && ship.nav.status != ShipNavStatus::IN_TRANSIT
+    });
+    if !present {
+        return Err(rejected(
+            4601,
+            format!("none of your ships are at {}", body.waypoint_symbol),
+        ));
+    }
+    spend(agent, listing.purchase_price.into())?;
+
+    let symbol = format!("{}-{:X}", agent.symbol, ships.len() + 1);
+    let now = Utc::now();
+    let here = route_waypoint(waypoint);
+    let cargo_capacity = listing
+        .modules
+        .iter()
+        .filter(|module| {
+            matches!(
+                module.symbol,
+                ShipModuleSymbol::MODULE_CARGO_HOLD_I
+                    | ShipModuleSymbol::MODULE_CARGO_HOLD_II
+                    | ShipModuleSymbol::MODULE_CARGO_HOLD_III
+            )
+        })
+        .filter_map(|module| module.capacity)
+        .sum();
+    let ship = Ship {
+        symbol: symbol.clone(),
+        registration: ShipRegistration {
+            name: symbol.clone(),
+            faction_symbol: agent.starting_faction.clone(),
+            role: match listing.ship_type {
+                ShipType::SHIP_PROBE => ShipRole::SATELLITE,
+                ShipType::SHIP_MINING_DRONE | ShipType::SHIP_ORE_HOUND => ShipRole::EXCAVATOR,
+                ShipType::SHIP_SIPHON_DRONE => ShipRole::HARVESTER,
+                ShipType::SHIP_SURVEYOR => ShipRole::SURVEYOR,
+                _ => ShipRole::HAULER,
+            },
+        },
+        nav: ShipNav {
+            system_symbol: waypoint.system_symbol.clone(),
+            waypoint_symbol: waypoint.symbol.clone(),
+            route: ShipNavRoute {
+                destination: here.clone(),
+                origin: here,
+                departure_time: now,
+                arrival: now,
+            },
+            status: ShipNavStatus::DOCKED,
+            flight_mode: ShipNavFlightMode::CRUISE,
+        },
+        crew: ShipCrew {
+            current: listing.crew.required,
+            required: listing.crew.required,
+            capacity: listing.crew.capacity,
+            rotation: CrewRotation::STRICT,
+            morale: 100,
+            wages: 0,
+        },
+        fuel: ShipFuel {
+            current: listing.frame.fuel_capacity,
+            capacity: listing.frame.fuel_capacity,
+            consumed: None,
+        },
+        frame: listing.frame,
+        reactor: listing.reactor,
+        engine: listing.engine,
+        cooldown: Cooldown {
+            ship_symbol: symbol.clone(),
+            total_seconds: 0,
+            remaining_seconds: 0,
+            expiration: None,
+        },
+        modules: listing.modules,
+        mounts: listing.mounts,
+        cargo: ShipCargo {
+            capacity: cargo_capacity,
+            units: 0,
+            inventory: Vec::new(),
+        },
+    };
+    let transaction = ShipyardTransaction {
+        waypoint_symbol: body.waypoint_symbol,
+        // deprecated in the spec, and sent as the ship type
+        ship_symbol: listing.ship_type.to_string(),
+        ship_type: listing.ship_type.clone(),
+        price: listing.purchase_price,
+        agent_symbol: agent.symbol.clone(),
+        timestamp: now,
+    };
+    shipyard.transactions.push(transaction.clone());
+    ships.push(ship.clone());
+    agent.ship_count = Some(ships.len() as i32);
+    data(PurchaseShipResponse {
+        agent: agent.clone(),
+        ship,
+        transaction,
+    })
+}
+
+// ---- CONTRACTS ----
+
+async fn contracts(State(state): State<Arc<FakeState>>) -> FakeResult<Vec<Contract>> {
+    data(state.world().contracts.clone())
+}
+
+async fn contract(
+    State(state): State<Arc<FakeState>>,
+    Path(contract_id): Path<String>,
+) -> FakeResult<Contract> {
+    data(find_contract(&mut state.world().contracts, &contract_id)?.clone())
+}
+
+async fn accept_contract(
+    State(state): State<Arc<FakeState>>,
+    Path(contract_id): Path<String>,
+) -> FakeResult<AcceptContractResponse> {
+    let mut world = state.world();
+    let World {
+        agent, contracts, ..
+    } = &mut *world;
+    let contract = find_contract(contracts, &contract_id)?;
+    if contract.accepted {
+        return Err(rejected(
+            4501,
+            format!("contract {contract_id} has already been accepted"),
+        ));
+    }
+    contract.accepted = true;
+    agent.credits += i64::from(contract.terms.payment.on_accepted);
+    data(AcceptContractResponse {
+        agent: agent.clone(),
+        contract: contract.clone(),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeliverBody {
+    ship_symbol: String,
+    trade_symbol: String,
+    units: i32,
+}
+
+async fn deliver_contract(
+    State(state): State<Arc<FakeState>>,
+    Path(contract_id): Path<String>,
+    Json(body): Json<DeliverBody>,
+) -> FakeResult<DeliverCargoResponse> {
+    let mut world = state.world();
+    let World {
+        ships, contracts, ..
+    } = &mut *world;
+    let contract = find_contract(contracts, &contract_id)?;
+    if !contract.accepted || contract.fulfilled {
+        return Err(rejected(
+            4502,
+            format!("contract {contract_id} isn't open for deliveries"),
+        ));
+    }
+    let ship = find_ship(ships, &body.ship_symbol)?;
+    ensure_status(ship, ShipNavStatus::DOCKED)?;
+    let terms = contract
+        .terms
+        .deliver
+        .iter_mut()
+        .find(|terms| {
+            terms.trade_symbol == body.trade_symbol
+                && terms.destination_symbol == ship.nav.waypoint_symbol
+        })
+        .ok_or_else(|| {
+            rejected(
+                4508,
+                format!(
+                    "contract {contract_id} takes no {} at {}",
+                    body.trade_symbol, ship.nav.waypoint_symbol
+                ),
+            )
+        })?;
+    if terms.units_fulfilled + body.units > terms.units_required {
+        return Err(rejected(
+            4509,
+            format!(
+                "only {} more {} are needed",
+                terms.units_required - terms.units_fulfilled,
+                body.trade_symbol
+            ),
+        ));
+    }
+    remove_cargo(&mut ship.cargo, &body.trade_symbol, body.units)?;
+    terms.units_fulfilled += body.units;
+    data(DeliverCargoResponse {
+        cargo: ship.cargo.clone(),
+        contract: contract.clone(),
+    })
+}
+
+async fn fulfill_contract(
+    State(state): State<Arc<FakeState>>,
+    Path(contract_id): Path<String>,
+) -> FakeResult<FulfillContractResponse> {
+    let mut world = state.world();
+    let World {
+        agent, contracts, ..
+    } = &mut *world;
+    let contract = find_contract(contracts, &contract_id)?;
+    let delivered = contract
+        .terms
+        .deliver
+        .iter()
+        .all(|terms| terms.units_fulfilled >= terms.units_required);
+    if !contract.accepted || contract.fulfilled || !delivered {
+        return Err(rejected(
+            4502,
+            format!("contract {contract_id} can't be fulfilled yet"),
+        ));
+    }
+    contract.fulfilled = true;
+    agent.credits += i64::from(contract.terms.payment.on_fulfilled);
+    data(FulfillContractResponse {
+        agent: agent.clone(),
+        contract: contract.clone(),
+    })
+}
+
+// ---- SYSTEMS ----
+
+#[derive(Deserialize)]
+struct PageQuery {
+    page: Option<usize>,
+    limit: Option<usize>,
+}
+
+async fn waypoints(
+    State(state): State<Arc<FakeState>>,
+    Path(system_symbol): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> FakeResult<Vec<Waypoint>> {
+    let world = state.world();
+    let in_system: Vec<&Waypoint> = world
+        .waypoints
+        .iter()
+        .filter(|waypoint| waypoint.system_symbol == system_symbol)
+        .collect();
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(10).clamp(1, MAX_PAGE_LIMIT);
+    let data = in_system
+        .iter()
+        .skip((page - 1) * limit)
+        .take(limit)
+        .map(|waypoint| (*waypoint).clone())
+        .collect();
+    Ok(Json(ApiSuccessResponse {
+        data,
+        meta: Some(Meta {
+            total: in_system.len() as i32,
+            page: page as i32,
+            limit: limit as i32,
+        }),
+    }))
+}
+
+async fn waypoint(
+    State(state): State<Arc<FakeState>>,
+    Path((_, waypoint_symbol)): Path<(String, String)>,
+) -> FakeResult<Waypoint> {
+    data(find_waypoint(&state.world().waypoints, &waypoint_symbol)?.clone())
+}
+
+/// Prices and transactions are only shown while one of our ships is there
+async fn market(
+    State(state): State<Arc<FakeState>>,
+    Path((_, waypoint_symbol)): Path<(String, String)>,
+) -> FakeResult<Market> {
+    let world = state.world();
+    let mut market = world
+        .markets
+        .iter()
+        .find(|market| market.symbol == waypoint_symbol)
+        .cloned()
+        .ok_or_else(|| not_found(&format!("market at {waypoint_symbol}")))?;
+    let present = world.ships.iter().any(|ship| {
+        ship.nav.waypoint_symbol == waypoint_symbol && ship.nav.status != ShipNavStatus::IN_TRANSIT
+    });
+    if !present {
+        market.trade_goods.clear();
+        market.transactions.clear();
+    }
+    data(market)
+}
+
+async fn shipyard(
+    State(state): State<Arc<FakeState>>,
+    Path((_, waypoint_symbol)): Path<(String, String)>,
+) -> FakeResult<Shipyard> {
+    let world = state.world();
+    let shipyard = world
+        .shipyards
+        .iter()
+        .find(|shipyard| shipyard.symbol == waypoint_symbol)
+        .cloned()
+        .ok_or_else(|| not_found(&format!("shipyard at {waypoint_symbol}")))?;
+    data(shipyard)
+}
+
+async fn jump_gate(
+    State(state): State<Arc<FakeState>>,
+    Path((_, waypoint_symbol)): Path<(String, String)>,
+) -> FakeResult<JumpGate> {
+    let world = state.world();
+    let jump_gate = world
+        .jump_gates
+        .get(&waypoint_symbol)
+        .cloned()
+        .ok_or_else(|| not_found(&format!("jump gate at {waypoint_symbol}")))?;
+    data(jump_gate)
+}
@@ -0,0 +1,228 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{MarketTransaction, MarketTransactionType, ShipyardTransaction};
+
+const LEDGER_FILE: &str = "ledger.jsonl";
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerKind {
+    /// Cargo sold at a market
+    Sell,
+    /// Cargo bought at a market
+    Purchase,
+    /// Fuel bought when refueling
+    Fuel,
+    /// A ship bought at a shipyard
+    Ship,
+}
+
+/// One credit-moving transaction, whichever endpoint it came back from
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub timestamp: DateTime<Utc>,
+    pub kind: LedgerKind,
+    pub ship_symbol: String,
+    pub waypoint_symbol: String,
+    /// Trade good, or ship type for ship purchases
+    pub item: String,
+    pub units: i32,
+    pub price_per_unit: i64,
+    pub total_price: i64,
+}
+
+impl LedgerEntry {
+    pub fn from_market_transaction(transaction: &MarketTransaction) -> Self {
+        let kind = match transaction.transaction_type {
+            MarketTransactionType::SELL => LedgerKind::Sell,
+            // Only sell, purchase and refuel responses are recorded, so anything else spent
+            // credits
+            _ if transaction.trade_symbol == "FUEL" => LedgerKind::Fuel,
+            _ => LedgerKind::Purchase,
+        };
+        LedgerEntry {
+            timestamp: transaction.timestamp,
+            kind,
+            ship_symbol: transaction.ship_symbol.clone(),
+            waypoint_symbol: transaction.waypoint_symbol.clone(),
+            item: transaction.trade_symbol.clone(),
+            units: transaction.units,
+            price_per_unit: transaction.price_per_unit as i64,
+            total_price: transaction.total_price as i64,
+        }
+    }
+
+    /// The transaction's own ship symbol is the deprecated ship type, so the symbol of the ship
+    /// bought comes from the rest of the response
+    pub fn from_shipyard_transaction(transaction: &ShipyardTransaction, ship_symbol: &str) -> Self {
+        LedgerEntry {
+            timestamp: transaction.timestamp,
+            kind: LedgerKind::Ship,
+            ship_symbol: ship_symbol.to_owned(),
+            waypoint_symbol: transaction.waypoint_symbol.clone(),
+            item: transaction.ship_type.to_string(),
+            units: 1,
+            price_per_unit: transaction.price as i64,
+            total_price: transaction.price as i64,
+        }
+    }
+}
+
+/// Append-only JSON lines file of every transaction made through the API
+pub struct Ledger {
+    path: PathBuf,
+}
+
+impl Ledger {
+    pub fn open(dir: &Path) -> Self {
+        Ledger {
+            path: dir.join(LEDGER_FILE),
+        }
+    }
+
+    pub fn append(&self, entry: &LedgerEntry) -> std::io::Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, entry)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+
+    pub fn all(&self) -> std::io::Result<Vec<LedgerEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let reader = BufReader::new(File::open(&self.path)?);
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(entries)
+    }
+
+    pub fn export_csv<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for entry in self.all()? {
+            writer.serialize(entry)?;
+        }
+        writer.flush()
+    }
+
+    pub fn export_json<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for entry in self.all()? {
+            serde_json::to_writer(&mut writer, &entry)?;
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+}
+
+/// Credits in and out, all as positive amounts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProfitSummary {
+    pub income: i64,
+    pub fuel: i64,
+    pub purchases: i64,
+    pub ships: i64,
+}
+
+impl ProfitSummary {
+    pub fn add(&mut self, entry: &LedgerEntry) {
+        match entry.kind {
+            LedgerKind::Sell => self.income += entry.total_price,
+            LedgerKind::Purchase => self.purchases += entry.total_price,
+            LedgerKind::Fuel => self.fuel += entry.total_price,
+            LedgerKind::Ship => self.ships += entry.total_price,
+        }
+    }
+
+    pub fn net(&self) -> i64 {
+        self.income - self.fuel - self.purchases - self.ships
+    }
+}
+
+impl fmt::Display for ProfitSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "income {:>9}  fuel {:>8}  goods {:>9}  ships {:>9}  net {:>10}",
+            self.income,
+            self.fuel,
+            self.purchases,
+            self.ships,
+            self.net()
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LedgerReport {
+    pub total: ProfitSummary,
+    pub per_ship: BTreeMap<String, ProfitSummary>,
+    /// Only cargo sales and purchases, fuel and ships are left out
+    pub per_good: BTreeMap<String, ProfitSummary>,
+}
+
+pub fn report(entries: &[LedgerEntry], since: Option<DateTime<Utc>>) -> LedgerReport {
+    let mut report = LedgerReport::default();
+    for entry in entries
+        .iter()
+        .filter(|entry| since.is_none_or(|since| entry.timestamp >= since))
+    {
+        report.total.add(entry);
+        report
+            .per_ship
+            .entry(entry.ship_symbol.clone())
+            .or_default()
+            .add(entry);
+        if matches!(entry.kind, LedgerKind::Sell | LedgerKind::Purchase) {
+            report
+                .per_good
+                .entry(entry.item.clone())
+                .or_default()
+                .add(entry);
+        }
+    }
+    report
+}
+
+/// Parses `--since` as an RFC 3339 timestamp, a date, or a span back from now like `30m`,
+/// `12h` or `7d`
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    }
+    let invalid = || format!("invalid time `{value}`, expected e.g. 2023-08-01, 12h or 7d");
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let span = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    Utc::now().checked_sub_signed(span).ok_or_else(invalid)
+}
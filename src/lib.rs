@@ -1,24 +1,77 @@
 #![allow(non_camel_case_types)]
 
-use std::{
-    error::Error,
-    fs::{self, File},
-    path::PathBuf,
-};
+use std::{error::Error, fs, net::SocketAddr, path::PathBuf};
 
 pub mod api;
+pub mod completions;
+pub mod contract;
+pub mod control;
+pub mod daemon;
+pub mod dashboard;
 pub mod domain;
+pub mod error;
+pub mod estimator;
+pub mod fake_server;
+pub mod ledger;
+pub mod market_history;
+pub mod mining;
+pub mod navigation;
+pub mod notes;
+pub mod rate_limit;
+pub mod recording;
+pub mod repl;
+pub mod route;
+pub mod script;
+pub mod selling;
+pub mod settings;
+pub mod ship_state;
+pub mod signal;
+pub mod strategy;
+pub mod symbol_cache;
+pub mod system_export;
+pub mod system_map;
+pub mod trade;
 
 use api::Api;
-use clap::{Args, Parser, Subcommand};
+use chrono::{DateTime, Utc};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use completions::CompletionShell;
+use contract::ContractRuns;
 use domain::*;
+use error::{AppError, AppResult};
+use ledger::Ledger;
+use market_history::MarketHistory;
+use recording::Recording;
 use serde::{Deserialize, Serialize};
+use settings::Settings;
+use signal::StopSignal;
+use symbol_cache::SymbolCache;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about=None)]
 pub struct AppArgs {
     #[command(subcommand)]
     command: Option<Command>,
+
+    /// Dock or orbit ships automatically before actions that need it, `--auto-state=false`
+    /// turns it off. Defaults to the `auto_state` setting, which is on unless changed.
+    #[arg(
+        long,
+        global = true,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    auto_state: Option<bool>,
+
+    /// Save every API request and response in this directory
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer API requests from responses saved with --record instead of the network
+    #[arg(long, global = true, value_name = "DIR")]
+    replay: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -38,7 +91,224 @@ enum Command {
     /// Show current player's details
     WhoAmI,
     Waypoint(WaypointSubCommand),
+    System(SystemSubCommand),
     Ship(ShipSubCommand),
+    Route(RouteSubCommand),
+    Market(MarketSubCommand),
+    Trade(TradeSubCommand),
+    Ledger(LedgerSubCommand),
+    /// Estimate fuel and travel time to a waypoint in every flight mode
+    Estimate {
+        #[arg(short = 's', long, visible_alias = "ship")]
+        ship_symbol: String,
+        #[arg(short = 't', long = "to")]
+        destination: String,
+    },
+    Config(ConfigSubCommand),
+    /// Run a file of commands, one per line, through a single API client
+    RunScript {
+        file: PathBuf,
+        /// Keep going after a failed command instead of stopping, the exit code still
+        /// reflects the first failure
+        #[arg(long)]
+        continue_on_error: bool,
+        /// Set a script variable, e.g. --var SHIP=ALI-1
+        #[arg(long = "var", value_parser = script::parse_var)]
+        vars: Vec<(String, String)>,
+    },
+    /// Interactive prompt running commands through one API client, with `use ship SYMBOL`
+    /// and friends to leave out symbols the following commands need
+    Shell,
+    /// Full-screen overview of the agent, ships, contracts and recent transactions
+    Dashboard,
+    Script(ScriptSubCommand),
+    Daemon(DaemonSubCommand),
+    /// Print a bash, zsh or fish completion script, which also completes ship, waypoint and
+    /// contract symbols seen in earlier commands
+    Completions {
+        #[arg(required_unless_present = "words")]
+        shell: Option<CompletionShell>,
+        /// Command line to complete symbols for, used by the completion scripts
+        #[arg(last = true, hide = true)]
+        words: Vec<String>,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct DaemonSubCommand {
+    #[command(subcommand)]
+    command: DaemonSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum DaemonSubCommandArgs {
+    /// Keep every assigned ship busy with its behaviour until Ctrl-C or SIGTERM
+    Run {
+        /// Ship assignments file, daemon.json in the user directory by default
+        #[arg(short = 'c', long = "config")]
+        file: Option<PathBuf>,
+        /// Address to serve the control API on
+        #[arg(long, default_value = control::DEFAULT_ADDR)]
+        control: SocketAddr,
+    },
+    /// Show each ship's behaviour and what it's doing
+    Status {
+        /// Address of the daemon's control API
+        #[arg(long, default_value = control::DEFAULT_ADDR)]
+        control: SocketAddr,
+    },
+    /// Give a ship a new behaviour, kept for the next start as well
+    Assign {
+        #[arg(short = 's', long, visible_alias = "ship")]
+        ship_symbol: String,
+        #[command(flatten)]
+        behaviour: daemon::BehaviourArgs,
+        /// Address of the daemon's control API
+        #[arg(long, default_value = control::DEFAULT_ADDR)]
+        control: SocketAddr,
+    },
+    /// Pause every ship, or just one, once its current step is done
+    Pause {
+        #[arg(short = 's', long, visible_alias = "ship")]
+        ship_symbol: Option<String>,
+        /// Address of the daemon's control API
+        #[arg(long, default_value = control::DEFAULT_ADDR)]
+        control: SocketAddr,
+    },
+    /// Resume every paused ship, or just one
+    Resume {
+        #[arg(short = 's', long, visible_alias = "ship")]
+        ship_symbol: Option<String>,
+        /// Address of the daemon's control API
+        #[arg(long, default_value = control::DEFAULT_ADDR)]
+        control: SocketAddr,
+    },
+    /// Show the daemon's recent events
+    Events {
+        /// Keep printing new events until Ctrl-C
+        #[arg(short = 'f', long)]
+        follow: bool,
+        /// Address of the daemon's control API
+        #[arg(long, default_value = control::DEFAULT_ADDR)]
+        control: SocketAddr,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct ScriptSubCommand {
+    #[command(subcommand)]
+    command: ScriptSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum ScriptSubCommandArgs {
+    /// Run a Rhai strategy script with access to ships, markets, contracts and sleeps
+    Run { file: PathBuf },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct MarketSubCommand {
+    #[command(subcommand)]
+    command: MarketSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum MarketSubCommandArgs {
+    /// Show recorded prices and supply of a good at a market over time, and its transactions
+    History {
+        #[arg(short = 'w', long, visible_alias = "waypoint")]
+        waypoint_symbol: String,
+        #[arg(short = 'g', long, visible_alias = "good")]
+        good_symbol: TradeSymbol,
+    },
+    /// Dump the recorded market history as CSV
+    Export {
+        /// File to write to instead of stdout
+        #[arg(short = 'o', long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct TradeSubCommand {
+    #[command(subcommand)]
+    command: TradeSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum TradeSubCommandArgs {
+    /// Rank buy-here/sell-there pairs in the ship's system using recorded market prices
+    Routes {
+        #[arg(short = 's', long, visible_alias = "ship")]
+        ship_symbol: String,
+        #[arg(long, value_enum, default_value_t)]
+        sort_by: trade::TradeRouteSort,
+        /// Only show the best N routes
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct LedgerSubCommand {
+    #[command(subcommand)]
+    command: LedgerSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum LedgerSubCommandArgs {
+    /// Summarise income, fuel spend, purchases and net profit per ship and per good
+    Report {
+        /// Only count transactions after this time, e.g. 2023-08-01, 12h or 7d
+        #[arg(long, value_parser = ledger::parse_since)]
+        since: Option<DateTime<Utc>>,
+    },
+    /// Dump every recorded transaction, as JSON lines unless --csv is given
+    Export {
+        #[arg(long)]
+        csv: bool,
+        /// File to write to instead of stdout
+        #[arg(short = 'o', long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct RouteSubCommand {
+    #[command(subcommand)]
+    command: RouteSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum RouteSubCommandArgs {
+    /// Plan the fastest route to a waypoint in the ship's system, with refuel stops
+    Plan {
+        #[arg(short = 's', long, visible_alias = "ship")]
+        ship_symbol: String,
+        #[arg(short = 't', long = "to")]
+        destination: String,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct ConfigSubCommand {
+    #[command(subcommand)]
+    command: ConfigSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigSubCommandArgs {
+    /// Show the current settings
+    Show,
+    /// Save the global options given with it as the defaults, e.g. `--auto-state=false`
+    Set,
 }
 
 #[derive(Debug, Args)]
@@ -69,6 +339,26 @@ enum ContractSubCommandArgs {
         #[arg(short = 'c', long)]
         contract_id: String,
     },
+    /// Estimate a contract's net profit, cargo trips and whether our ships can meet its
+    /// deadline, from recorded market prices
+    Evaluate {
+        #[arg(short = 'c', long, visible_alias = "contract")]
+        contract_id: String,
+    },
+    /// Source, deliver and fulfill a contract, resuming any interrupted run
+    Run {
+        #[arg(short = 'c', long)]
+        contract_id: String,
+        /// Ship to run the contract with, optional when resuming
+        #[arg(short = 's', long)]
+        ship_symbol: Option<String>,
+        /// Buy the goods at this market
+        #[arg(short = 'b', long, conflicts_with = "mine_at")]
+        buy_at: Option<String>,
+        /// Mine the goods at this asteroid field, jettisoning anything else
+        #[arg(short = 'm', long)]
+        mine_at: Option<String>,
+    },
 }
 
 #[derive(Debug, Args)]
@@ -100,6 +390,37 @@ enum WaypointSubCommandArgs {
     },
 }
 
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct SystemSubCommand {
+    #[command(subcommand)]
+    command: SystemSubCommandArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum SystemSubCommandArgs {
+    /// Plot the waypoints and our ships, including those in transit, on a grid
+    Map {
+        /// Defaults to the headquarters' system
+        #[arg(long)]
+        system: Option<String>,
+    },
+    /// Draw the waypoints, orbitals, traits and our ships as an SVG picture or Graphviz graph
+    Export {
+        /// Defaults to the headquarters' system
+        #[arg(long)]
+        system: Option<String>,
+        #[arg(short = 'f', long, value_enum)]
+        format: system_export::ExportFormat,
+        /// Also draw the systems the jump gates lead to
+        #[arg(long)]
+        jump_gates: bool,
+        /// File to write to instead of stdout
+        #[arg(short = 'o', long)]
+        output: Option<PathBuf>,
+    },
+}
+
 #[derive(Debug, Args)]
 #[command(args_conflicts_with_subcommands = true)]
 struct ShipSubCommand {
@@ -151,6 +472,23 @@ enum ShipSubCommandArgs {
         #[arg(short = 's', long)]
         ship_symbol: String,
     },
+    /// Mine, sell and return in a loop until Ctrl-C or the cycle limit
+    Mine {
+        #[arg(short = 's', long)]
+        ship_symbol: String,
+        /// Asteroid field to mine at
+        #[arg(short = 'a', long = "at")]
+        asteroid_field: String,
+        /// Market to sell the haul at
+        #[arg(short = 'm', long)]
+        market: String,
+        /// Goods to keep in the hold instead of selling, may be repeated
+        #[arg(short = 'k', long)]
+        keep: Vec<TradeSymbol>,
+        /// Stop after this many cycles
+        #[arg(short = 'n', long)]
+        cycles: Option<u32>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -164,6 +502,15 @@ enum ShipNavigateSubCommandArgs {
         ship_symbol: String,
         #[arg(short = 'w', long)]
         waypoint_symbol: String,
+        /// Wait until the ship arrives, showing a progress bar
+        #[arg(long)]
+        wait: bool,
+        /// Dock once the ship arrives (implies --wait)
+        #[arg(long)]
+        dock_on_arrival: bool,
+        /// Dock and refuel once the ship arrives (implies --wait)
+        #[arg(long)]
+        refuel_on_arrival: bool,
     },
 }
 
@@ -201,208 +548,507 @@ pub fn get_args() -> MyResult<AppArgs> {
     Ok(AppArgs::parse())
 }
 
-pub async fn run<'a>(args: AppArgs, config: Config) -> MyResult<()> {
-    if let Some(user_info) = auth::check_user_token(&config.current_user_dir) {
-        let api = Api::new(&user_info);
-        match args.command {
-            Some(Command::GenerateDoc) => {
-                fs::write(
-                    "DOCUMENTATION.md",
-                    clap_markdown::help_markdown::<AppArgs>(),
+/// Everything commands share, set up once per process so scripts can run many commands
+/// through the same client and rate limiter
+pub struct Context<'a> {
+    pub config: &'a Config,
+    pub user_info: &'a UserInfo,
+    pub api: Api<'a>,
+    pub settings: Settings,
+    pub market_history: MarketHistory,
+}
+
+pub async fn run(args: AppArgs, config: Config) -> AppResult<()> {
+    let recording = match (&args.record, &args.replay) {
+        (Some(dir), _) => Some(Recording::record(dir)?),
+        (_, Some(dir)) => Some(Recording::replay(dir)?),
+        _ => None,
+    };
+    if let Some(Command::Completions { shell, words }) = &args.command {
+        return print_completions(&config, *shell, words);
+    }
+    if let Some(Command::Register { username, faction }) = &args.command {
+        let res =
+            api::register_player(username.clone(), faction.clone(), recording.as_ref()).await?;
+        auth::save_user_info(&res.data, &config.current_user_dir)?;
+        return Ok(());
+    }
+    let user_info =
+        auth::check_user_token(&config.current_user_dir)?.ok_or(AppError::AuthMissing)?;
+    let mut api = Api::new(&user_info)
+        .with_ledger(Ledger::open(&config.current_user_dir))
+        .with_market_history(MarketHistory::open(&config.current_user_dir))
+        .with_symbol_cache(SymbolCache::open(&config.current_user_dir));
+    if let Some(recording) = recording {
+        api = api.with_recording(recording);
+    }
+    let ctx = Context {
+        config: &config,
+        user_info: &user_info,
+        api,
+        settings: Settings::load(&config.current_user_dir)?,
+        market_history: MarketHistory::open(&config.current_user_dir),
+    };
+    match args.command {
+        Some(Command::RunScript {
+            file,
+            continue_on_error,
+            vars,
+        }) => {
+            let options = script::ScriptOptions {
+                continue_on_error,
+                vars: vars.into_iter().collect(),
+            };
+            script::run_file(&ctx, &file, options).await
+        }
+        Some(Command::Shell) => repl::run(&ctx).await,
+        _ => execute(&ctx, args).await,
+    }
+}
+
+/// Needs neither a login nor the network, so completion works anywhere
+fn print_completions(
+    config: &Config,
+    shell: Option<CompletionShell>,
+    words: &[String],
+) -> AppResult<()> {
+    let mut cmd = AppArgs::command();
+    match shell {
+        Some(shell) => completions::write_script(shell, &mut cmd, &mut std::io::stdout()),
+        None => {
+            let symbols = SymbolCache::open(&config.current_user_dir).load()?;
+            completions::complete(&mut cmd, words, &symbols)
+        }
+    }
+}
+
+/// Runs a single parsed command
+pub async fn execute(ctx: &Context<'_>, args: AppArgs) -> AppResult<()> {
+    let Context {
+        config,
+        user_info,
+        api,
+        settings,
+        market_history,
+    } = ctx;
+    let auto_state = args.auto_state.unwrap_or(settings.auto_state);
+    match args.command {
+        Some(Command::GenerateDoc) => {
+            fs::write(
+                "DOCUMENTATION.md",
+                clap_markdown::help_markdown::<AppArgs>(),
+            )?;
+        }
+        Some(Command::Status) => println!("You are logged in as {:#?}", user_info),
+        Some(Command::Register { username, faction }) => {
+            let res = crate::api::register_player(username, faction, api.recording()).await?;
+            auth::save_user_info(&res.data, &config.current_user_dir)?;
+        }
+        Some(Command::Dashboard) => {
+            dashboard::run(api, &Ledger::open(&config.current_user_dir), auto_state).await?;
+        }
+        Some(Command::WhoAmI) => {
+            eprintln!("fetching Agent info...");
+            let res = api.fetch_agent_info().await?;
+            println!("{:#?}", res);
+        }
+        Some(Command::Contract(ContractSubCommand { command })) => match command {
+            ContractSubCommandArgs::List => {
+                let res = api.fetch_contracts().await?;
+                println!("{:#?}", res);
+                for contract in res.data.iter() {
+                    println!(
+                        "{}: deadline in {}",
+                        contract.id,
+                        format_duration(contract.time_until_deadline())
+                    );
+                }
+            }
+            ContractSubCommandArgs::Accept { contract_id } => {
+                let res = api.accept_contract(contract_id).await?;
+                println!("{:#?}", res);
+            }
+            ContractSubCommandArgs::Deliver {
+                ship_symbol,
+                contract_id,
+                trade_symbol,
+                units,
+            } => {
+                if auto_state {
+                    ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::DOCKED).await?;
+                }
+                let res = api
+                    .deliver_contract_goods(ship_symbol, contract_id, trade_symbol, units)
+                    .await?;
+                println!("{:#?}", res);
+            }
+            ContractSubCommandArgs::Fulfill { contract_id } => {
+                let res = api.fulfill_contract(contract_id).await?;
+                println!("{:#?}", res);
+            }
+            ContractSubCommandArgs::Evaluate { contract_id } => {
+                let evaluation = contract::evaluate(api, market_history, contract_id).await?;
+                println!("{evaluation}");
+            }
+            ContractSubCommandArgs::Run {
+                contract_id,
+                ship_symbol,
+                buy_at,
+                mine_at,
+            } => {
+                let runs = ContractRuns::open(&config.current_user_dir);
+                let contract_run =
+                    contract::resolve_run(&runs, contract_id, ship_symbol, buy_at, mine_at)?;
+                let completed = contract::run(
+                    api,
+                    &runs,
+                    market_history,
+                    &contract_run,
+                    StopSignal::on_ctrl_c(),
+                    &notes::to_stdout,
                 )
-                .expect("Unable to write documentation");
-            }
-            Some(Command::Status) => println!("You are logged in as {:#?}", user_info),
-            Some(Command::Register { username, faction }) => {
-                let res = api.register_player(username, faction).await.unwrap();
-                auth::save_user_info(&res.data, &config.current_user_dir);
-            }
-            Some(Command::WhoAmI) => {
-                println!("fetching Agent info...");
-                let res = api.fetch_agent_info().await;
-                match res {
-                    Ok(res) => println!("{:#?}", res),
-                    Err(e) => println!("{:#?}", e),
+                .await?;
+                if !completed {
+                    eprintln!(
+                        "stopped, run `contract run -c {}` to resume",
+                        contract_run.contract_id
+                    );
                 }
             }
-            Some(Command::Contract(ContractSubCommand { command })) => match command {
-                ContractSubCommandArgs::List => {
-                    let res = api.fetch_contracts().await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => eprintln!("{:#?}", e),
+        },
+        Some(Command::System(SystemSubCommand { command })) => match command {
+            SystemSubCommandArgs::Map { system } => {
+                let system = system.unwrap_or_else(|| user_info.agent.get_system());
+                println!("{}", system_map::map(api, system, false).await?);
+            }
+            SystemSubCommandArgs::Export {
+                system,
+                format,
+                jump_gates,
+                output,
+            } => {
+                let system = system.unwrap_or_else(|| user_info.agent.get_system());
+                let map = system_map::map(api, system, jump_gates).await?;
+                match output {
+                    Some(path) => {
+                        system_export::export(&map, format, &mut fs::File::create(path)?)?
                     }
+                    None => system_export::export(&map, format, &mut std::io::stdout())?,
                 }
-                ContractSubCommandArgs::Accept { contract_id } => {
-                    let res = api.accept_contract(contract_id).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => eprintln!("{:#?}", e),
-                    }
+            }
+        },
+        Some(Command::Waypoint(WaypointSubCommand { command })) => match command {
+            WaypointSubCommandArgs::Get { waypoint_symbol } => {
+                let res = api.get_waypoint(waypoint_symbol).await?;
+                println!("{:#?}", res);
+            }
+            WaypointSubCommandArgs::List {
+                filter_by_trait,
+                filter_by_type,
+            } => {
+                let res = api
+                    .list_waypoints(
+                        user_info.agent.get_system(),
+                        filter_by_trait,
+                        filter_by_type,
+                    )
+                    .await?;
+                println!("{:#?}", res)
+            }
+            WaypointSubCommandArgs::Market { waypoint_symbol } => {
+                let res = api.get_market(waypoint_symbol).await?;
+                println!("{:#?}", res);
+            }
+            WaypointSubCommandArgs::Shipyard { waypoint_symbol } => {
+                let res = api.get_shipyard_for_waypoint(waypoint_symbol).await?;
+                println!("{:#?}", res);
+            }
+        },
+        Some(Command::Ship(ShipSubCommand { command })) => match command {
+            ShipSubCommandArgs::Survey { ship_symbol } => {
+                if auto_state {
+                    ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::IN_ORBIT).await?;
                 }
-                ContractSubCommandArgs::Deliver {
+                let res = api.create_survey(ship_symbol).await?;
+                println!("{:#?}", res);
+            }
+            ShipSubCommandArgs::Mine {
+                ship_symbol,
+                asteroid_field,
+                market,
+                keep,
+                cycles,
+            } => {
+                let options = mining::MiningOptions {
                     ship_symbol,
-                    contract_id,
-                    trade_symbol,
-                    units,
-                } => {
-                    let res = api
-                        .deliver_contract_goods(ship_symbol, contract_id, trade_symbol, units)
-                        .await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => eprintln!("{:#?}", e),
-                    }
-                }
-                ContractSubCommandArgs::Fulfill { contract_id } => {
-                    let res = api.fulfill_contract(contract_id).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => eprintln!("{:#?}", e),
-                    }
+                    asteroid_field,
+                    market,
+                    keep,
+                    cycles,
+                };
+                mining::mine(api, &options, StopSignal::on_ctrl_c(), &notes::to_stdout).await?;
+            }
+            ShipSubCommandArgs::Purchase {
+                ship_type,
+                waypoint_symbol,
+            } => {
+                if auto_state {
+                    ship_state::ensure_docked_at(api, &waypoint_symbol).await?;
                 }
-            },
-            Some(Command::Waypoint(WaypointSubCommand { command })) => match command {
-                WaypointSubCommandArgs::Get { waypoint_symbol } => {
-                    let res = api.get_waypoint(waypoint_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => eprintln!("{:#?}", e),
-                    }
+                let res = api.purchase_ship(ship_type, waypoint_symbol).await?;
+                println!("{:#?}", res);
+            }
+            ShipSubCommandArgs::List => {
+                let res = api.list_ships().await?;
+                println!("{:#?}", res);
+            }
+            ShipSubCommandArgs::Orbit { ship_symbol } => {
+                let res = api.orbit_ship(ship_symbol).await?;
+                println!("{:#?}", res);
+            }
+            ShipSubCommandArgs::Dock { ship_symbol } => {
+                let res = api.dock_ship(ship_symbol).await?;
+                println!("{:#?}", res);
+            }
+            ShipSubCommandArgs::Status { ship_symbol } => {
+                let res = api.get_ship_status(ship_symbol).await?;
+                println!("{:#?}", res);
+                println!("{}", res.data.nav.route.arrival_description());
+            }
+            ShipSubCommandArgs::Refuel { ship_symbol, units } => {
+                if auto_state {
+                    ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::DOCKED).await?;
                 }
-                WaypointSubCommandArgs::List {
-                    filter_by_trait,
-                    filter_by_type,
-                } => {
-                    let res = api
-                        .list_waypoints(
-                            user_info.agent.get_system(),
-                            filter_by_trait,
-                            filter_by_type,
-                        )
-                        .await
-                        .unwrap();
-                    println!("{:#?}", res)
+                let res = api.refuel_ship(ship_symbol, units).await?;
+                println!("{:#?}", res);
+            }
+            ShipSubCommandArgs::Extract { ship_symbol } => {
+                if auto_state {
+                    ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::IN_ORBIT).await?;
                 }
-                WaypointSubCommandArgs::Market { waypoint_symbol } => {
-                    let res = api.get_market(waypoint_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => eprintln!("{:#?}", e),
-                    }
+                let res = api.extract_resource(ship_symbol, None).await?;
+                println!("{:#?}", res);
+                println!("{}", res.data.cooldown.expiration_description());
+            }
+            ShipSubCommandArgs::Navigate { command } => match command {
+                ShipNavigateSubCommandArgs::Status { ship_symbol } => {
+                    let res = api.get_ship_nav_status(ship_symbol).await?;
+                    println!("{:#?}", res);
+                    println!("{}", res.data.route.arrival_description());
                 }
-                WaypointSubCommandArgs::Shipyard { waypoint_symbol } => {
-                    let res = api.get_shipyard_for_waypoint(waypoint_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => eprintln!("{:#?}", e),
-                    }
+                ShipNavigateSubCommandArgs::Waypoint {
+                    ship_symbol,
+                    waypoint_symbol,
+                    wait,
+                    dock_on_arrival,
+                    refuel_on_arrival,
+                } => {
+                    let actions = navigation::ArrivalActions {
+                        wait,
+                        dock: dock_on_arrival,
+                        refuel: refuel_on_arrival,
+                        auto_state,
+                    };
+                    navigation::navigate(api, ship_symbol, waypoint_symbol, actions).await?;
                 }
             },
-            Some(Command::Ship(ShipSubCommand { command })) => match command {
-                ShipSubCommandArgs::Survey { ship_symbol } => {
-                    let res = api.create_survey(ship_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
-                    }
+            ShipSubCommandArgs::Cargo { command } => match command {
+                ShipCargoSubCommandArgs::Status { ship_symbol } => {
+                    let res = api.get_ship_cargo(ship_symbol).await?;
+                    println!("{:#?}", res);
                 }
-                ShipSubCommandArgs::Purchase {
-                    ship_type,
-                    waypoint_symbol,
+                ShipCargoSubCommandArgs::Sell {
+                    ship_symbol,
+                    good_symbol,
+                    units,
                 } => {
-                    let res = api.purchase_ship(ship_type, waypoint_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
+                    if auto_state {
+                        ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::DOCKED).await?;
                     }
+                    let res = api.sell_ship_cargo(ship_symbol, good_symbol, units).await?;
+                    println!("{:#?}", res);
                 }
-                ShipSubCommandArgs::List => {
-                    let res = api.list_ships().await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
-                    }
+            },
+        },
+        Some(Command::Market(MarketSubCommand { command })) => match command {
+            MarketSubCommandArgs::History {
+                waypoint_symbol,
+                good_symbol,
+            } => {
+                let history = market_history.history(&waypoint_symbol, &good_symbol.to_string())?;
+                if history.is_empty() {
+                    eprintln!("No prices recorded for {good_symbol} at {waypoint_symbol} yet, run `waypoint market` with a ship there");
                 }
-                ShipSubCommandArgs::Orbit { ship_symbol } => {
-                    let res = api.orbit_ship(ship_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
-                    }
+                for record in history {
+                    println!(
+                        "{}  buy {:>6}  sell {:>6}  volume {:>4}  {}",
+                        record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        record.purchase_price,
+                        record.sell_price,
+                        record.trade_volume,
+                        record.supply
+                    );
                 }
-                ShipSubCommandArgs::Dock { ship_symbol } => {
-                    let res = api.dock_ship(ship_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
+                let transactions: Vec<_> = market_history
+                    .transactions(&waypoint_symbol)?
+                    .into_iter()
+                    .filter(|t| t.trade_symbol == good_symbol.to_string())
+                    .collect();
+                if !transactions.is_empty() {
+                    println!();
+                    for transaction in transactions {
+                        println!(
+                            "{}  {:<8} {:>4} at {:>6}  {}",
+                            transaction.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                            transaction.transaction_type,
+                            transaction.units,
+                            transaction.price_per_unit,
+                            transaction.ship_symbol
+                        );
                     }
                 }
-                ShipSubCommandArgs::Status { ship_symbol } => {
-                    let res = api.get_ship_status(ship_symbol).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
-                    }
+            }
+            MarketSubCommandArgs::Export { output } => match output {
+                Some(path) => market_history.export_csv(fs::File::create(path)?)?,
+                None => market_history.export_csv(std::io::stdout())?,
+            },
+        },
+        Some(Command::Trade(TradeSubCommand { command })) => match command {
+            TradeSubCommandArgs::Routes {
+                ship_symbol,
+                sort_by,
+                limit,
+            } => {
+                let routes = trade::routes(api, market_history, ship_symbol, sort_by).await?;
+                if routes.is_empty() {
+                    eprintln!("No profitable routes in recorded market data, visit more markets with `waypoint market`");
                 }
-                ShipSubCommandArgs::Refuel { ship_symbol, units } => {
-                    let res = api.refuel_ship(ship_symbol, units).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
-                    }
+                for route in routes.iter().take(limit) {
+                    println!("{}", route);
                 }
-                ShipSubCommandArgs::Extract { ship_symbol } => {
-                    let res = api.extract_resource(ship_symbol, None).await;
-                    match res {
-                        Ok(res) => println!("{:#?}", res),
-                        Err(e) => println!("{:#?}", e),
+            }
+        },
+        Some(Command::Ledger(LedgerSubCommand { command })) => {
+            let ledger = Ledger::open(&config.current_user_dir);
+            match command {
+                LedgerSubCommandArgs::Report { since } => {
+                    let entries = ledger.all()?;
+                    if entries.is_empty() {
+                        eprintln!("No transactions recorded yet, they are added as ships buy, sell and refuel");
                     }
-                }
-                ShipSubCommandArgs::Navigate { command } => match command {
-                    ShipNavigateSubCommandArgs::Status { ship_symbol } => {
-                        let res = api.get_ship_nav_status(ship_symbol).await;
-                        match res {
-                            Ok(res) => println!("{:#?}", res),
-                            Err(e) => println!("{:#?}", e),
-                        }
+                    let report = ledger::report(&entries, since);
+                    match since {
+                        Some(since) => println!("since {}", since.format("%Y-%m-%d %H:%M:%S")),
+                        None => println!("all time"),
                     }
-                    ShipNavigateSubCommandArgs::Waypoint {
-                        ship_symbol,
-                        waypoint_symbol,
-                    } => {
-                        let res = api.navigate_ship(ship_symbol, waypoint_symbol).await;
-                        match res {
-                            Ok(res) => println!("{:#?}", res),
-                            Err(e) => println!("{:#?}", e),
-                        }
+                    println!("{:<20} {}", "total", report.total);
+                    println!("per ship:");
+                    for (ship_symbol, summary) in report.per_ship.iter() {
+                        println!("  {:<18} {}", ship_symbol, summary);
                     }
-                },
-                ShipSubCommandArgs::Cargo { command } => match command {
-                    ShipCargoSubCommandArgs::Status { ship_symbol } => {
-                        let res = api.get_ship_cargo(ship_symbol).await;
-                        match res {
-                            Ok(res) => println!("{:#?}", res),
-                            Err(e) => eprintln!("{:#?}", e),
-                        }
+                    println!("per good:");
+                    for (good, summary) in report.per_good.iter() {
+                        println!("  {:<18} {}", good, summary);
                     }
-                    ShipCargoSubCommandArgs::Sell {
-                        ship_symbol,
-                        good_symbol,
-                        units,
-                    } => {
-                        let res = api.sell_ship_cargo(ship_symbol, good_symbol, units).await;
-                        match res {
-                            Ok(res) => println!("{:#?}", res),
-                            Err(e) => eprintln!("{:#?}", e),
-                        }
+                }
+                LedgerSubCommandArgs::Export { csv, output } => {
+                    let writer: Box<dyn std::io::Write> = match output {
+                        Some(path) => Box::new(fs::File::create(path)?),
+                        None => Box::new(std::io::stdout()),
+                    };
+                    if csv {
+                        ledger.export_csv(writer)?;
+                    } else {
+                        ledger.export_json(writer)?;
                     }
-                },
-            },
-            None => println!("invalid command"),
+                }
+            }
         }
-    } else {
-        println!("please log in first by typing st-app login <username>")
+        Some(Command::Route(RouteSubCommand { command })) => match command {
+            RouteSubCommandArgs::Plan {
+                ship_symbol,
+                destination,
+            } => {
+                let plan = route::plan(api, ship_symbol, destination).await?;
+                println!("{}", plan);
+            }
+        },
+        Some(Command::Estimate {
+            ship_symbol,
+            destination,
+        }) => estimator::estimate(api, ship_symbol, destination).await?,
+        Some(Command::Config(ConfigSubCommand { command })) => match command {
+            ConfigSubCommandArgs::Show => println!("{:#?}", settings),
+            ConfigSubCommandArgs::Set => {
+                let mut settings = settings.clone();
+                if let Some(new_auto_state) = args.auto_state {
+                    settings.auto_state = new_auto_state;
+                }
+                settings.save(&config.current_user_dir)?;
+                println!("{:#?}", settings);
+            }
+        },
+        Some(Command::Script(ScriptSubCommand { command })) => match command {
+            ScriptSubCommandArgs::Run { file } => strategy::run_file(ctx, &file).await?,
+        },
+        Some(Command::Completions { shell, words }) => print_completions(config, shell, &words)?,
+        Some(Command::Daemon(DaemonSubCommand { command })) => match command {
+            DaemonSubCommandArgs::Run { file, control } => {
+                let assignments = match file {
+                    Some(path) => daemon::Assignments::at(path),
+                    None => daemon::Assignments::open(&config.current_user_dir),
+                };
+                daemon::run(ctx, assignments, control).await?;
+            }
+            DaemonSubCommandArgs::Status { control } => {
+                for ship in control::ControlClient::new(control).ships().await? {
+                    println!("{}", ship);
+                }
+            }
+            DaemonSubCommandArgs::Assign {
+                ship_symbol,
+                behaviour,
+                control,
+            } => {
+                let behaviour = daemon::Behaviour::from(behaviour);
+                control::ControlClient::new(control)
+                    .assign(&ship_symbol, &behaviour)
+                    .await?;
+                eprintln!("{ship_symbol} will {behaviour} once its current step is done");
+            }
+            DaemonSubCommandArgs::Pause {
+                ship_symbol,
+                control,
+            } => {
+                control::ControlClient::new(control)
+                    .pause(ship_symbol.as_deref())
+                    .await?;
+                eprintln!("pausing once the current step is done");
+            }
+            DaemonSubCommandArgs::Resume {
+                ship_symbol,
+                control,
+            } => {
+                control::ControlClient::new(control)
+                    .resume(ship_symbol.as_deref())
+                    .await?;
+            }
+            DaemonSubCommandArgs::Events { follow, control } => {
+                control::print_events(&control::ControlClient::new(control), follow).await?;
+            }
+        },
+
+        Some(Command::RunScript { .. }) => {
+            return Err(AppError::Usage(String::from(
+                "run-script can't be used inside a script",
+            )))
+        }
+        Some(Command::Shell) => {
+            return Err(AppError::Usage(String::from(
+                "shell can't be used inside a script",
+            )))
+        }
+        None => return Err(AppError::Usage(String::from("invalid command"))),
     }
     Ok(())
 }
@@ -412,33 +1058,38 @@ pub async fn run<'a>(args: AppArgs, config: Config) -> MyResult<()> {
 pub mod auth {
     use std::{
         fs::File,
-        io::{BufReader, BufWriter, Write},
+        io::{self, BufReader, BufWriter, Write},
         path::Path,
     };
 
     use crate::{domain::RegisterResponse, UserInfo};
 
-    pub fn save_user_info(register_resp: &RegisterResponse, user_dir: &Path) {
+    pub fn save_user_info(register_resp: &RegisterResponse, user_dir: &Path) -> io::Result<()> {
         let user_info: UserInfo = UserInfo {
             agent: register_resp.agent.to_owned(),
             token: register_resp.token.to_owned(),
         };
         let file_path = user_dir.join("current_user.json");
-        let token_file = File::create(file_path).unwrap();
+        let token_file = File::create(file_path)?;
         let mut writer = BufWriter::new(token_file);
-        serde_json::to_writer(&mut writer, &user_info).unwrap();
-        writer.flush().unwrap();
+        serde_json::to_writer(&mut writer, &user_info)?;
+        writer.flush()
     }
 
-    pub fn check_user_token(current_user_dir: &Path) -> Option<UserInfo> {
+    /// `None` when nobody is logged in; a token file that can't be read or parsed is an error
+    pub fn check_user_token(current_user_dir: &Path) -> io::Result<Option<UserInfo>> {
         let token_file = current_user_dir.join("current_user.json");
         if token_file.exists() {
-            let token_file = File::open(token_file).unwrap();
-            let reader = BufReader::new(token_file);
-            let user_info: UserInfo = serde_json::from_reader(reader).unwrap();
-            Some(user_info)
+            let reader = BufReader::new(File::open(&token_file)?);
+            let user_info: UserInfo = serde_json::from_reader(reader).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} is corrupt: {err}", token_file.display()),
+                )
+            })?;
+            Ok(Some(user_info))
         } else {
-            None
+            Ok(None)
         }
     }
 }
@@ -19,7 +19,7 @@ async fn main() {
         },
     };
     if let Err(e) = space_traders_rust::run(args, config).await {
-        eprintln!("{}", e);
-        std::process::exit(1);
+        eprintln!("error: {}", e);
+        std::process::exit(e.exit_code());
     }
 }
@@ -0,0 +1,238 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::domain::{Market, MarketTransaction, Supply};
+
+const MARKET_HISTORY_FILE: &str = "market_history.jsonl";
+const MARKET_TRANSACTIONS_FILE: &str = "market_transactions.jsonl";
+
+/// One observed price of one good at one market
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceRecord {
+    pub timestamp: DateTime<Utc>,
+    pub waypoint_symbol: String,
+    pub trade_symbol: String,
+    pub supply: Supply,
+    pub purchase_price: u32,
+    pub sell_price: u32,
+    pub trade_volume: u32,
+}
+
+/// What sets one transaction apart from another listed in the same second
+type TransactionKey = (DateTime<Utc>, String, String, String, i32);
+
+fn transaction_key(transaction: &MarketTransaction) -> TransactionKey {
+    (
+        transaction.timestamp,
+        transaction.ship_symbol.clone(),
+        transaction.transaction_type.to_string(),
+        transaction.trade_symbol.clone(),
+        transaction.units,
+    )
+}
+
+/// The newest stored transaction time of a market and every transaction stored at that time
+#[derive(Debug, Default)]
+struct HighWater {
+    timestamp: Option<DateTime<Utc>>,
+    at_timestamp: HashSet<TransactionKey>,
+}
+
+impl HighWater {
+    fn is_new(&self, transaction: &MarketTransaction) -> bool {
+        match self.timestamp {
+            None => true,
+            Some(timestamp) if transaction.timestamp > timestamp => true,
+            Some(timestamp) if transaction.timestamp == timestamp => {
+                !self.at_timestamp.contains(&transaction_key(transaction))
+            }
+            Some(_) => false,
+        }
+    }
+
+    fn advance(&mut self, transaction: &MarketTransaction) {
+        if self
+            .timestamp
+            .is_none_or(|timestamp| transaction.timestamp > timestamp)
+        {
+            self.timestamp = Some(transaction.timestamp);
+            self.at_timestamp.clear();
+        }
+        if self.timestamp == Some(transaction.timestamp) {
+            self.at_timestamp.insert(transaction_key(transaction));
+        }
+    }
+}
+
+/// Append-only JSON lines files of every market snapshot we've seen, prices in one and the
+/// market's recent transactions in the other
+#[derive(Debug, Clone)]
+pub struct MarketHistory {
+    path: PathBuf,
+    transactions_path: PathBuf,
+    /// Per market high-water marks, read from the transactions file on first use
+    high_water: Arc<Mutex<Option<HashMap<String, HighWater>>>>,
+}
+
+impl MarketHistory {
+    pub fn open(dir: &Path) -> Self {
+        MarketHistory {
+            path: dir.join(MARKET_HISTORY_FILE),
+            transactions_path: dir.join(MARKET_TRANSACTIONS_FILE),
+            high_water: Arc::default(),
+        }
+    }
+
+    /// Records the market's trade goods and transactions, returning how many prices were
+    /// stored. Markets fetched without one of our ships present carry neither and record
+    /// nothing.
+    pub fn record(&self, market: &Market) -> std::io::Result<usize> {
+        self.record_transactions(market)?;
+        if market.trade_goods.is_empty() {
+            return Ok(0);
+        }
+        let timestamp = Utc::now();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        for good in market.trade_goods.iter() {
+            let record = PriceRecord {
+                timestamp,
+                waypoint_symbol: market.symbol.clone(),
+                trade_symbol: good.symbol.clone(),
+                supply: good.supply.clone(),
+                purchase_price: good.purchase_price,
+                sell_price: good.sell_price,
+                trade_volume: good.trade_volume,
+            };
+            serde_json::to_writer(&mut writer, &record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(market.trade_goods.len())
+    }
+
+    /// Markets list the same recent transactions on every fetch, so only ones past the
+    /// market's high-water mark are appended. Several ships often trade in the same second,
+    /// so transactions at the mark itself are told apart by ship, type, good and units.
+    fn record_transactions(&self, market: &Market) -> std::io::Result<()> {
+        let mut high_water = self.high_water.lock().unwrap();
+        if high_water.is_none() {
+            let mut marks: HashMap<String, HighWater> = HashMap::new();
+            for transaction in read_lines::<MarketTransaction>(&self.transactions_path)? {
+                marks
+                    .entry(transaction.waypoint_symbol.clone())
+                    .or_default()
+                    .advance(&transaction);
+            }
+            *high_water = Some(marks);
+        }
+        let mark = high_water
+            .as_mut()
+            .expect("high-water marks were just loaded")
+            .entry(market.symbol.clone())
+            .or_default();
+        let mut new: Vec<&MarketTransaction> = market
+            .transactions
+            .iter()
+            .filter(|t| mark.is_new(t))
+            .collect();
+        if new.is_empty() {
+            return Ok(());
+        }
+        new.sort_by_key(|t| t.timestamp);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.transactions_path)?;
+        let mut writer = BufWriter::new(file);
+        for transaction in new.iter() {
+            serde_json::to_writer(&mut writer, transaction)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        for transaction in new {
+            mark.advance(transaction);
+        }
+        Ok(())
+    }
+
+    pub fn all(&self) -> std::io::Result<Vec<PriceRecord>> {
+        read_lines(&self.path)
+    }
+
+    /// Transactions seen at one market, oldest first
+    pub fn transactions(&self, waypoint_symbol: &str) -> std::io::Result<Vec<MarketTransaction>> {
+        let mut transactions: Vec<MarketTransaction> = read_lines(&self.transactions_path)?
+            .into_iter()
+            .filter(|t: &MarketTransaction| t.waypoint_symbol == waypoint_symbol)
+            .collect();
+        transactions.sort_by_key(|t| t.timestamp);
+        Ok(transactions)
+    }
+
+    /// Price history of one good at one market, oldest first
+    pub fn history(
+        &self,
+        waypoint_symbol: &str,
+        trade_symbol: &str,
+    ) -> std::io::Result<Vec<PriceRecord>> {
+        let mut records: Vec<_> = self
+            .all()?
+            .into_iter()
+            .filter(|r| r.waypoint_symbol == waypoint_symbol && r.trade_symbol == trade_symbol)
+            .collect();
+        records.sort_by_key(|r| r.timestamp);
+        Ok(records)
+    }
+
+    /// The most recent price of every good at every market
+    pub fn latest(&self) -> std::io::Result<Vec<PriceRecord>> {
+        let mut latest: HashMap<(String, String), PriceRecord> = HashMap::new();
+        for record in self.all()? {
+            let key = (record.waypoint_symbol.clone(), record.trade_symbol.clone());
+            match latest.get(&key) {
+                Some(existing) if existing.timestamp > record.timestamp => {}
+                _ => {
+                    latest.insert(key, record);
+                }
+            }
+        }
+        Ok(latest.into_values().collect())
+    }
+
+    pub fn export_csv<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for record in self.all()? {
+            writer.serialize(record)?;
+        }
+        writer.flush()
+    }
+}
+
+fn read_lines<T: DeserializeOwned>(path: &Path) -> std::io::Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line)?);
+    }
+    Ok(records)
+}
@@ -0,0 +1,283 @@
+use std::{collections::BTreeMap, fmt};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    api::{Api, ApiError},
+    domain::{format_duration, DepositSize, ShipMountSymbol, ShipNavStatus, Survey, TradeSymbol},
+    error::{AppError, AppResult},
+    navigation,
+    notes::Progress,
+    selling, ship_state,
+    signal::StopSignal,
+};
+
+/// API error codes for extracting with a survey that has expired or been mined out
+const SURVEY_EXPIRED: i32 = 4221;
+const SURVEY_EXHAUSTED: i32 = 4224;
+
+#[derive(Debug, Clone)]
+pub struct MiningOptions {
+    pub ship_symbol: String,
+    pub asteroid_field: String,
+    pub market: String,
+    /// Goods to hold on to instead of selling
+    pub keep: Vec<TradeSymbol>,
+    /// Stop after this many cycles instead of running until Ctrl-C
+    pub cycles: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+pub struct CycleSummary {
+    pub cycle: u32,
+    pub extractions: u32,
+    pub yields: BTreeMap<String, i32>,
+    pub income: i64,
+    pub fuel_spend: i64,
+    pub seconds: i64,
+    /// False when a stop request cut the cycle short
+    pub completed: bool,
+}
+
+impl fmt::Display for CycleSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yields: Vec<String> = self
+            .yields
+            .iter()
+            .map(|(symbol, units)| format!("{units} {symbol}"))
+            .collect();
+        write!(
+            f,
+            "cycle {}{}: {} extractions ({}), income {}, fuel {}, took {}",
+            self.cycle,
+            if self.completed { "" } else { " (stopped)" },
+            self.extractions,
+            if yields.is_empty() {
+                String::from("nothing")
+            } else {
+                yields.join(", ")
+            },
+            self.income,
+            self.fuel_spend,
+            format_duration(chrono::Duration::seconds(self.seconds))
+        )
+    }
+}
+
+/// Mines at the asteroid field until the hold is full, sells everything not on the keep list
+/// at the market, jettisoning what it doesn't trade, and flies back, cycle after cycle until
+/// stopped
+pub async fn mine(
+    api: &Api<'_>,
+    options: &MiningOptions,
+    mut stop: StopSignal,
+    progress: Progress<'_>,
+) -> AppResult<Vec<CycleSummary>> {
+    let mut summaries = Vec::new();
+    let mut cycle = 1;
+    while !stop.is_stopped() && options.cycles.is_none_or(|cycles| cycle <= cycles) {
+        let started = Utc::now();
+        let mut summary = CycleSummary {
+            cycle,
+            ..Default::default()
+        };
+        summary.completed = run_cycle(api, options, &mut stop, &mut summary, progress).await?;
+        summary.seconds = (Utc::now() - started).num_seconds();
+        progress(summary.to_string());
+        // flying back and forth without mining would only burn fuel
+        if summary.completed && summary.extractions == 0 {
+            return Err(AppError::InvalidShipState(format!(
+                "{} extracted nothing in cycle {cycle}, stopping",
+                options.ship_symbol
+            )));
+        }
+        summaries.push(summary);
+        cycle += 1;
+    }
+
+    let income: i64 = summaries.iter().map(|s| s.income).sum();
+    let fuel_spend: i64 = summaries.iter().map(|s| s.fuel_spend).sum();
+    progress(format!(
+        "{} cycles, income {income}, fuel {fuel_spend}, net {}",
+        summaries.len(),
+        income - fuel_spend
+    ));
+    Ok(summaries)
+}
+
+/// Returns false if a stop request ended the cycle early
+async fn run_cycle(
+    api: &Api<'_>,
+    options: &MiningOptions,
+    stop: &mut StopSignal,
+    summary: &mut CycleSummary,
+    progress: Progress<'_>,
+) -> AppResult<bool> {
+    let ship_symbol = &options.ship_symbol;
+    // a hold left full by an earlier run is sold off first, so every cycle has room to mine
+    let cargo = api.get_ship_cargo(ship_symbol.clone()).await?.data;
+    if cargo.units >= cargo.capacity
+        && !sell_at_market(api, options, stop, summary, progress).await?
+    {
+        return Ok(false);
+    }
+    if !navigation::travel_to(api, ship_symbol, &options.asteroid_field, stop).await? {
+        return Ok(false);
+    }
+    ship_state::ensure_state(api, ship_symbol, ShipNavStatus::IN_ORBIT).await?;
+
+    let ship = api.get_ship_status(ship_symbol.clone()).await?.data;
+    let has_surveyor = ship.mounts.iter().any(|mount| {
+        matches!(
+            mount.symbol,
+            ShipMountSymbol::MOUNT_SURVEYOR_I
+                | ShipMountSymbol::MOUNT_SURVEYOR_II
+                | ShipMountSymbol::MOUNT_SURVEYOR_III
+        )
+    });
+    let mut cargo_full = ship.cargo.units >= ship.cargo.capacity;
+    let mut ready_at = api
+        .get_ship_cooldown(ship_symbol.clone())
+        .await?
+        .data
+        .and_then(|cooldown| cooldown.expiration);
+    let mut survey: Option<Survey> = None;
+
+    while !cargo_full {
+        if !wait_until(ready_at, stop, progress).await {
+            return Ok(false);
+        }
+        if has_surveyor && survey.as_ref().is_none_or(|s| s.is_expired()) {
+            let res = api.create_survey(ship_symbol.clone()).await?.data;
+            ready_at = res.cooldown.expiration;
+            survey = best_survey(res.surveys);
+            if !wait_until(ready_at, stop, progress).await {
+                return Ok(false);
+            }
+        }
+
+        let res = match api
+            .extract_resource(ship_symbol.clone(), survey.as_ref())
+            .await
+        {
+            Ok(res) => res.data,
+            // exhausted or expired surveys are rejected, so mine without one and survey again
+            Err(ApiError::ServiceError {
+                code: SURVEY_EXPIRED | SURVEY_EXHAUSTED,
+                message,
+                ..
+            }) if survey.is_some() => {
+                progress(format!("dropping survey: {message}"));
+                survey = None;
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let extracted = &res.extraction.extraction_yield;
+        progress(format!(
+            "extracted {} {} (cargo {}/{})",
+            extracted.units, extracted.symbol, res.cargo.units, res.cargo.capacity
+        ));
+        summary.extractions += 1;
+        *summary
+            .yields
+            .entry(extracted.symbol.to_string())
+            .or_default() += extracted.units;
+        ready_at = res.cooldown.expiration;
+        cargo_full = res.cargo.units >= res.cargo.capacity;
+    }
+
+    sell_at_market(api, options, stop, summary, progress).await
+}
+
+/// Returns false if a stop request came before reaching the market
+async fn sell_at_market(
+    api: &Api<'_>,
+    options: &MiningOptions,
+    stop: &mut StopSignal,
+    summary: &mut CycleSummary,
+    progress: Progress<'_>,
+) -> AppResult<bool> {
+    if !navigation::travel_to(api, &options.ship_symbol, &options.market, stop).await? {
+        return Ok(false);
+    }
+    sell_cargo(api, options, summary, progress).await?;
+    Ok(true)
+}
+
+/// Sells everything not on the keep list and jettisons what the market doesn't trade. Fails
+/// if the goods kept still fill the hold, as the next cycle couldn't extract anything.
+async fn sell_cargo(
+    api: &Api<'_>,
+    options: &MiningOptions,
+    summary: &mut CycleSummary,
+    progress: Progress<'_>,
+) -> AppResult<()> {
+    let ship_symbol = &options.ship_symbol;
+    ship_state::ensure_state(api, ship_symbol, ShipNavStatus::DOCKED).await?;
+    let market = api.get_market(options.market.clone()).await?.data;
+
+    let sale = selling::sell_hold(api, ship_symbol, &market, &options.keep).await?;
+    for transaction in sale.transactions.iter() {
+        progress(format!(
+            "sold {} {} for {}",
+            transaction.units, transaction.trade_symbol, transaction.total_price
+        ));
+    }
+    for item in sale.unsold.iter() {
+        selling::jettison(api, ship_symbol, item).await?;
+        progress(format!(
+            "jettisoned {} {}, not traded at {}",
+            item.units, item.symbol, market.symbol
+        ));
+    }
+    summary.income += sale.income();
+
+    let sells_fuel = market.trade_goods.iter().any(|g| g.symbol == "FUEL");
+    let ship = api.get_ship_status(ship_symbol.clone()).await?.data;
+    if sells_fuel && ship.fuel.current < ship.fuel.capacity {
+        let res = api.refuel_ship(ship_symbol.clone(), None).await?.data;
+        summary.fuel_spend += res.transaction.total_price as i64;
+    }
+    if ship.cargo.units >= ship.cargo.capacity {
+        return Err(AppError::InvalidShipState(format!(
+            "{ship_symbol}'s hold is still full ({}/{}) after selling, there's no room to mine",
+            ship.cargo.units, ship.cargo.capacity
+        )));
+    }
+    Ok(())
+}
+
+/// Sleeps until `ready_at`, returning false if stopped first
+pub async fn wait_until(
+    ready_at: Option<DateTime<Utc>>,
+    stop: &mut StopSignal,
+    progress: Progress<'_>,
+) -> bool {
+    if stop.is_stopped() {
+        return false;
+    }
+    match ready_at {
+        Some(ready_at) if ready_at > Utc::now() => {
+            let remaining = (ready_at - Utc::now()).to_std().unwrap_or_default();
+            progress(format!(
+                "waiting {} for cooldown",
+                format_duration(ready_at - Utc::now())
+            ));
+            stop.sleep(remaining).await
+        }
+        _ => true,
+    }
+}
+
+/// Prefers the largest deposit, then the one with the most variety
+fn best_survey(surveys: Vec<Survey>) -> Option<Survey> {
+    surveys.into_iter().max_by_key(|survey| {
+        let size = match survey.size {
+            DepositSize::SMALL | DepositSize::Unknown(_) => 0,
+            DepositSize::MODERATE => 1,
+            DepositSize::LARGE => 2,
+        };
+        (size, survey.deposits.len())
+    })
+}
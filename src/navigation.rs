@@ -0,0 +1,140 @@
+use std::time::Duration as StdDuration;
+
+use chrono::Utc;
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::{
+    api::Api,
+    domain::{format_duration, ShipNavRoute, ShipNavStatus},
+    error::{AppError, AppResult},
+    estimator, ship_state,
+    signal::StopSignal,
+};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ArrivalActions {
+    pub wait: bool,
+    pub dock: bool,
+    pub refuel: bool,
+    pub auto_state: bool,
+}
+
+impl ArrivalActions {
+    /// Docking and refueling only make sense once the ship is there, so both imply waiting
+    pub fn needs_wait(&self) -> bool {
+        self.wait || self.dock || self.refuel
+    }
+}
+
+pub async fn navigate(
+    api: &Api<'_>,
+    ship_symbol: String,
+    waypoint_symbol: String,
+    actions: ArrivalActions,
+) -> AppResult<()> {
+    let ship = api.get_ship_status(ship_symbol.clone()).await?.data;
+    let nav = &ship.nav;
+    if nav.status == ShipNavStatus::IN_TRANSIT && !nav.route.has_arrived() {
+        return Err(AppError::InvalidShipState(format!(
+            "Ship {ship_symbol} is already in transit to {}, {}",
+            nav.route.destination.symbol,
+            nav.route.arrival_description()
+        )));
+    }
+
+    if actions.auto_state && nav.status != ShipNavStatus::IN_ORBIT {
+        ship_state::ensure_state(api, &ship_symbol, ShipNavStatus::IN_ORBIT).await?;
+    }
+
+    // the warning is best-effort, an unknown waypoint is reported by the navigate call itself
+    if let Ok(destination) = api.get_waypoint(waypoint_symbol.clone()).await {
+        estimator::warn_if_out_of_fuel(&ship, &destination.data);
+    }
+
+    let res = api
+        .navigate_ship(ship_symbol.clone(), waypoint_symbol)
+        .await?;
+    println!("{:#?}", res);
+    println!("{}", res.data.nav.route.arrival_description());
+
+    if !actions.needs_wait() {
+        return Ok(());
+    }
+    wait_for_arrival(&res.data.nav.route).await;
+
+    // refueling needs the ship docked whether or not auto-state is on
+    if actions.dock || actions.refuel {
+        let res = api.dock_ship(ship_symbol.clone()).await?;
+        println!("{:#?}", res);
+    }
+    if actions.refuel {
+        let res = api.refuel_ship(ship_symbol, None).await?;
+        println!("{:#?}", res);
+    }
+    Ok(())
+}
+
+/// Sleeps until the route's arrival time, drawing a progress bar on stderr
+pub async fn wait_for_arrival(route: &ShipNavRoute) {
+    let total = (route.arrival - route.departure_time).num_seconds().max(1) as u64;
+    let progress = ProgressBar::new(total);
+    progress.set_style(
+        ProgressStyle::with_template("{prefix} [{bar:30}] {msg}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    progress.set_prefix(format!("-> {}", route.destination.symbol));
+
+    while !route.has_arrived() {
+        let elapsed = (Utc::now() - route.departure_time).num_seconds().max(0) as u64;
+        progress.set_position(elapsed.min(total));
+        progress.set_message(route.arrival_description());
+        tokio::time::sleep(StdDuration::from_secs(1)).await;
+    }
+
+    progress.finish_with_message(format!(
+        "arrived after {}",
+        format_duration(route.arrival - route.departure_time)
+    ));
+}
+
+/// Flies to the waypoint unless the ship is already there, returning false if stopped en route
+pub async fn travel_to(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    waypoint_symbol: &str,
+    stop: &mut StopSignal,
+) -> AppResult<bool> {
+    let nav = api.get_ship_nav_status(ship_symbol.to_owned()).await?.data;
+    if nav.status == ShipNavStatus::IN_TRANSIT && !nav.route.has_arrived() {
+        println!(
+            "{ship_symbol} is in transit, {}",
+            nav.route.arrival_description()
+        );
+        if !wait_for_arrival_or_stop(&nav.route, stop).await {
+            return Ok(false);
+        }
+    }
+    if nav.route.destination.symbol == waypoint_symbol {
+        return Ok(true);
+    }
+
+    ship_state::ensure_state(api, ship_symbol, ShipNavStatus::IN_ORBIT).await?;
+    let res = api
+        .navigate_ship(ship_symbol.to_owned(), waypoint_symbol.to_owned())
+        .await?
+        .data;
+    println!(
+        "{ship_symbol} -> {waypoint_symbol}, {}",
+        res.nav.route.arrival_description()
+    );
+    Ok(wait_for_arrival_or_stop(&res.nav.route, stop).await)
+}
+
+/// Like [`wait_for_arrival`], returning false if stopped first
+pub async fn wait_for_arrival_or_stop(route: &ShipNavRoute, stop: &mut StopSignal) -> bool {
+    tokio::select! {
+        _ = wait_for_arrival(route) => true,
+        _ = stop.stopped() => false,
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Mutex;
+
+use tokio::sync::mpsc;
+
+static CAPTURE: Mutex<Option<mpsc::UnboundedSender<String>>> = Mutex::new(None);
+
+/// Prints a side note, like a warning or an automatic dock, on stderr. While a full-screen
+/// view holds a `Capture` the note goes to it instead, so it doesn't scribble over the screen.
+pub fn note(message: String) {
+    let message = match CAPTURE.lock().unwrap().as_ref() {
+        Some(capture) => match capture.send(message) {
+            Ok(()) => return,
+            Err(unsent) => unsent.0,
+        },
+        None => message,
+    };
+    eprintln!("{message}");
+}
+
+/// Collects every note instead of printing it, until dropped
+pub fn capture() -> Capture {
+    let (sender, notes) = mpsc::unbounded_channel();
+    *CAPTURE.lock().unwrap() = Some(sender);
+    Capture { notes }
+}
+
+pub struct Capture {
+    notes: mpsc::UnboundedReceiver<String>,
+}
+
+impl Capture {
+    /// The notes taken since the last call
+    pub fn drain(&mut self) -> Vec<String> {
+        let mut notes = Vec::new();
+        while let Ok(note) = self.notes.try_recv() {
+            notes.push(note);
+        }
+        notes
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        CAPTURE.lock().unwrap().take();
+    }
+}
+
+/// Where a long-running job like mining reports each step it takes: stdout when run as a
+/// command, the event log when the daemon drives it
+pub type Progress<'a> = &'a (dyn Fn(String) + Sync);
+
+/// The [`Progress`] of a job run from the terminal
+pub fn to_stdout(message: String) {
+    println!("{message}");
+}
@@ -0,0 +1,61 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// SpaceTraders allows 2 requests per second per account, with some room for bursts
+pub const REQUESTS_PER_SECOND: f64 = 2.0;
+pub const BURST: u32 = 10;
+
+/// Token bucket shared by every request an `Api` makes, so long-running commands pace
+/// themselves instead of running into 429s
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter::new(REQUESTS_PER_SECOND, BURST)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        RateLimiter {
+            per_second,
+            burst: burst as f64,
+            bucket: Mutex::new(Bucket {
+                tokens: burst as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until a request may be sent. Tokens are reserved up front, so concurrent callers
+    /// queue up behind each other rather than all waking at once.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let now = Instant::now();
+            let refill = (now - bucket.updated).as_secs_f64() * self.per_second;
+            bucket.tokens = (bucket.tokens + refill).min(self.burst);
+            bucket.updated = now;
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                None
+            } else {
+                Some(Duration::from_secs_f64(-bucket.tokens / self.per_second))
+            }
+        };
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
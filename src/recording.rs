@@ -0,0 +1,226 @@
+//! Saves every API request and response to a directory, or answers requests from one
+//! instead of the network, so real sessions can be captured once and replayed in tests and
+//! bug reports.
+//!
+//! Each exchange is a numbered JSON file, `0001-GET-my_ships.json` and so on. Headers aren't
+//! kept and the token `register` returns is redacted, so recordings are safe to share. Replay
+//! hands out the recorded responses for each method and path in the order they were recorded.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use reqwest::{Client, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api::ApiError;
+
+const REDACTED_TOKEN: &str = "REDACTED";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query below the API base URL, e.g. `/my/ships?page=1`
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    /// The JSON body, a string if it wasn't JSON, or null if it was empty
+    pub body: Value,
+}
+
+pub enum Recording {
+    Record {
+        dir: PathBuf,
+        recorded: Mutex<usize>,
+    },
+    Replay {
+        dir: PathBuf,
+        responses: Mutex<HashMap<(String, String), VecDeque<RecordedResponse>>>,
+    },
+}
+
+impl Recording {
+    /// Records into `dir`, after any exchanges already there
+    pub fn record(dir: &Path) -> std::io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Recording::Record {
+            dir: dir.to_path_buf(),
+            recorded: Mutex::new(load(dir)?.len()),
+        })
+    }
+
+    pub fn replay(dir: &Path) -> std::io::Result<Self> {
+        let mut responses: HashMap<_, VecDeque<_>> = HashMap::new();
+        for exchange in load(dir)? {
+            let key = (exchange.request.method, exchange.request.path);
+            responses
+                .entry(key)
+                .or_default()
+                .push_back(exchange.response);
+        }
+        Ok(Recording::Replay {
+            dir: dir.to_path_buf(),
+            responses: Mutex::new(responses),
+        })
+    }
+
+    /// Sends the request through `client` and saves the exchange, or answers it from the
+    /// recording
+    pub async fn send(
+        &self,
+        client: &Client,
+        base_url: &str,
+        request: Request,
+    ) -> Result<Response, ApiError> {
+        let recorded_request = RecordedRequest {
+            method: request.method().to_string(),
+            path: request
+                .url()
+                .as_str()
+                .strip_prefix(base_url)
+                .unwrap_or(request.url().as_str())
+                .to_owned(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|bytes| serde_json::from_slice(bytes).ok()),
+        };
+        match self {
+            Recording::Record { dir, recorded } => {
+                let response = client.execute(request).await.map_err(network_error)?;
+                let status = response.status();
+                let bytes = response.bytes().await.map_err(network_error)?;
+                let mut body = body_value(&bytes);
+                if let Some(token) = body.pointer_mut("/data/token") {
+                    *token = Value::from(REDACTED_TOKEN);
+                }
+                let exchange = Exchange {
+                    request: recorded_request,
+                    response: RecordedResponse {
+                        status: status.as_u16(),
+                        body,
+                    },
+                };
+                save(dir, recorded, &exchange).map_err(|e| ApiError::NetworkError {
+                    message: format!("could not record response in {}: {e}", dir.display()),
+                })?;
+                Ok(to_response(status.as_u16(), bytes.to_vec()))
+            }
+            Recording::Replay { dir, responses } => {
+                let key = (recorded_request.method, recorded_request.path);
+                let response = responses
+                    .lock()
+                    .unwrap()
+                    .get_mut(&key)
+                    .and_then(VecDeque::pop_front)
+                    .ok_or_else(|| ApiError::NetworkError {
+                        message: format!(
+                            "no recorded response left for {} {} in {}",
+                            key.0,
+                            key.1,
+                            dir.display()
+                        ),
+                    })?;
+                let bytes = match response.body {
+                    Value::Null => Vec::new(),
+                    Value::String(text) => text.into_bytes(),
+                    body => body.to_string().into_bytes(),
+                };
+                Ok(to_response(response.status, bytes))
+            }
+        }
+    }
+}
+
+fn network_error(e: reqwest::Error) -> ApiError {
+    ApiError::NetworkError {
+        message: e.to_string(),
+    }
+}
+
+fn body_value(bytes: &[u8]) -> Value {
+    if bytes.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_slice(bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+fn to_response(status: u16, body: Vec<u8>) -> Response {
+    http::Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .expect("a status and body make a valid response")
+        .into()
+}
+
+/// Every exchange in `dir`, in recorded order
+pub fn load(dir: &Path) -> std::io::Result<Vec<Exchange>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            paths.push(path);
+        }
+    }
+    // by the sequence number, as the zero padding runs out past 9999 exchanges
+    paths.sort_by_cached_key(|path| (sequence(path), path.clone()));
+    paths
+        .iter()
+        .map(|path| {
+            let exchange = serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })?;
+            Ok(exchange)
+        })
+        .collect()
+}
+
+/// The number a recorded file starts with, or `u64::MAX` for files named some other way
+fn sequence(path: &Path) -> u64 {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('-').next())
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(u64::MAX)
+}
+
+fn save(dir: &Path, recorded: &Mutex<usize>, exchange: &Exchange) -> std::io::Result<()> {
+    let mut recorded = recorded.lock().unwrap();
+    *recorded += 1;
+    let endpoint = exchange
+        .request
+        .path
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .trim_matches('/')
+        .replace('/', "_");
+    let path = dir.join(format!(
+        "{:04}-{}-{endpoint}.json",
+        *recorded, exchange.request.method
+    ));
+    fs::write(path, serde_json::to_string_pretty(exchange)?)
+}
@@ -0,0 +1,238 @@
+//! The interactive `shell`: every subcommand at a prompt with history and line editing, all
+//! sharing one API client. `use ship ALI-1` and friends set a symbol that later commands get
+//! as their `--ship-symbol` (or waypoint or contract) when the line doesn't give one.
+
+use std::collections::HashMap;
+
+use clap::{error::ErrorKind, CommandFactory};
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use crate::{
+    error::{AppError, AppResult},
+    execute,
+    script::{self, Step},
+    signal::StopSignal,
+    AppArgs, Command, Context,
+};
+
+const HISTORY_FILE: &str = "shell_history.txt";
+
+/// Symbols set with `use`, filled into commands that need them
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Session {
+    pub ship: Option<String>,
+    pub waypoint: Option<String>,
+    pub contract: Option<String>,
+}
+
+impl Session {
+    /// The symbol for a command argument, by its name
+    fn for_arg(&self, id: &str) -> Option<&String> {
+        match id {
+            "ship_symbol" => self.ship.as_ref(),
+            "waypoint_symbol" => self.waypoint.as_ref(),
+            "contract_id" => self.contract.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn slot(&mut self, kind: &str) -> Option<&mut Option<String>> {
+        match kind {
+            "ship" => Some(&mut self.ship),
+            "waypoint" => Some(&mut self.waypoint),
+            "contract" => Some(&mut self.contract),
+            _ => None,
+        }
+    }
+
+    /// `use` on its own shows the session, `use ship ALI-1` sets the ship and `use ship`
+    /// clears it
+    pub fn apply_use(&mut self, args: &[String]) -> AppResult<()> {
+        let [kind, rest @ ..] = args else {
+            println!("{self}");
+            return Ok(());
+        };
+        let slot = self.slot(kind).ok_or_else(|| {
+            AppError::Usage(format!(
+                "can't use `{kind}`, expected ship, waypoint or contract"
+            ))
+        })?;
+        match rest {
+            [] => *slot = None,
+            [symbol] => *slot = Some(symbol.clone()),
+            _ => return Err(AppError::Usage(format!("usage: use {kind} [SYMBOL]"))),
+        }
+        Ok(())
+    }
+
+    /// Appends the session's symbols for the required symbol arguments `tokens` leaves out
+    pub fn fill_in(&self, tokens: &mut Vec<String>) {
+        let mut cmd = AppArgs::command();
+        cmd.build();
+        let mut cmd = &cmd;
+        for token in tokens.iter() {
+            match cmd.find_subcommand(token) {
+                Some(subcommand) => cmd = subcommand,
+                None => break,
+            }
+        }
+        let mut missing = Vec::new();
+        for arg in cmd.get_arguments().filter(|arg| arg.is_required_set()) {
+            let (Some(long), Some(symbol)) = (arg.get_long(), self.for_arg(arg.get_id().as_str()))
+            else {
+                continue;
+            };
+            let mut longs = vec![long];
+            longs.extend(arg.get_all_aliases().unwrap_or_default());
+            let given = tokens.iter().any(|token| match token.strip_prefix("--") {
+                Some(option) => longs.contains(&option.split('=').next().unwrap_or_default()),
+                None => token
+                    .strip_prefix('-')
+                    .is_some_and(|option| option.chars().next() == arg.get_short()),
+            });
+            if !given {
+                missing.push(format!("--{long}"));
+                missing.push(symbol.clone());
+            }
+        }
+        tokens.extend(missing);
+    }
+}
+
+impl std::fmt::Display for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let none = String::from("-");
+        writeln!(f, "ship:     {}", self.ship.as_ref().unwrap_or(&none))?;
+        writeln!(f, "waypoint: {}", self.waypoint.as_ref().unwrap_or(&none))?;
+        write!(f, "contract: {}", self.contract.as_ref().unwrap_or(&none))
+    }
+}
+
+/// Reads commands until `exit` or end of input. A failed command only prints its error, and
+/// Ctrl-C cancels the running command or clears the line being typed.
+pub async fn run(ctx: &Context<'_>) -> AppResult<()> {
+    let mut editor = DefaultEditor::new().map_err(readline_error)?;
+    let history = ctx.config.current_user_dir.join(HISTORY_FILE);
+    // Missing on the first run
+    let _ = editor.load_history(&history);
+    let mut session = Session::default();
+    let mut vars = HashMap::new();
+
+    loop {
+        let prompt = prompt(&ctx.user_info.agent.symbol, &session);
+        let line = match tokio::task::block_in_place(|| editor.readline(&prompt)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(readline_error(e)),
+        };
+        if !line.trim().is_empty() {
+            editor
+                .add_history_entry(line.as_str())
+                .map_err(readline_error)?;
+        }
+        let step = match script::parse_line(&line, &vars) {
+            Ok(Some(step)) => step,
+            Ok(None) => continue,
+            Err(message) => {
+                eprintln!("error: {message}");
+                continue;
+            }
+        };
+        if let Step::Command(tokens) = &step {
+            match tokens.first().map(String::as_str) {
+                Some("exit" | "quit") => break,
+                Some("use") => {
+                    if let Err(e) = session.apply_use(&tokens[1..]) {
+                        eprintln!("error: {e}");
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        let result = tokio::select! {
+            result = run_step(ctx, step, &session, &mut vars) => result,
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("cancelled");
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("error: {e}");
+        }
+    }
+
+    if let Err(e) = editor.save_history(&history) {
+        eprintln!("warning: could not save shell history: {e}");
+    }
+    Ok(())
+}
+
+async fn run_step(
+    ctx: &Context<'_>,
+    step: Step,
+    session: &Session,
+    vars: &mut HashMap<String, String>,
+) -> AppResult<()> {
+    let mut tokens = match step {
+        Step::Command(tokens) => tokens,
+        step => {
+            // on-error has nothing to stop in the shell, the setting is simply unused
+            let mut continue_on_error = true;
+            let mut stop = StopSignal::on_ctrl_c();
+            return script::run_step(ctx, step, vars, &mut continue_on_error, &mut stop).await;
+        }
+    };
+    session.fill_in(&mut tokens);
+    let args = match script::parse_args(tokens) {
+        Ok(args) => args,
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::DisplayHelp
+                    | ErrorKind::DisplayVersion
+                    | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+            ) =>
+        {
+            e.print()?;
+            return Ok(());
+        }
+        Err(e) => return Err(script::usage_error(e)),
+    };
+    match args.command {
+        Some(Command::RunScript {
+            file,
+            continue_on_error,
+            vars,
+        }) => {
+            let options = script::ScriptOptions {
+                continue_on_error,
+                vars: vars.into_iter().collect(),
+            };
+            script::run_file(ctx, &file, options).await
+        }
+        Some(Command::Shell) => Err(AppError::Usage(String::from("already in the shell"))),
+        _ => execute(ctx, args).await,
+    }
+}
+
+fn prompt(agent_symbol: &str, session: &Session) -> String {
+    let symbols: Vec<&str> = [&session.ship, &session.waypoint, &session.contract]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    if symbols.is_empty() {
+        format!("{agent_symbol}> ")
+    } else {
+        format!("{agent_symbol} [{}]> ", symbols.join(" "))
+    }
+}
+
+fn readline_error(e: ReadlineError) -> AppError {
+    match e {
+        ReadlineError::Io(e) => AppError::Io(e),
+        e => AppError::Io(std::io::Error::other(e)),
+    }
+}
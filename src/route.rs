@@ -0,0 +1,268 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+};
+
+use crate::{
+    api::Api,
+    domain::{format_duration, ShipNavFlightMode, TradeSymbol, Waypoint, WaypointTraitSymbol},
+    error::{AppError, AppResult},
+    estimator::{self, fuel_cost, travel_seconds},
+};
+
+/// Flight modes the planner considers. STEALTH burns as much fuel as CRUISE but is slower,
+/// so it never ends up on a shortest route.
+const PLANNED_FLIGHT_MODES: [ShipNavFlightMode; 3] = [
+    ShipNavFlightMode::BURN,
+    ShipNavFlightMode::CRUISE,
+    ShipNavFlightMode::DRIFT,
+];
+
+#[derive(Debug, Clone)]
+pub struct RoutePoint {
+    pub symbol: String,
+    pub x: i32,
+    pub y: i32,
+    pub sells_fuel: bool,
+}
+
+impl RoutePoint {
+    pub fn distance_to(&self, other: &RoutePoint) -> f64 {
+        estimator::distance((self.x, self.y), (other.x, other.y))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShipRouteSpec {
+    pub speed: i32,
+    pub fuel_current: i32,
+    pub fuel_capacity: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct RouteLeg {
+    pub from: String,
+    pub to: String,
+    pub flight_mode: ShipNavFlightMode,
+    pub distance: f64,
+    pub fuel_cost: i32,
+    pub travel_seconds: i64,
+}
+
+#[derive(Debug, Clone)]
+pub enum RouteStep {
+    Travel(RouteLeg),
+    Refuel { waypoint_symbol: String, units: i32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct RoutePlan {
+    pub steps: Vec<RouteStep>,
+}
+
+impl RoutePlan {
+    pub fn total_fuel(&self) -> i32 {
+        self.legs().map(|leg| leg.fuel_cost).sum()
+    }
+
+    pub fn total_seconds(&self) -> i64 {
+        self.legs().map(|leg| leg.travel_seconds).sum()
+    }
+
+    pub fn legs(&self) -> impl Iterator<Item = &RouteLeg> {
+        self.steps.iter().filter_map(|step| match step {
+            RouteStep::Travel(leg) => Some(leg),
+            RouteStep::Refuel { .. } => None,
+        })
+    }
+}
+
+impl fmt::Display for RouteStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteStep::Travel(leg) => write!(
+                f,
+                "{} -> {} [{}] distance {:.1}, fuel {}, time {}",
+                leg.from,
+                leg.to,
+                leg.flight_mode,
+                leg.distance,
+                leg.fuel_cost,
+                format_duration(chrono::Duration::seconds(leg.travel_seconds))
+            ),
+            RouteStep::Refuel {
+                waypoint_symbol,
+                units,
+            } => write!(f, "refuel {units} units at {waypoint_symbol}"),
+        }
+    }
+}
+
+impl fmt::Display for RoutePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{:>2}. {step}", i + 1)?;
+        }
+        write!(
+            f,
+            "total: fuel {}, time {}",
+            self.total_fuel(),
+            format_duration(chrono::Duration::seconds(self.total_seconds()))
+        )
+    }
+}
+
+/// Finds the fastest route from `from` to `to`, inserting refuel stops at waypoints that sell
+/// fuel and picking flight modes so the tank never runs dry. Ships without a fuel tank (e.g.
+/// probes) travel for free.
+pub fn plan_route(
+    points: &[RoutePoint],
+    from: &str,
+    to: &str,
+    ship: ShipRouteSpec,
+) -> Option<RoutePlan> {
+    let start = points.iter().position(|p| p.symbol == from)?;
+    let goal = points.iter().position(|p| p.symbol == to)?;
+    let uses_fuel = ship.fuel_capacity > 0;
+    let start_fuel = if uses_fuel { ship.fuel_current } else { 0 };
+
+    // state: (waypoint index, fuel in tank), ordered by (travel time, number of steps)
+    type State = (usize, i32);
+    let mut best: HashMap<State, (i64, usize)> = HashMap::new();
+    let mut previous: HashMap<State, (State, RouteStep)> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    best.insert((start, start_fuel), (0, 0));
+    queue.push(Reverse((0i64, 0usize, start, start_fuel)));
+
+    while let Some(Reverse((seconds, steps, here, fuel))) = queue.pop() {
+        if here == goal {
+            return Some(RoutePlan {
+                steps: unwind(&previous, (here, fuel)),
+            });
+        }
+        if best
+            .get(&(here, fuel))
+            .is_some_and(|&b| b < (seconds, steps))
+        {
+            continue;
+        }
+
+        let mut relax = |state: State, candidate: (i64, usize), step: &dyn Fn() -> RouteStep| {
+            if best.get(&state).is_none_or(|&b| candidate < b) {
+                best.insert(state, candidate);
+                previous.insert(state, ((here, fuel), step()));
+                queue.push(Reverse((candidate.0, candidate.1, state.0, state.1)));
+            }
+        };
+
+        if uses_fuel && points[here].sells_fuel && fuel < ship.fuel_capacity {
+            relax((here, ship.fuel_capacity), (seconds, steps + 1), &|| {
+                RouteStep::Refuel {
+                    waypoint_symbol: points[here].symbol.clone(),
+                    units: ship.fuel_capacity - fuel,
+                }
+            });
+        }
+        for (there, point) in points.iter().enumerate() {
+            if there == here {
+                continue;
+            }
+            let distance = points[here].distance_to(point);
+            for flight_mode in PLANNED_FLIGHT_MODES {
+                let cost = if uses_fuel {
+                    fuel_cost(flight_mode.clone(), distance)
+                } else {
+                    0
+                };
+                if cost > fuel {
+                    continue;
+                }
+                let leg_seconds = travel_seconds(flight_mode.clone(), distance, ship.speed);
+                relax(
+                    (there, fuel - cost),
+                    (seconds + leg_seconds, steps + 1),
+                    &|| {
+                        RouteStep::Travel(RouteLeg {
+                            from: points[here].symbol.clone(),
+                            to: point.symbol.clone(),
+                            flight_mode: flight_mode.clone(),
+                            distance,
+                            fuel_cost: cost,
+                            travel_seconds: leg_seconds,
+                        })
+                    },
+                );
+            }
+        }
+    }
+    None
+}
+
+fn unwind<S: std::hash::Hash + Eq + Copy>(
+    previous: &HashMap<S, (S, RouteStep)>,
+    mut state: S,
+) -> Vec<RouteStep> {
+    let mut steps = Vec::new();
+    while let Some((before, step)) = previous.get(&state) {
+        steps.push(step.clone());
+        state = *before;
+    }
+    steps.reverse();
+    steps
+}
+
+pub async fn plan(api: &Api<'_>, ship_symbol: String, destination: String) -> AppResult<RoutePlan> {
+    let ship = api.get_ship_status(ship_symbol.clone()).await?.data;
+    let waypoints = api
+        .list_waypoints(ship.nav.system_symbol.clone(), None, None)
+        .await?
+        .data;
+    let points = route_points(api, &waypoints).await?;
+
+    let spec = ShipRouteSpec {
+        speed: ship.engine.speed,
+        fuel_current: ship.fuel.current,
+        fuel_capacity: ship.fuel.capacity,
+    };
+    if !points.iter().any(|p| p.symbol == destination) {
+        return Err(AppError::Usage(format!(
+            "Waypoint {destination} is not in system {}",
+            ship.nav.system_symbol
+        )));
+    }
+    plan_route(&points, &ship.nav.waypoint_symbol, &destination, spec).ok_or_else(|| {
+        AppError::InvalidShipState(format!(
+            "No route from {} to {destination} that {ship_symbol} can make on its fuel",
+            ship.nav.waypoint_symbol
+        ))
+    })
+}
+
+/// Looks up every marketplace in the system to see which ones sell fuel
+async fn route_points(api: &Api<'_>, waypoints: &[Waypoint]) -> AppResult<Vec<RoutePoint>> {
+    let mut points = Vec::new();
+    for waypoint in waypoints {
+        let is_market = waypoint
+            .traits
+            .iter()
+            .any(|t| t.symbol == WaypointTraitSymbol::MARKETPLACE);
+        let sells_fuel = if is_market {
+            let market = api.get_market(waypoint.symbol.clone()).await?.data;
+            market
+                .exports
+                .iter()
+                .chain(market.exchange.iter())
+                .any(|good| good.symbol == TradeSymbol::FUEL)
+        } else {
+            false
+        };
+        points.push(RoutePoint {
+            symbol: waypoint.symbol.clone(),
+            x: waypoint.x,
+            y: waypoint.y,
+            sells_fuel,
+        });
+    }
+    Ok(points)
+}
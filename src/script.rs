@@ -0,0 +1,238 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use clap::Parser;
+
+use crate::{
+    api::Api,
+    domain::ShipNavStatus,
+    error::{AppError, AppResult},
+    execute, mining, navigation, notes,
+    signal::StopSignal,
+    AppArgs, Context,
+};
+
+#[derive(Debug, Default, Clone)]
+pub struct ScriptOptions {
+    pub continue_on_error: bool,
+    /// Variables set before the first line, overridable with `set`
+    pub vars: HashMap<String, String>,
+}
+
+/// One line of a script, with variables already expanded
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// `set NAME=value`
+    Set(String, String),
+    /// `on-error stop` or `on-error continue`
+    OnError { continue_on_error: bool },
+    /// `wait-until-arrival SHIP`
+    WaitUntilArrival(String),
+    /// `wait-cooldown SHIP`
+    WaitCooldown(String),
+    /// Anything else is a regular command line, without the program name
+    Command(Vec<String>),
+}
+
+/// Parses `NAME=value` for `--var`
+pub fn parse_var(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=value, got `{value}`"))?;
+    if !is_var_name(name) {
+        return Err(format!("invalid variable name `{name}`"));
+    }
+    Ok((name.to_owned(), value.to_owned()))
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses one script line. Blank lines and `#` comments give `None`.
+pub fn parse_line(line: &str, vars: &HashMap<String, String>) -> Result<Option<Step>, String> {
+    let tokens = tokenize(line, vars)?;
+    let Some((first, rest)) = tokens.split_first() else {
+        return Ok(None);
+    };
+    let single_arg = |usage: &str| match rest {
+        [arg] => Ok(arg.clone()),
+        _ => Err(format!("usage: {usage}")),
+    };
+    let step = match first.as_str() {
+        "set" => {
+            let (name, value) = parse_var(&single_arg("set NAME=value")?)?;
+            Step::Set(name, value)
+        }
+        "on-error" => match single_arg("on-error stop|continue")?.as_str() {
+            "stop" => Step::OnError {
+                continue_on_error: false,
+            },
+            "continue" => Step::OnError {
+                continue_on_error: true,
+            },
+            _ => return Err(String::from("usage: on-error stop|continue")),
+        },
+        "wait-until-arrival" => Step::WaitUntilArrival(single_arg("wait-until-arrival SHIP")?),
+        "wait-cooldown" => Step::WaitCooldown(single_arg("wait-cooldown SHIP")?),
+        _ => Step::Command(tokens),
+    };
+    Ok(Some(step))
+}
+
+/// Splits a line into words like a shell would, honouring quotes, `#` comments and `$NAME` or
+/// `${NAME}` variables. Single quotes keep `$` literal.
+fn tokenize(line: &str, vars: &HashMap<String, String>) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, '#') if current.is_none() => break,
+            (None, c) if c.is_whitespace() => {
+                if let Some(token) = current.take() {
+                    tokens.push(token);
+                }
+            }
+            (Some('\''), c) => current.get_or_insert_with(String::new).push(c),
+            (_, '$') => {
+                let braced = chars.next_if_eq(&'{').is_some();
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                if braced && chars.next() != Some('}') {
+                    return Err(String::from("unterminated ${...}"));
+                }
+                if name.is_empty() {
+                    return Err(String::from("expected a variable name after $"));
+                }
+                let value = vars
+                    .get(&name)
+                    .ok_or_else(|| format!("undefined variable `{name}`"))?;
+                current.get_or_insert_with(String::new).push_str(value);
+            }
+            (_, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(String::from("unterminated quote"));
+    }
+    if let Some(token) = current {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+pub async fn run_file(ctx: &Context<'_>, path: &Path, options: ScriptOptions) -> AppResult<()> {
+    let script = fs::read_to_string(path)?;
+    run_script(ctx, &script, options).await
+}
+
+/// Runs the script line by line. With `continue_on_error` the remaining lines still run after a
+/// failure, but the first error is returned at the end so the exit code shows it.
+pub async fn run_script(ctx: &Context<'_>, script: &str, options: ScriptOptions) -> AppResult<()> {
+    let ScriptOptions {
+        mut continue_on_error,
+        mut vars,
+    } = options;
+    let mut stop = StopSignal::on_ctrl_c();
+    let mut first_error = None;
+    let mut failures = 0;
+
+    for (index, line) in script.lines().enumerate() {
+        let line_number = index + 1;
+        if stop.is_stopped() {
+            eprintln!("script stopped before line {line_number}");
+            break;
+        }
+        let result = match parse_line(line, &vars) {
+            Ok(None) => continue,
+            Ok(Some(step)) => {
+                eprintln!("[{line_number}] {}", line.trim());
+                run_step(ctx, step, &mut vars, &mut continue_on_error, &mut stop).await
+            }
+            Err(message) => Err(AppError::Usage(message)),
+        };
+        if let Err(e) = result {
+            eprintln!("line {line_number}: error: {e}");
+            if !continue_on_error {
+                return Err(e);
+            }
+            failures += 1;
+            first_error.get_or_insert(e);
+        }
+    }
+
+    match first_error {
+        Some(e) => {
+            eprintln!("script finished with {failures} failed lines");
+            Err(e)
+        }
+        None => Ok(()),
+    }
+}
+
+pub(crate) async fn run_step(
+    ctx: &Context<'_>,
+    step: Step,
+    vars: &mut HashMap<String, String>,
+    continue_on_error: &mut bool,
+    stop: &mut StopSignal,
+) -> AppResult<()> {
+    match step {
+        Step::Set(name, value) => {
+            vars.insert(name, value);
+        }
+        Step::OnError {
+            continue_on_error: value,
+        } => *continue_on_error = value,
+        Step::WaitUntilArrival(ship_symbol) => {
+            wait_until_arrival(&ctx.api, ship_symbol, stop).await?
+        }
+        Step::WaitCooldown(ship_symbol) => {
+            let ready_at = ctx
+                .api
+                .get_ship_cooldown(ship_symbol)
+                .await?
+                .data
+                .and_then(|cooldown| cooldown.expiration);
+            mining::wait_until(ready_at, stop, &notes::to_stdout).await;
+        }
+        Step::Command(tokens) => execute(ctx, parse_args(tokens).map_err(usage_error)?).await?,
+    }
+    Ok(())
+}
+
+/// Parses a command line without the program name
+pub(crate) fn parse_args(tokens: Vec<String>) -> Result<AppArgs, clap::Error> {
+    AppArgs::try_parse_from(std::iter::once(String::from("space_traders_rust")).chain(tokens))
+}
+
+pub(crate) fn usage_error(e: clap::Error) -> AppError {
+    // clap prefixes its own "error: ", which the caller adds as well
+    let message = e.to_string();
+    AppError::Usage(message.trim_start_matches("error: ").trim_end().to_owned())
+}
+
+async fn wait_until_arrival(
+    api: &Api<'_>,
+    ship_symbol: String,
+    stop: &mut StopSignal,
+) -> AppResult<()> {
+    let nav = api.get_ship_nav_status(ship_symbol).await?.data;
+    if nav.status == ShipNavStatus::IN_TRANSIT && !nav.route.has_arrived() {
+        navigation::wait_for_arrival_or_stop(&nav.route, stop).await;
+    }
+    Ok(())
+}
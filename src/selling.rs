@@ -0,0 +1,66 @@
+use crate::{
+    api::Api,
+    domain::{Inventory, Market, MarketTransaction, TradeSymbol},
+    error::AppResult,
+};
+
+#[derive(Debug, Default)]
+pub struct Sale {
+    pub transactions: Vec<MarketTransaction>,
+    /// Cargo the market doesn't buy, left in the hold
+    pub unsold: Vec<Inventory>,
+}
+
+impl Sale {
+    pub fn income(&self) -> i64 {
+        self.transactions
+            .iter()
+            .map(|transaction| i64::from(transaction.total_price))
+            .sum()
+    }
+}
+
+/// Sells the hold at the market the ship is docked at, except goods on the keep list. A market
+/// buys whatever it lists a price for, but only up to its trade volume per transaction.
+pub async fn sell_hold(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    market: &Market,
+    keep: &[TradeSymbol],
+) -> AppResult<Sale> {
+    let cargo = api.get_ship_cargo(ship_symbol.to_owned()).await?.data;
+    let mut sale = Sale::default();
+    for item in cargo.inventory {
+        let trade_symbol = TradeSymbol::from_symbol(&item.symbol);
+        if keep.contains(&trade_symbol) {
+            continue;
+        }
+        let Some(good) = market.trade_goods.iter().find(|g| g.symbol == item.symbol) else {
+            sale.unsold.push(item);
+            continue;
+        };
+
+        let mut remaining = item.units as u32;
+        while remaining > 0 {
+            let units = remaining.min(good.trade_volume.max(1));
+            let res = api
+                .sell_ship_cargo(ship_symbol.to_owned(), trade_symbol.clone(), units)
+                .await?
+                .data;
+            sale.transactions.push(res.transaction);
+            remaining -= units;
+        }
+    }
+    Ok(sale)
+}
+
+/// Dumps the whole stack of a good
+pub async fn jettison(api: &Api<'_>, ship_symbol: &str, item: &Inventory) -> AppResult<()> {
+    api.jettison_cargo(
+        ship_symbol.to_owned(),
+        TradeSymbol::from_symbol(&item.symbol),
+        item.units as u32,
+    )
+    .await?;
+    Ok(())
+}
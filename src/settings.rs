@@ -0,0 +1,40 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// Dock or orbit ships automatically before actions that need it
+    pub auto_state: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { auto_state: true }
+    }
+}
+
+impl Settings {
+    pub fn load(dir: &Path) -> std::io::Result<Settings> {
+        let file_path = dir.join(SETTINGS_FILE);
+        if !file_path.exists() {
+            return Ok(Settings::default());
+        }
+        let contents = fs::read_to_string(file_path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, dir: &Path) -> std::io::Result<()> {
+        let file = File::create(dir.join(SETTINGS_FILE))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{
+    api::Api,
+    domain::ShipNavStatus,
+    error::{AppError, AppResult},
+    notes,
+};
+
+/// Docks or orbits the ship if it isn't already in the `required` state, noting any
+/// transition. Ships still in transit can't be moved into either state.
+pub async fn ensure_state(
+    api: &Api<'_>,
+    ship_symbol: &str,
+    required: ShipNavStatus,
+) -> AppResult<()> {
+    let nav = api.get_ship_nav_status(ship_symbol.to_owned()).await?.data;
+    if nav.status == required {
+        return Ok(());
+    }
+    if nav.status == ShipNavStatus::IN_TRANSIT && !nav.route.has_arrived() {
+        return Err(AppError::InvalidShipState(format!(
+            "Ship {ship_symbol} is in transit to {}, {}",
+            nav.route.destination.symbol,
+            nav.route.arrival_description()
+        )));
+    }
+
+    match required {
+        ShipNavStatus::DOCKED => {
+            api.dock_ship(ship_symbol.to_owned()).await?;
+            notes::note(format!(
+                "auto-state: docked {ship_symbol} at {}",
+                nav.waypoint_symbol
+            ));
+        }
+        ShipNavStatus::IN_ORBIT => {
+            api.orbit_ship(ship_symbol.to_owned()).await?;
+            notes::note(format!(
+                "auto-state: moved {ship_symbol} into orbit at {}",
+                nav.waypoint_symbol
+            ));
+        }
+        ShipNavStatus::IN_TRANSIT | ShipNavStatus::Unknown(_) => {}
+    }
+    Ok(())
+}
+
+/// Purchasing a ship needs one of our ships docked at the shipyard, so dock the first one
+/// found there unless one is docked already
+pub async fn ensure_docked_at(api: &Api<'_>, waypoint_symbol: &str) -> AppResult<()> {
+    let ships = api.list_ships().await?.data;
+    let ships_here: Vec<_> = ships
+        .iter()
+        .filter(|ship| {
+            ship.nav.waypoint_symbol == waypoint_symbol
+                && (ship.nav.status != ShipNavStatus::IN_TRANSIT || ship.nav.route.has_arrived())
+        })
+        .collect();
+
+    if ships_here
+        .iter()
+        .any(|ship| ship.nav.status == ShipNavStatus::DOCKED)
+    {
+        return Ok(());
+    }
+    match ships_here.first() {
+        Some(ship) => ensure_state(api, &ship.symbol, ShipNavStatus::DOCKED).await,
+        None => Err(AppError::InvalidShipState(format!(
+            "None of your ships are at {waypoint_symbol}"
+        ))),
+    }
+}
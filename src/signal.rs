@@ -0,0 +1,87 @@
+use std::{future::Future, time::Duration as StdDuration};
+
+use tokio::sync::watch;
+
+/// Lets long-running loops stop at the next safe point instead of dying mid-request
+#[derive(Clone)]
+pub struct StopSignal(watch::Receiver<bool>);
+
+pub struct StopHandle(watch::Sender<bool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        let _ = self.0.send(true);
+    }
+}
+
+impl StopSignal {
+    pub fn new() -> (StopHandle, StopSignal) {
+        let (sender, receiver) = watch::channel(false);
+        (StopHandle(sender), StopSignal(receiver))
+    }
+
+    /// A signal that fires on the first Ctrl-C
+    pub fn on_ctrl_c() -> StopSignal {
+        let (handle, signal) = StopSignal::new();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                eprintln!("stopping after the current step...");
+                handle.stop();
+            }
+        });
+        signal
+    }
+
+    /// A signal that fires on the first Ctrl-C or SIGTERM, for processes meant to run unattended
+    pub fn on_shutdown() -> StopSignal {
+        let (handle, signal) = StopSignal::new();
+        let shutdown = shutdown_requested();
+        tokio::spawn(async move {
+            if shutdown.await.is_ok() {
+                eprintln!("shutting down after the current step...");
+                handle.stop();
+            }
+        });
+        signal
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once a stop has been requested, never if the handle is gone without one
+    pub async fn stopped(&mut self) {
+        if self.0.wait_for(|stopped| *stopped).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Sleeps for `duration`, returning `false` if a stop cut the sleep short
+    pub async fn sleep(&mut self, duration: StdDuration) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => true,
+            _ = self.stopped() => false,
+        }
+    }
+}
+
+/// SIGTERM is hooked straight away rather than when first polled, so one sent right after
+/// startup still stops cleanly instead of killing the process
+#[cfg(unix)]
+fn shutdown_requested() -> impl Future<Output = std::io::Result<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let terminate = signal(SignalKind::terminate());
+    async move {
+        let mut terminate = terminate?;
+        tokio::select! {
+            res = tokio::signal::ctrl_c() => res,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn shutdown_requested() -> impl Future<Output = std::io::Result<()>> {
+    tokio::signal::ctrl_c()
+}
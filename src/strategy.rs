@@ -0,0 +1,232 @@
+//! Rhai strategy scripts. The engine runs on its own thread and every API function it calls is
+//! sent back to the async side, so scripts share the command's client and rate limiter and
+//! can't reach anything we don't register.
+
+use std::{fs, path::Path, sync::mpsc, time::Duration as StdDuration};
+
+use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString};
+use serde::Serialize;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::{
+    domain::{ShipNavStatus, TradeSymbol},
+    error::{AppError, AppResult},
+    mining, navigation, notes,
+    signal::StopSignal,
+    Context,
+};
+
+/// A script function call waiting to be served on the async side
+struct Call {
+    name: &'static str,
+    args: Vec<Dynamic>,
+    reply: mpsc::Sender<Result<Dynamic, String>>,
+}
+
+/// Blocks the script thread until the call has been served
+fn call(
+    calls: &UnboundedSender<Call>,
+    name: &'static str,
+    args: Vec<Dynamic>,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    let (reply, response) = mpsc::channel();
+    calls
+        .send(Call { name, args, reply })
+        .map_err(|_| "the script was stopped")?;
+    response
+        .recv()
+        .map_err(|_| "the script was stopped")?
+        .map_err(|e| format!("{name}: {e}").into())
+}
+
+macro_rules! register_call {
+    ($engine:expr, $calls:expr, $name:literal $(, $arg:ident: $ty:ty)*) => {{
+        let calls = $calls.clone();
+        $engine.register_fn($name, move |$($arg: $ty),*| {
+            call(&calls, $name, vec![$(Dynamic::from($arg)),*])
+        });
+    }};
+}
+
+/// Enough for a strategy that spends its time waiting on the API to run for months, while a
+/// busy loop in pure Rhai gives up within a minute or so
+const MAX_OPERATIONS: u64 = 1_000_000_000;
+const MAX_CALL_LEVELS: usize = 32;
+
+fn build_engine(calls: UnboundedSender<Call>, stop: StopSignal) -> Engine {
+    type Str = ImmutableString;
+    let mut engine = Engine::new();
+    engine.disable_symbol("eval");
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    // scripts that never call back into the API would otherwise miss the stop
+    engine.on_progress(move |_| stop.is_stopped().then_some(Dynamic::UNIT));
+
+    register_call!(engine, calls, "agent");
+    register_call!(engine, calls, "ships");
+    register_call!(engine, calls, "ship", ship: Str);
+    register_call!(engine, calls, "nav", ship: Str);
+    register_call!(engine, calls, "cargo", ship: Str);
+    register_call!(engine, calls, "cooldown", ship: Str);
+    register_call!(engine, calls, "dock", ship: Str);
+    register_call!(engine, calls, "orbit", ship: Str);
+    register_call!(engine, calls, "navigate", ship: Str, waypoint: Str);
+    register_call!(engine, calls, "refuel", ship: Str);
+    register_call!(engine, calls, "extract", ship: Str);
+    register_call!(engine, calls, "survey", ship: Str);
+    register_call!(engine, calls, "sell", ship: Str, good: Str, units: i64);
+    register_call!(engine, calls, "buy", ship: Str, good: Str, units: i64);
+    register_call!(engine, calls, "market", waypoint: Str);
+    register_call!(engine, calls, "waypoint", waypoint: Str);
+    register_call!(engine, calls, "waypoints", system: Str);
+    register_call!(engine, calls, "contracts");
+    register_call!(engine, calls, "deliver", contract: Str, ship: Str, good: Str, units: i64);
+    register_call!(engine, calls, "fulfill", contract: Str);
+    register_call!(engine, calls, "sleep", seconds: i64);
+    register_call!(engine, calls, "sleep", seconds: f64);
+    register_call!(engine, calls, "wait_until_arrival", ship: Str);
+    register_call!(engine, calls, "wait_cooldown", ship: Str);
+    engine
+}
+
+pub async fn run_file(ctx: &Context<'_>, path: &Path) -> AppResult<()> {
+    let source = fs::read_to_string(path)?;
+    run_source(ctx, source, StopSignal::on_ctrl_c()).await
+}
+
+pub async fn run_source(ctx: &Context<'_>, source: String, mut stop: StopSignal) -> AppResult<()> {
+    let (calls, mut pending) = unbounded_channel();
+    let script_stop = stop.clone();
+    let script = std::thread::spawn(move || {
+        let engine = build_engine(calls, script_stop);
+        engine.run(&source).map_err(|e| match *e {
+            EvalAltResult::ErrorTerminated(..) => String::from("stopped"),
+            e => e.to_string(),
+        })
+    });
+
+    // the engine holds the only senders, so this ends once the script finishes
+    while let Some(Call { name, args, reply }) = pending.recv().await {
+        let result = if stop.is_stopped() {
+            Err(String::from("stopped"))
+        } else {
+            serve(ctx, &mut stop, name, &args)
+                .await
+                .map_err(|e| e.to_string())
+        };
+        let _ = reply.send(result);
+    }
+
+    script
+        .join()
+        .map_err(|_| AppError::Script(String::from("the script thread panicked")))?
+        .map_err(AppError::Script)
+}
+
+fn to_dynamic<T: Serialize>(value: T) -> AppResult<Dynamic> {
+    rhai::serde::to_dynamic(value).map_err(|e| AppError::Script(e.to_string()))
+}
+
+fn stopped() -> AppError {
+    AppError::Script(String::from("stopped"))
+}
+
+fn string_arg(args: &[Dynamic], index: usize) -> String {
+    args[index].to_string()
+}
+
+fn good_arg(args: &[Dynamic], index: usize) -> AppResult<TradeSymbol> {
+    let good = string_arg(args, index);
+    // Goods the server adds parse as `Unknown`, which a script can't have meant
+    match TradeSymbol::from_symbol(&good) {
+        TradeSymbol::Unknown(_) => Err(AppError::Usage(format!("unknown trade good `{good}`"))),
+        symbol => Ok(symbol),
+    }
+}
+
+fn units_arg(args: &[Dynamic], index: usize) -> AppResult<u32> {
+    let units = args[index].as_int().unwrap_or_default();
+    u32::try_from(units).map_err(|_| AppError::Usage(format!("invalid number of units {units}")))
+}
+
+async fn serve(
+    ctx: &Context<'_>,
+    stop: &mut StopSignal,
+    name: &str,
+    args: &[Dynamic],
+) -> AppResult<Dynamic> {
+    let api = &ctx.api;
+    let s = |index| string_arg(args, index);
+    match name {
+        "agent" => to_dynamic(api.fetch_agent_info().await?.data),
+        "ships" => to_dynamic(api.list_ships().await?.data),
+        "ship" => to_dynamic(api.get_ship_status(s(0)).await?.data),
+        "nav" => to_dynamic(api.get_ship_nav_status(s(0)).await?.data),
+        "cargo" => to_dynamic(api.get_ship_cargo(s(0)).await?.data),
+        "cooldown" => to_dynamic(api.get_ship_cooldown(s(0)).await?.data),
+        "dock" => to_dynamic(api.dock_ship(s(0)).await?.data),
+        "orbit" => to_dynamic(api.orbit_ship(s(0)).await?.data),
+        "navigate" => to_dynamic(api.navigate_ship(s(0), s(1)).await?.data),
+        "refuel" => to_dynamic(api.refuel_ship(s(0), None).await?.data),
+        "extract" => to_dynamic(api.extract_resource(s(0), None).await?.data),
+        "survey" => to_dynamic(api.create_survey(s(0)).await?.data),
+        "sell" => to_dynamic(
+            api.sell_ship_cargo(s(0), good_arg(args, 1)?, units_arg(args, 2)?)
+                .await?
+                .data,
+        ),
+        "buy" => to_dynamic(
+            api.purchase_cargo(s(0), good_arg(args, 1)?, units_arg(args, 2)?)
+                .await?
+                .data,
+        ),
+        "market" => {
+            let market = api.get_market(s(0)).await?.data;
+            to_dynamic(market)
+        }
+        "waypoint" => to_dynamic(api.get_waypoint(s(0)).await?.data),
+        "waypoints" => to_dynamic(api.list_waypoints(s(0), None, None).await?.data),
+        "contracts" => to_dynamic(api.fetch_contracts().await?.data),
+        "deliver" => to_dynamic(
+            api.deliver_contract_goods(s(1), s(0), good_arg(args, 2)?, units_arg(args, 3)?)
+                .await?
+                .data,
+        ),
+        "fulfill" => to_dynamic(api.fulfill_contract(s(0)).await?.data),
+        "sleep" => {
+            let seconds = args[0]
+                .as_float()
+                .or_else(|_| args[0].as_int().map(|i| i as f64))
+                .unwrap_or_default();
+            if !stop
+                .sleep(StdDuration::from_secs_f64(seconds.max(0.0)))
+                .await
+            {
+                return Err(stopped());
+            }
+            Ok(Dynamic::UNIT)
+        }
+        "wait_until_arrival" => {
+            let nav = api.get_ship_nav_status(s(0)).await?.data;
+            if nav.status == ShipNavStatus::IN_TRANSIT
+                && !nav.route.has_arrived()
+                && !navigation::wait_for_arrival_or_stop(&nav.route, stop).await
+            {
+                return Err(stopped());
+            }
+            Ok(Dynamic::UNIT)
+        }
+        "wait_cooldown" => {
+            let ready_at = api
+                .get_ship_cooldown(s(0))
+                .await?
+                .data
+                .and_then(|cooldown| cooldown.expiration);
+            if !mining::wait_until(ready_at, stop, &notes::to_stdout).await {
+                return Err(stopped());
+            }
+            Ok(Dynamic::UNIT)
+        }
+        _ => Err(AppError::Usage(format!("unknown function {name}"))),
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+
+const SYMBOL_CACHE_FILE: &str = "symbols.json";
+
+/// Ship, waypoint and contract symbols seen in API responses, for shell completion
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbols {
+    #[serde(default)]
+    pub ships: BTreeSet<String>,
+    #[serde(default)]
+    pub waypoints: BTreeSet<String>,
+    #[serde(default)]
+    pub contracts: BTreeSet<String>,
+}
+
+pub struct SymbolCache {
+    path: PathBuf,
+    /// Serializes read-modify-write updates from concurrent requests
+    lock: Mutex<()>,
+}
+
+impl SymbolCache {
+    pub fn open(dir: &Path) -> Self {
+        SymbolCache {
+            path: dir.join(SYMBOL_CACHE_FILE),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn load(&self) -> std::io::Result<Symbols> {
+        if !self.path.exists() {
+            return Ok(Symbols::default());
+        }
+        let contents = fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Applies `change` to the cached symbols, writing them back only if they changed
+    pub fn update(&self, change: impl FnOnce(&mut Symbols)) -> std::io::Result<()> {
+        let _lock = self.lock.lock().unwrap();
+        let symbols = self.load()?;
+        let mut changed = symbols.clone();
+        change(&mut changed);
+        if changed == symbols {
+            return Ok(());
+        }
+        let file = File::create(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &changed)?;
+        writer.flush()
+    }
+}
@@ -0,0 +1,383 @@
+//! Writes a `SystemMap` as an SVG picture or a Graphviz graph for wikis and planning docs.
+//! Both use the same layout: waypoints at their coordinates with orbitals that share their
+//! parent's coordinates fanned out around it, so nothing is drawn on top of anything else.
+
+use std::{collections::HashMap, f64::consts::PI, io::Write};
+
+use clap::ValueEnum;
+
+use crate::{
+    domain::{Waypoint, WaypointTraitSymbol, WaypointType},
+    system_map::{has_trait, Bounds, SystemMap},
+};
+
+/// Width or height of the waypoints' bounding box in the picture, in pixels
+const MAP_SIZE: f64 = 800.0;
+const MARGIN: f64 = 100.0;
+/// Room to the right of the easternmost waypoint for its labels
+const LABELS_WIDTH: f64 = 240.0;
+/// Room to the right of the map for the systems jump gates lead to
+const LINKS_WIDTH: f64 = 220.0;
+/// How far orbitals sharing their parent's coordinates are drawn from it
+const ORBIT_OFFSET: f64 = 24.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Svg,
+    Dot,
+}
+
+pub fn export(map: &SystemMap, format: ExportFormat, out: &mut impl Write) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Svg => write_svg(map, out),
+        ExportFormat::Dot => write_dot(map, out),
+    }
+}
+
+/// Positions in picture pixels, with y growing downwards
+struct Layout {
+    bounds: Bounds,
+    scale: f64,
+    waypoints: HashMap<String, (f64, f64)>,
+    /// (parent, orbital) pairs
+    orbits: Vec<(String, String)>,
+}
+
+impl Layout {
+    fn of(map: &SystemMap) -> Layout {
+        let points = map
+            .waypoints
+            .iter()
+            .map(|w| (w.x as f64, w.y as f64))
+            .chain(map.ships.iter().map(|s| (s.x, s.y)));
+        let bounds = Bounds::of(points).unwrap_or(Bounds {
+            min_x: 0.0,
+            max_x: 0.0,
+            min_y: 0.0,
+            max_y: 0.0,
+        });
+        let span = (bounds.max_x - bounds.min_x)
+            .max(bounds.max_y - bounds.min_y)
+            .max(1.0);
+        let mut layout = Layout {
+            bounds,
+            scale: MAP_SIZE / span,
+            waypoints: HashMap::new(),
+            orbits: Vec::new(),
+        };
+
+        let by_symbol: HashMap<&str, &Waypoint> = map
+            .waypoints
+            .iter()
+            .map(|waypoint| (waypoint.symbol.as_str(), waypoint))
+            .collect();
+        for waypoint in &map.waypoints {
+            let position = layout.to_px(waypoint.x as f64, waypoint.y as f64);
+            layout.waypoints.insert(waypoint.symbol.clone(), position);
+        }
+        for parent in &map.waypoints {
+            let stacked: Vec<&Waypoint> = parent
+                .orbitals
+                .iter()
+                .filter_map(|orbital| by_symbol.get(orbital.symbol.as_str()).copied())
+                .filter(|orbital| (orbital.x, orbital.y) == (parent.x, parent.y))
+                .collect();
+            let (px, py) = layout.waypoints[&parent.symbol];
+            for (index, orbital) in stacked.iter().enumerate() {
+                let angle = -PI / 2.0 + 2.0 * PI * index as f64 / stacked.len() as f64;
+                let position = (
+                    px + ORBIT_OFFSET * angle.cos(),
+                    py + ORBIT_OFFSET * angle.sin(),
+                );
+                layout.waypoints.insert(orbital.symbol.clone(), position);
+            }
+            for orbital in &parent.orbitals {
+                layout
+                    .orbits
+                    .push((parent.symbol.clone(), orbital.symbol.clone()));
+            }
+        }
+        layout
+    }
+
+    fn to_px(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            MARGIN + (x - self.bounds.min_x) * self.scale,
+            MARGIN + (self.bounds.max_y - y) * self.scale,
+        )
+    }
+
+    fn width(&self) -> f64 {
+        MARGIN + (self.bounds.max_x - self.bounds.min_x) * self.scale + LABELS_WIDTH
+    }
+
+    fn height(&self) -> f64 {
+        2.0 * MARGIN + (self.bounds.max_y - self.bounds.min_y) * self.scale
+    }
+
+    /// Ships at a waypoint sit just off it, ships in transit at their interpolated position
+    fn ship_px(&self, map: &SystemMap, index: usize) -> (f64, f64) {
+        let ship = &map.ships[index];
+        match ship
+            .waypoint_symbol
+            .as_ref()
+            .and_then(|symbol| self.waypoints.get(symbol))
+        {
+            Some((x, y)) => {
+                let docked_before = map.ships[..index]
+                    .iter()
+                    .filter(|other| other.waypoint_symbol == ship.waypoint_symbol)
+                    .count();
+                (x + 14.0 + 12.0 * docked_before as f64, y - 12.0)
+            }
+            None => self.to_px(ship.x, ship.y),
+        }
+    }
+}
+
+fn waypoint_style(waypoint_type: &WaypointType) -> (&'static str, f64) {
+    match waypoint_type {
+        WaypointType::PLANET => ("#4f86c6", 10.0),
+        WaypointType::GAS_GIANT => ("#c98f4f", 12.0),
+        WaypointType::MOON => ("#9a9a9a", 6.0),
+        WaypointType::ORBITAL_STATION => ("#6fbf73", 6.0),
+        WaypointType::JUMP_GATE => ("#b36ad1", 8.0),
+        WaypointType::ASTEROID_FIELD => ("#8c6d4f", 7.0),
+        WaypointType::ASTEROID => ("#8c6d4f", 4.0),
+        WaypointType::ENGINEERED_ASTEROID => ("#a9825a", 5.0),
+        WaypointType::ASTEROID_BASE => ("#7fa36b", 6.0),
+        WaypointType::NEBULA => ("#d96fa8", 9.0),
+        WaypointType::DEBRIS_FIELD => ("#777777", 6.0),
+        WaypointType::GRAVITY_WELL => ("#333333", 9.0),
+        WaypointType::ARTIFICIAL_GRAVITY_WELL => ("#4a3d5c", 9.0),
+        WaypointType::FUEL_STATION => ("#e0b040", 6.0),
+        WaypointType::Unknown(_) => ("#ffffff", 6.0),
+    }
+}
+
+fn trait_list(waypoint: &Waypoint) -> String {
+    waypoint
+        .traits
+        .iter()
+        .map(|t| format!("{:?}", t.symbol))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn write_svg(map: &SystemMap, out: &mut impl Write) -> std::io::Result<()> {
+    let layout = Layout::of(map);
+    let links: Vec<_> = map
+        .jump_gates
+        .iter()
+        .flat_map(|(gate, jump_gate)| {
+            jump_gate
+                .connections
+                .iter()
+                .map(move |connection| (gate, connection))
+        })
+        .collect();
+    let width = layout.width() + if links.is_empty() { 0.0 } else { LINKS_WIDTH };
+    let height = layout.height().max(MARGIN + 24.0 * links.len() as f64);
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.0} {height:.0}" font-family="sans-serif">"#
+    )?;
+    writeln!(
+        out,
+        r##"<rect width="100%" height="100%" fill="#0d1321"/>"##
+    )?;
+    writeln!(
+        out,
+        r##"<text x="16" y="28" font-size="18" fill="#ffffff">{}</text>"##,
+        xml_escape(&map.system_symbol)
+    )?;
+
+    for (parent, orbital) in &layout.orbits {
+        if let (Some((x1, y1)), Some((x2, y2))) =
+            (layout.waypoints.get(parent), layout.waypoints.get(orbital))
+        {
+            writeln!(
+                out,
+                r##"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="#56627a" stroke-dasharray="3 3"/>"##
+            )?;
+        }
+    }
+
+    let links_x = layout.width() + 20.0;
+    for (index, (gate, connection)) in links.iter().enumerate() {
+        let Some((x1, y1)) = layout.waypoints.get(*gate) else {
+            continue;
+        };
+        let y2 = MARGIN + 24.0 * index as f64;
+        writeln!(
+            out,
+            r##"<line x1="{x1:.1}" y1="{y1:.1}" x2="{links_x:.1}" y2="{y2:.1}" stroke="#b36ad1" stroke-dasharray="8 4"/>"##
+        )?;
+        writeln!(
+            out,
+            r##"<text x="{:.1}" y="{:.1}" font-size="12" fill="#e0c8ee">{} ({})</text>"##,
+            links_x + 6.0,
+            y2 + 4.0,
+            xml_escape(connection),
+            xml_escape(&Waypoint::get_system_id(connection))
+        )?;
+    }
+
+    let mut waypoints: Vec<&Waypoint> = map.waypoints.iter().collect();
+    waypoints.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    for waypoint in waypoints {
+        let (x, y) = layout.waypoints[&waypoint.symbol];
+        let (fill, radius) = waypoint_style(&waypoint.waypoint_type);
+        let traits = trait_list(waypoint);
+        writeln!(
+            out,
+            r##"<circle cx="{x:.1}" cy="{y:.1}" r="{radius}" fill="{fill}"><title>{} {:?}</title></circle>"##,
+            xml_escape(&waypoint.symbol),
+            waypoint.waypoint_type
+        )?;
+        writeln!(
+            out,
+            r##"<text x="{:.1}" y="{:.1}" font-size="11" fill="#ffffff">{} {:?}</text>"##,
+            x + radius + 4.0,
+            y + 4.0,
+            xml_escape(&waypoint.symbol),
+            waypoint.waypoint_type
+        )?;
+        if !traits.is_empty() {
+            writeln!(
+                out,
+                r##"<text x="{:.1}" y="{:.1}" font-size="9" fill="#9fb3c8">{}</text>"##,
+                x + radius + 4.0,
+                y + 16.0,
+                xml_escape(&traits)
+            )?;
+        }
+    }
+
+    for (index, ship) in map.ships.iter().enumerate() {
+        let (x, y) = layout.ship_px(map, index);
+        writeln!(
+            out,
+            r##"<polygon points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" fill="#f2c14e"><title>{}</title></polygon>"##,
+            x,
+            y - 6.0,
+            x - 5.0,
+            y + 4.0,
+            x + 5.0,
+            y + 4.0,
+            xml_escape(&ship.location)
+        )?;
+        writeln!(
+            out,
+            r##"<text x="{:.1}" y="{:.1}" font-size="10" fill="#f2c14e">{}</text>"##,
+            x + 7.0,
+            y - 4.0,
+            xml_escape(&ship.symbol)
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Pins the positions of waypoints and ships, which `layout=neato` keeps, so the graph looks
+/// like the system. Connected systems are placed freely.
+pub fn write_dot(map: &SystemMap, out: &mut impl Write) -> std::io::Result<()> {
+    let layout = Layout::of(map);
+    // Graphviz points grow upwards
+    let pos = |(x, y): (f64, f64)| format!("{x:.1},{:.1}!", layout.height() - y);
+
+    writeln!(out, "graph \"{}\" {{", dot_escape(&map.system_symbol))?;
+    writeln!(out, "  layout=neato;")?;
+    writeln!(out, "  label=\"{}\";", dot_escape(&map.system_symbol))?;
+    writeln!(
+        out,
+        "  node [fontname=\"Helvetica\" fontsize=10 style=filled];"
+    )?;
+
+    let mut waypoints: Vec<&Waypoint> = map.waypoints.iter().collect();
+    waypoints.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    for waypoint in &waypoints {
+        let (fill, _) = waypoint_style(&waypoint.waypoint_type);
+        let mut label = format!(
+            "{}\\n{:?}",
+            dot_escape(&waypoint.symbol),
+            waypoint.waypoint_type
+        );
+        let traits = trait_list(waypoint);
+        if !traits.is_empty() {
+            label.push_str(&format!("\\n{}", dot_escape(&traits)));
+        }
+        let shape = if has_trait(waypoint, WaypointTraitSymbol::SHIPYARD) {
+            "doublecircle"
+        } else if waypoint.waypoint_type == WaypointType::JUMP_GATE {
+            "octagon"
+        } else {
+            "circle"
+        };
+        writeln!(
+            out,
+            "  \"{}\" [label=\"{label}\" shape={shape} fillcolor=\"{fill}\" pos=\"{}\"];",
+            dot_escape(&waypoint.symbol),
+            pos(layout.waypoints[&waypoint.symbol])
+        )?;
+    }
+    for waypoint in &waypoints {
+        for orbital in &waypoint.orbitals {
+            writeln!(
+                out,
+                "  \"{}\" -- \"{}\" [style=dashed label=\"orbital\"];",
+                dot_escape(&waypoint.symbol),
+                dot_escape(&orbital.symbol)
+            )?;
+        }
+    }
+
+    for (index, ship) in map.ships.iter().enumerate() {
+        writeln!(
+            out,
+            "  \"{}\" [label=\"{}\\n{}\" shape=triangle fillcolor=\"#f2c14e\" pos=\"{}\"];",
+            dot_escape(&ship.symbol),
+            dot_escape(&ship.symbol),
+            dot_escape(&ship.location),
+            pos(layout.ship_px(map, index))
+        )?;
+        if let Some(waypoint_symbol) = &ship.waypoint_symbol {
+            writeln!(
+                out,
+                "  \"{}\" -- \"{}\" [style=dotted];",
+                dot_escape(&ship.symbol),
+                dot_escape(waypoint_symbol)
+            )?;
+        }
+    }
+
+    for (gate, jump_gate) in &map.jump_gates {
+        for connection in &jump_gate.connections {
+            writeln!(
+                out,
+                "  \"{}\" [label=\"{}\\n{}\" shape=doubleoctagon fillcolor=\"#e0c8ee\"];",
+                dot_escape(connection),
+                dot_escape(connection),
+                dot_escape(&Waypoint::get_system_id(connection))
+            )?;
+            writeln!(
+                out,
+                "  \"{}\" -- \"{}\" [style=bold color=\"#b36ad1\"];",
+                dot_escape(gate),
+                dot_escape(connection)
+            )?;
+        }
+    }
+    writeln!(out, "}}")
+}
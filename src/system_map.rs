@@ -0,0 +1,309 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    api::Api,
+    domain::{JumpGate, ShipNav, ShipNavStatus, Waypoint, WaypointTraitSymbol, WaypointType},
+    error::AppResult,
+};
+
+/// Grid size in cells. A cell is three characters wide, the waypoint glyph, its market or
+/// shipyard mark and the ship there, which keeps the map roughly square in a terminal.
+const MAP_COLUMNS: usize = 25;
+const MAP_ROWS: usize = 19;
+
+const LEGEND: &str = "O planet  G gas giant  o moon  # station  @ jump gate  * asteroid field
+~ nebula  % debris field  V gravity well  ? other  $ marketplace  S shipyard  1-9 a-z our ships";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShipPosition {
+    pub symbol: String,
+    pub x: f64,
+    pub y: f64,
+    /// Where the ship is, e.g. `DOCKED at X1-DF55-20250Z`
+    pub location: String,
+    /// The waypoint the ship is at, `None` in transit
+    pub waypoint_symbol: Option<String>,
+}
+
+impl ShipPosition {
+    /// Ships in transit are placed along the straight line of their route by the share of the
+    /// flight time gone at `now`
+    pub fn from_nav(symbol: &str, nav: &ShipNav, now: DateTime<Utc>) -> Self {
+        let route = &nav.route;
+        let destination = (route.destination.x as f64, route.destination.y as f64);
+        let (x, y, location, waypoint_symbol) =
+            if nav.status == ShipNavStatus::IN_TRANSIT && route.arrival > now {
+                let flight = (route.arrival - route.departure_time).num_milliseconds() as f64;
+                let flown = (now - route.departure_time).num_milliseconds() as f64;
+                let progress = if flight > 0.0 {
+                    (flown / flight).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+                let origin = (route.origin.x as f64, route.origin.y as f64);
+                (
+                    origin.0 + (destination.0 - origin.0) * progress,
+                    origin.1 + (destination.1 - origin.1) * progress,
+                    format!(
+                        "IN_TRANSIT {} -> {}, {:.0}%",
+                        route.origin.symbol,
+                        route.destination.symbol,
+                        progress * 100.0
+                    ),
+                    None,
+                )
+            } else {
+                let status = match &nav.status {
+                    ShipNavStatus::DOCKED => "DOCKED",
+                    // Arrived, the status just hasn't been fetched since
+                    ShipNavStatus::IN_ORBIT | ShipNavStatus::IN_TRANSIT => "IN_ORBIT",
+                    ShipNavStatus::Unknown(status) => status,
+                };
+                (
+                    destination.0,
+                    destination.1,
+                    format!("{status} at {}", nav.waypoint_symbol),
+                    Some(nav.waypoint_symbol.clone()),
+                )
+            };
+        ShipPosition {
+            symbol: symbol.to_owned(),
+            x,
+            y,
+            location,
+            waypoint_symbol,
+        }
+    }
+}
+
+/// A system's waypoints and our ships in it, drawn as a grid with the largest y at the top
+pub struct SystemMap {
+    pub system_symbol: String,
+    pub waypoints: Vec<Waypoint>,
+    pub ships: Vec<ShipPosition>,
+    /// Jump gates in the system by waypoint symbol, only fetched when asked for
+    pub jump_gates: Vec<(String, JumpGate)>,
+}
+
+#[derive(Default, Clone)]
+struct Cell {
+    /// The most prominent waypoint's glyph, as (rank, glyph)
+    glyph: Option<(u8, char)>,
+    marketplace: bool,
+    shipyard: bool,
+    ships: Vec<usize>,
+}
+
+impl Cell {
+    fn render(&self) -> String {
+        let glyph = self.glyph.map_or(' ', |(_, glyph)| glyph);
+        let mark = if self.shipyard {
+            'S'
+        } else if self.marketplace {
+            '$'
+        } else {
+            ' '
+        };
+        let ship = match self.ships.as_slice() {
+            [] => ' ',
+            [index] => ship_label(*index),
+            _ => '+',
+        };
+        if glyph == ' ' && mark == ' ' && ship == ' ' {
+            String::from(".  ")
+        } else {
+            format!("{glyph}{mark}{ship}")
+        }
+    }
+}
+
+/// The glyph for a waypoint type and how prominent it is, planets hide the moons and stations
+/// orbiting them
+pub fn waypoint_glyph(waypoint_type: &WaypointType) -> (u8, char) {
+    match waypoint_type {
+        WaypointType::PLANET => (0, 'O'),
+        WaypointType::GAS_GIANT => (0, 'G'),
+        WaypointType::JUMP_GATE => (1, '@'),
+        WaypointType::ASTEROID_FIELD => (2, '*'),
+        WaypointType::ASTEROID => (2, 'a'),
+        WaypointType::ENGINEERED_ASTEROID => (2, 'e'),
+        WaypointType::NEBULA => (2, '~'),
+        WaypointType::DEBRIS_FIELD => (2, '%'),
+        WaypointType::GRAVITY_WELL => (2, 'V'),
+        WaypointType::ARTIFICIAL_GRAVITY_WELL => (2, 'W'),
+        WaypointType::ORBITAL_STATION => (3, '#'),
+        WaypointType::ASTEROID_BASE => (3, 'B'),
+        WaypointType::FUEL_STATION => (3, 'F'),
+        WaypointType::MOON => (4, 'o'),
+        WaypointType::Unknown(_) => (2, '?'),
+    }
+}
+
+/// Ships are numbered 1-9 and then lettered on the map
+fn ship_label(index: usize) -> char {
+    char::from_digit(index as u32 + 1, 36).unwrap_or('+')
+}
+
+pub(crate) fn has_trait(waypoint: &Waypoint, symbol: WaypointTraitSymbol) -> bool {
+    waypoint.traits.iter().any(|t| t.symbol == symbol)
+}
+
+pub(crate) struct Bounds {
+    pub(crate) min_x: f64,
+    pub(crate) max_x: f64,
+    pub(crate) min_y: f64,
+    pub(crate) max_y: f64,
+}
+
+impl Bounds {
+    pub(crate) fn of(points: impl Iterator<Item = (f64, f64)>) -> Option<Bounds> {
+        points.fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                None => Bounds {
+                    min_x: x,
+                    max_x: x,
+                    min_y: y,
+                    max_y: y,
+                },
+                Some(b) => Bounds {
+                    min_x: b.min_x.min(x),
+                    max_x: b.max_x.max(x),
+                    min_y: b.min_y.min(y),
+                    max_y: b.max_y.max(y),
+                },
+            })
+        })
+    }
+
+    /// The (column, row) of a point, with the largest y in the top row
+    fn cell(&self, x: f64, y: f64) -> (usize, usize) {
+        let scale = |value: f64, min: f64, max: f64, cells: usize| {
+            let span = (max - min).max(1.0);
+            (((value - min) / span) * (cells - 1) as f64).round() as usize
+        };
+        (
+            scale(x, self.min_x, self.max_x, MAP_COLUMNS),
+            MAP_ROWS - 1 - scale(y, self.min_y, self.max_y, MAP_ROWS),
+        )
+    }
+}
+
+impl fmt::Display for SystemMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let points = self
+            .waypoints
+            .iter()
+            .map(|w| (w.x as f64, w.y as f64))
+            .chain(self.ships.iter().map(|s| (s.x, s.y)));
+        let Some(bounds) = Bounds::of(points) else {
+            return write!(f, "No waypoints or ships in {}", self.system_symbol);
+        };
+
+        let mut grid = vec![vec![Cell::default(); MAP_COLUMNS]; MAP_ROWS];
+        for waypoint in &self.waypoints {
+            let (column, row) = bounds.cell(waypoint.x as f64, waypoint.y as f64);
+            let cell = &mut grid[row][column];
+            let glyph = waypoint_glyph(&waypoint.waypoint_type);
+            if cell.glyph.is_none_or(|shown| glyph.0 < shown.0) {
+                cell.glyph = Some(glyph);
+            }
+            cell.marketplace |= has_trait(waypoint, WaypointTraitSymbol::MARKETPLACE);
+            cell.shipyard |= has_trait(waypoint, WaypointTraitSymbol::SHIPYARD);
+        }
+        for (index, ship) in self.ships.iter().enumerate() {
+            let (column, row) = bounds.cell(ship.x, ship.y);
+            grid[row][column].ships.push(index);
+        }
+
+        writeln!(f, "{}", self.system_symbol)?;
+        let border = format!("+{}+", "-".repeat(MAP_COLUMNS * 3));
+        writeln!(f, "{border}")?;
+        for row in &grid {
+            let line: String = row.iter().map(Cell::render).collect();
+            writeln!(f, "|{line}|")?;
+        }
+        writeln!(f, "{border}")?;
+        writeln!(
+            f,
+            "x {} to {}, y {} to {}",
+            bounds.min_x, bounds.max_x, bounds.min_y, bounds.max_y
+        )?;
+        writeln!(f, "{LEGEND}")?;
+
+        if !self.ships.is_empty() {
+            writeln!(f)?;
+            for (index, ship) in self.ships.iter().enumerate() {
+                writeln!(
+                    f,
+                    "{} {:<12} {}",
+                    ship_label(index),
+                    ship.symbol,
+                    ship.location
+                )?;
+            }
+        }
+
+        writeln!(f)?;
+        let mut waypoints: Vec<&Waypoint> = self.waypoints.iter().collect();
+        waypoints.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        for waypoint in waypoints {
+            let mut marks = String::new();
+            marks.push(waypoint_glyph(&waypoint.waypoint_type).1);
+            if has_trait(waypoint, WaypointTraitSymbol::MARKETPLACE) {
+                marks.push('$');
+            }
+            if has_trait(waypoint, WaypointTraitSymbol::SHIPYARD) {
+                marks.push('S');
+            }
+            writeln!(
+                f,
+                "{marks:<3} {:<16} {:<16} ({}, {})",
+                waypoint.symbol,
+                format!("{:?}", waypoint.waypoint_type),
+                waypoint.x,
+                waypoint.y
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Fetches the system's waypoints and where our ships in it are now, and with `jump_gates`
+/// the systems its jump gates lead to
+pub async fn map(api: &Api<'_>, system_symbol: String, jump_gates: bool) -> AppResult<SystemMap> {
+    let waypoints = api
+        .list_waypoints(system_symbol.clone(), None, None)
+        .await?
+        .data;
+    let now = Utc::now();
+    let ships = api
+        .list_ships()
+        .await?
+        .data
+        .iter()
+        .filter(|ship| ship.nav.system_symbol == system_symbol)
+        .map(|ship| ShipPosition::from_nav(&ship.symbol, &ship.nav, now))
+        .collect();
+    let mut gates = Vec::new();
+    if jump_gates {
+        let gate_symbols = waypoints
+            .iter()
+            .filter(|waypoint| waypoint.waypoint_type == WaypointType::JUMP_GATE)
+            .map(|waypoint| waypoint.symbol.clone());
+        for gate_symbol in gate_symbols {
+            // Uncharted gates don't reveal their connections, which shouldn't cost the map
+            match api.get_jump_gate(gate_symbol.clone()).await {
+                Ok(res) => gates.push((gate_symbol, res.data)),
+                Err(e) => eprintln!("warning: no connections for jump gate {gate_symbol}: {e}"),
+            }
+        }
+    }
+    Ok(SystemMap {
+        system_symbol,
+        waypoints,
+        ships,
+        jump_gates: gates,
+    })
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, fmt};
+
+use clap::ValueEnum;
+
+use crate::{
+    api::Api,
+    domain::{format_duration, ShipNavFlightMode},
+    error::AppResult,
+    estimator::{distance, travel_seconds},
+    market_history::{MarketHistory, PriceRecord},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum TradeRouteSort {
+    /// Profit per unit of cargo
+    Unit,
+    /// Profit per full cargo hold
+    Trip,
+    /// Profit per minute, including travel time
+    #[default]
+    Minute,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TradeShip {
+    pub location: (i32, i32),
+    pub cargo_capacity: i32,
+    pub speed: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeRoute {
+    pub trade_symbol: String,
+    pub buy_waypoint: String,
+    pub sell_waypoint: String,
+    pub purchase_price: u32,
+    pub sell_price: u32,
+    pub units_per_trip: i32,
+    /// From the ship's location to the buying market, then on to the selling market
+    pub trip_seconds: i64,
+}
+
+impl TradeRoute {
+    pub fn profit_per_unit(&self) -> i64 {
+        self.sell_price as i64 - self.purchase_price as i64
+    }
+
+    pub fn profit_per_trip(&self) -> i64 {
+        self.profit_per_unit() * self.units_per_trip as i64
+    }
+
+    pub fn profit_per_minute(&self) -> f64 {
+        self.profit_per_trip() as f64 / (self.trip_seconds.max(1) as f64 / 60.0)
+    }
+}
+
+impl fmt::Display for TradeRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<20} {} ({}) -> {} ({})  {:>5}/unit  {:>7}/trip of {}  {:>8.1}/min  trip {}",
+            self.trade_symbol,
+            self.buy_waypoint,
+            self.purchase_price,
+            self.sell_waypoint,
+            self.sell_price,
+            self.profit_per_unit(),
+            self.profit_per_trip(),
+            self.units_per_trip,
+            self.profit_per_minute(),
+            format_duration(chrono::Duration::seconds(self.trip_seconds))
+        )
+    }
+}
+
+/// Pairs every market selling a good with every market buying it for more. Only markets with
+/// known coordinates are considered. A trip carries the ship's full hold, capped by the
+/// smaller trade volume of the two markets since prices move beyond it.
+pub fn find_trade_routes(
+    prices: &[PriceRecord],
+    coordinates: &HashMap<String, (i32, i32)>,
+    ship: TradeShip,
+    sort: TradeRouteSort,
+) -> Vec<TradeRoute> {
+    let mut routes = Vec::new();
+    for buy in prices {
+        let Some(&buy_at) = coordinates.get(&buy.waypoint_symbol) else {
+            continue;
+        };
+        for sell in prices {
+            if sell.trade_symbol != buy.trade_symbol
+                || sell.waypoint_symbol == buy.waypoint_symbol
+                || sell.sell_price <= buy.purchase_price
+            {
+                continue;
+            }
+            let Some(&sell_at) = coordinates.get(&sell.waypoint_symbol) else {
+                continue;
+            };
+            let trip_seconds = travel_seconds(
+                ShipNavFlightMode::CRUISE,
+                distance(ship.location, buy_at),
+                ship.speed,
+            ) + travel_seconds(
+                ShipNavFlightMode::CRUISE,
+                distance(buy_at, sell_at),
+                ship.speed,
+            );
+            routes.push(TradeRoute {
+                trade_symbol: buy.trade_symbol.clone(),
+                buy_waypoint: buy.waypoint_symbol.clone(),
+                sell_waypoint: sell.waypoint_symbol.clone(),
+                purchase_price: buy.purchase_price,
+                sell_price: sell.sell_price,
+                units_per_trip: ship
+                    .cargo_capacity
+                    .min(buy.trade_volume as i32)
+                    .min(sell.trade_volume as i32),
+                trip_seconds,
+            });
+        }
+    }
+
+    match sort {
+        TradeRouteSort::Unit => routes.sort_by_key(|r| std::cmp::Reverse(r.profit_per_unit())),
+        TradeRouteSort::Trip => routes.sort_by_key(|r| std::cmp::Reverse(r.profit_per_trip())),
+        TradeRouteSort::Minute => routes.sort_by(|a, b| {
+            b.profit_per_minute()
+                .partial_cmp(&a.profit_per_minute())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+    }
+    routes
+}
+
+pub async fn routes(
+    api: &Api<'_>,
+    market_history: &MarketHistory,
+    ship_symbol: String,
+    sort: TradeRouteSort,
+) -> AppResult<Vec<TradeRoute>> {
+    let ship = api.get_ship_status(ship_symbol).await?.data;
+    let waypoints = api
+        .list_waypoints(ship.nav.system_symbol.clone(), None, None)
+        .await?
+        .data;
+    let coordinates: HashMap<_, _> = waypoints
+        .into_iter()
+        .map(|w| (w.symbol, (w.x, w.y)))
+        .collect();
+    let here = &ship.nav.route.destination;
+    let trade_ship = TradeShip {
+        location: (here.x, here.y),
+        cargo_capacity: ship.cargo.capacity,
+        speed: ship.engine.speed,
+    };
+    Ok(find_trade_routes(
+        &market_history.latest()?,
+        &coordinates,
+        trade_ship,
+        sort,
+    ))
+}
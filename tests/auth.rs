@@ -20,6 +20,7 @@ mod auth_tests {
                 agent: Agent {
                     credits: 100,
                     headquarters: String::from("headquarters"),
+                    account_id: None,
                     ship_count: None,
                     starting_faction: String::from("starting_faction"),
                     symbol: String::from("test_agent"),
@@ -27,7 +28,7 @@ mod auth_tests {
                 token: String::from("dummy_token"),
             };
 
-            save_user_info(&register_resp, &current_user_dir.path());
+            save_user_info(&register_resp, current_user_dir.path()).unwrap();
 
             let file_contents = fs::read_to_string(current_user_file.path()).unwrap();
             let result = serde_json::to_string(&register_resp).unwrap();
@@ -44,6 +45,7 @@ mod auth_tests {
                 agent: Agent {
                     credits: 100,
                     headquarters: String::from("headquarters"),
+                    account_id: None,
                     ship_count: None,
                     starting_faction: String::from("starting_faction"),
                     symbol: String::from("test_agent"),
@@ -51,7 +53,7 @@ mod auth_tests {
                 token: String::from("dummy_token"),
             };
 
-            save_user_info(&register_resp_1, &current_user_dir.path());
+            save_user_info(&register_resp_1, current_user_dir.path()).unwrap();
 
             let file_contents = fs::read_to_string(current_user_file.path()).unwrap();
             let result = serde_json::to_string(&register_resp_1).unwrap();
@@ -61,6 +63,7 @@ mod auth_tests {
                 agent: Agent {
                     credits: 200,
                     headquarters: String::from("second headquarters"),
+                    account_id: None,
                     ship_count: None,
                     starting_faction: String::from("pro_faction"),
                     symbol: String::from("test_agent_2"),
@@ -68,7 +71,7 @@ mod auth_tests {
                 token: String::from("dummy_token_2"),
             };
 
-            save_user_info(&register_resp_2, &current_user_dir.path());
+            save_user_info(&register_resp_2, current_user_dir.path()).unwrap();
 
             let file_contents = fs::read_to_string(current_user_file.path()).unwrap();
             let result = serde_json::to_string(&register_resp_2).unwrap();
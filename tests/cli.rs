@@ -1,5 +1,44 @@
 #[cfg(test)]
 mod cli_tests {
+    use std::{
+        fs::File,
+        io::{BufWriter, Write},
+    };
+
+    use assert_fs::prelude::{FileTouch, PathChild};
+    use space_traders_rust::{domain::Agent, UserInfo};
+
+    fn logged_in_user_dir() -> assert_fs::TempDir {
+        let current_user_dir = assert_fs::TempDir::new().unwrap();
+        let current_user_file = current_user_dir.child("current_user.json");
+        current_user_file.touch().unwrap();
+
+        let test_user_info = UserInfo {
+            token: String::from("fake_token"),
+            agent: Agent {
+                credits: 100,
+                symbol: "Fake_Agent".to_string(),
+                account_id: None,
+                ship_count: None,
+                headquarters: "X1-DF55-20250Z".to_string(),
+                starting_faction: "starting_faction".to_string(),
+            },
+        };
+        let file = File::create(current_user_file).unwrap();
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &test_user_info).unwrap();
+        writer.flush().unwrap();
+        current_user_dir
+    }
+
+    /// The ship fixture with its nav replaced and `fuel_current` units in the tank
+    fn ship_json(nav: &str, fuel_current: i32) -> String {
+        let mut ship: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/ship.json")).unwrap();
+        ship["nav"] = serde_json::from_str(nav).unwrap();
+        ship["fuel"]["current"] = fuel_current.into();
+        ship.to_string()
+    }
 
     mod test_whoami_command {
         type TestResult = Result<(), Box<dyn std::error::Error>>;
@@ -25,6 +64,7 @@ mod cli_tests {
                 agent: Agent {
                     credits: 100,
                     symbol: "Fake_Agent".to_string(),
+                    account_id: None,
                     ship_count: None,
                     headquarters: "fake_headquarters".to_string(),
                     starting_faction: "starting_faction".to_string(),
@@ -34,6 +74,7 @@ mod cli_tests {
             let api_response_agent: Agent = Agent {
                 credits: 1000,
                 symbol: "Fake_Agent".to_string(),
+                account_id: None,
                 ship_count: None,
                 headquarters: "fake_headquarters_2".to_string(),
                 starting_faction: "starting_faction_2".to_string(),
@@ -59,7 +100,7 @@ mod cli_tests {
                 .create();
 
             Command::cargo_bin(PRG)?
-                .args(&["whoami"])
+                .args(["whoami"])
                 .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
                 .env("TEST_API_BASE_URL", url)
                 .assert()
@@ -70,4 +111,1503 @@ mod cli_tests {
             Ok(())
         }
     }
+
+    mod test_exit_codes {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use assert_fs::prelude::{PathAssert, PathChild};
+        use predicates::prelude::predicate;
+
+        use super::logged_in_user_dir;
+
+        #[test]
+        fn exits_with_auth_missing_when_not_logged_in() -> TestResult {
+            let current_user_dir = assert_fs::TempDir::new().unwrap();
+
+            Command::cargo_bin(PRG)?
+                .args(["whoami"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .code(3)
+                .stdout(predicate::str::is_empty())
+                .stderr(predicate::str::contains("Not logged in"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn exits_with_failure_on_a_corrupt_token_file() -> TestResult {
+            let current_user_dir = assert_fs::TempDir::new().unwrap();
+            std::fs::write(
+                current_user_dir.child("current_user.json").path(),
+                "{\"token\":",
+            )?;
+
+            Command::cargo_bin(PRG)?
+                .args(["whoami"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .code(1)
+                .stdout(predicate::str::is_empty())
+                .stderr(predicate::str::contains("current_user.json is corrupt"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn registers_when_not_logged_in() -> TestResult {
+            let current_user_dir = assert_fs::TempDir::new().unwrap();
+
+            let mut server = mockito::Server::new();
+            let mock = server
+                .mock("POST", "/register")
+                .with_status(201)
+                .with_header("content-type", "application/json")
+                .with_body(
+                    r#"{"data":{"token":"new_token","agent":{"symbol":"NEW_AGENT","headquarters":"X1-DF55-20250Z","credits":100000,"startingFaction":"COSMIC","shipCount":null}}}"#,
+                )
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["register", "--username", "NEW_AGENT"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success();
+
+            mock.assert();
+            current_user_dir
+                .child("current_user.json")
+                .assert(predicate::str::contains("new_token"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn exits_with_usage_error_without_a_command() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            Command::cargo_bin(PRG)?
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .code(2)
+                .stdout(predicate::str::is_empty());
+
+            Ok(())
+        }
+
+        #[test]
+        fn exits_with_service_error_on_api_error_response() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            let mut server = mockito::Server::new();
+            let mock = server
+                .mock("GET", "/my/ships/FAKE-1")
+                .with_status(404)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"error":{"message":"Ship not found","code":404}}"#)
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["ship", "status", "--ship-symbol", "FAKE-1"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .code(4)
+                .stdout(predicate::str::is_empty())
+                .stderr(predicate::str::contains("Ship not found"));
+
+            mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn exits_with_parse_error_on_malformed_response() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            let mut server = mockito::Server::new();
+            let mock = server
+                .mock("GET", "/my/agent")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"data":{"symbol":"Fake_Agent"}}"#)
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["whoami"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .code(6)
+                .stdout(predicate::str::is_empty());
+
+            mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn exits_with_network_error_when_server_is_unreachable() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            Command::cargo_bin(PRG)?
+                .args(["whoami"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", "http://127.0.0.1:1")
+                .assert()
+                .code(5)
+                .stdout(predicate::str::is_empty());
+
+            Ok(())
+        }
+    }
+
+    mod test_navigate_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use chrono::{Duration, Utc};
+        use predicates::prelude::predicate;
+
+        use super::{logged_in_user_dir, ship_json};
+
+        const WAYPOINT: &str = include_str!("fixtures/waypoint.json");
+
+        fn nav_json(status: &str, arrival: &str) -> String {
+            format!(
+                r#"{{
+                    "systemSymbol": "X1-DF55",
+                    "waypointSymbol": "X1-DF55-69207D",
+                    "route": {{
+                        "destination": {{"symbol": "X1-DF55-69207D", "type": "ASTEROID_FIELD", "systemSymbol": "X1-DF55", "x": 10, "y": 20}},
+                        "origin": {{"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0}},
+                        "departureTime": "2023-08-01T10:00:00.000Z",
+                        "arrival": "{arrival}"
+                    }},
+                    "status": "{status}",
+                    "flightMode": "CRUISE"
+                }}"#
+            )
+        }
+
+        /// A ship sitting at X1-DF55-20250Z (0, 0)
+        fn parked_nav_json(status: &str) -> String {
+            nav_json(status, "2023-08-01T10:00:00.000Z")
+                .replace("X1-DF55-69207D", "X1-DF55-20250Z")
+                .replace(r#""type": "ASTEROID_FIELD""#, r#""type": "PLANET""#)
+                .replace(r#""x": 10, "y": 20"#, r#""x": 0, "y": 0"#)
+        }
+
+        #[test]
+        fn fails_cleanly_when_ship_is_already_in_transit() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let arrival = (Utc::now() + Duration::minutes(5)).to_rfc3339();
+
+            let mut server = mockito::Server::new();
+            let ship_mock = server
+                .mock("GET", "/my/ships/ALI-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {}}}"#,
+                    ship_json(&nav_json("IN_TRANSIT", &arrival), 1200)
+                ))
+                .create();
+            let navigate_mock = server
+                .mock("POST", "/my/ships/ALI-1/navigate")
+                .expect(0)
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship",
+                    "navigate",
+                    "waypoint",
+                    "-s",
+                    "ALI-1",
+                    "-w",
+                    "X1-DF55-69207D",
+                    "--wait",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .code(1)
+                .stderr(predicate::str::contains("already in transit"));
+
+            ship_mock.assert();
+            navigate_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn docks_and_refuels_on_arrival() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let arrival = "2023-08-01T10:00:30.000Z";
+            let fuel = r#"{"current": 100, "capacity": 100}"#;
+            let agent = r#"{"symbol": "Fake_Agent", "headquarters": "X1-DF55-20250Z", "credits": 90, "startingFaction": "COSMIC"}"#;
+            let transaction = r#"{"waypointSymbol": "X1-DF55-69207D", "shipSymbol": "ALI-1", "tradeSymbol": "FUEL", "type": "PURCHASE", "units": 10, "pricePerUnit": 1, "totalPrice": 10, "timestamp": "2023-08-01T10:01:00.000Z"}"#;
+
+            let mut server = mockito::Server::new();
+            server
+                .mock("GET", "/my/ships/ALI-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {}}}"#,
+                    ship_json(&nav_json("IN_ORBIT", arrival), 1200)
+                ))
+                .create();
+            server
+                .mock("GET", "/systems/X1-DF55/waypoints/X1-DF55-69207D")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {WAYPOINT}}}"#))
+                .create();
+            let navigate_mock = server
+                .mock("POST", "/my/ships/ALI-1/navigate")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"fuel": {fuel}, "nav": {}}}}}"#,
+                    nav_json("IN_TRANSIT", arrival)
+                ))
+                .create();
+            let dock_mock = server
+                .mock("POST", "/my/ships/ALI-1/dock")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"nav": {}}}}}"#,
+                    nav_json("DOCKED", arrival)
+                ))
+                .create();
+            let refuel_mock = server
+                .mock("POST", "/my/ships/ALI-1/refuel")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"agent": {agent}, "fuel": {fuel}, "transaction": {transaction}}}}}"#
+                ))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship",
+                    "navigate",
+                    "waypoint",
+                    "-s",
+                    "ALI-1",
+                    "-w",
+                    "X1-DF55-69207D",
+                    "--refuel-on-arrival",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success();
+
+            navigate_mock.assert();
+            dock_mock.assert();
+            refuel_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn warns_when_the_hop_needs_more_fuel_than_the_ship_has() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let arrival = "2023-08-01T10:00:30.000Z";
+            let fuel = r#"{"current": 0, "capacity": 1200}"#;
+
+            let mut server = mockito::Server::new();
+            server
+                .mock("GET", "/my/ships/ALI-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {}}}"#,
+                    ship_json(&parked_nav_json("IN_ORBIT"), 10)
+                ))
+                .create();
+            server
+                .mock("GET", "/systems/X1-DF55/waypoints/X1-DF55-69207D")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {WAYPOINT}}}"#))
+                .create();
+            let navigate_mock = server
+                .mock("POST", "/my/ships/ALI-1/navigate")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"fuel": {fuel}, "nav": {}}}}}"#,
+                    nav_json("IN_TRANSIT", arrival)
+                ))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship",
+                    "navigate",
+                    "waypoint",
+                    "-s",
+                    "ALI-1",
+                    "-w",
+                    "X1-DF55-69207D",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stderr(predicate::str::contains(
+                    "warning: CRUISE to X1-DF55-69207D needs ~50 fuel but ALI-1 only has 10",
+                ));
+
+            navigate_mock.assert();
+
+            Ok(())
+        }
+    }
+
+    mod test_auto_state {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use mockito::{Mock, ServerGuard};
+        use predicates::prelude::predicate;
+
+        use super::logged_in_user_dir;
+
+        const NAV: &str = r#"{
+            "systemSymbol": "X1-DF55",
+            "waypointSymbol": "X1-DF55-20250Z",
+            "route": {
+                "destination": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                "origin": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                "departureTime": "2023-08-01T10:00:00.000Z",
+                "arrival": "2023-08-01T10:00:00.000Z"
+            },
+            "status": "IN_ORBIT",
+            "flightMode": "CRUISE"
+        }"#;
+
+        fn mock_sell(server: &mut ServerGuard) -> Mock {
+            let agent = r#"{"symbol": "Fake_Agent", "headquarters": "X1-DF55-20250Z", "credits": 190, "startingFaction": "COSMIC"}"#;
+            let cargo = r#"{"capacity": 30, "units": 0, "inventory": []}"#;
+            let transaction = r#"{"waypointSymbol": "X1-DF55-20250Z", "shipSymbol": "ALI-1", "tradeSymbol": "IRON_ORE", "type": "SELL", "units": 10, "pricePerUnit": 9, "totalPrice": 90, "timestamp": "2023-08-01T10:01:00.000Z"}"#;
+            server
+                .mock("POST", "/my/ships/ALI-1/sell")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"agent": {agent}, "cargo": {cargo}, "transaction": {transaction}}}}}"#
+                ))
+                .create()
+        }
+
+        #[test]
+        fn docks_before_selling_cargo() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            let mut server = mockito::Server::new();
+            let nav_mock = server
+                .mock("GET", "/my/ships/ALI-1/nav")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {NAV}}}"#))
+                .create();
+            let dock_mock = server
+                .mock("POST", "/my/ships/ALI-1/dock")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"nav": {}}}}}"#,
+                    NAV.replace("IN_ORBIT", "DOCKED")
+                ))
+                .create();
+            let sell_mock = mock_sell(&mut server);
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship", "cargo", "sell", "-s", "ALI-1", "-g", "iron-ore", "-u", "10",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stderr(predicate::str::contains("auto-state: docked ALI-1"));
+
+            nav_mock.assert();
+            dock_mock.assert();
+            sell_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn can_be_switched_off_in_settings() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            Command::cargo_bin(PRG)?
+                .args(["config", "set", "--auto-state=false"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .success();
+
+            let mut server = mockito::Server::new();
+            let nav_mock = server.mock("GET", "/my/ships/ALI-1/nav").expect(0).create();
+            let dock_mock = server
+                .mock("POST", "/my/ships/ALI-1/dock")
+                .expect(0)
+                .create();
+            let sell_mock = mock_sell(&mut server);
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship", "cargo", "sell", "-s", "ALI-1", "-g", "iron-ore", "-u", "10",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success();
+
+            nav_mock.assert();
+            dock_mock.assert();
+            sell_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn can_be_switched_off_per_command() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            let mut server = mockito::Server::new();
+            let nav_mock = server.mock("GET", "/my/ships/ALI-1/nav").expect(0).create();
+            let sell_mock = mock_sell(&mut server);
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship",
+                    "cargo",
+                    "sell",
+                    "-s",
+                    "ALI-1",
+                    "-g",
+                    "iron-ore",
+                    "-u",
+                    "10",
+                    "--auto-state=false",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success();
+
+            nav_mock.assert();
+            sell_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn can_be_switched_on_per_command_over_settings() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            Command::cargo_bin(PRG)?
+                .args(["config", "set", "--auto-state=false"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .success();
+
+            let mut server = mockito::Server::new();
+            let nav_mock = server
+                .mock("GET", "/my/ships/ALI-1/nav")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {NAV}}}"#))
+                .create();
+            let dock_mock = server
+                .mock("POST", "/my/ships/ALI-1/dock")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"nav": {}}}}}"#,
+                    NAV.replace("IN_ORBIT", "DOCKED")
+                ))
+                .create();
+            let sell_mock = mock_sell(&mut server);
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship",
+                    "cargo",
+                    "sell",
+                    "-s",
+                    "ALI-1",
+                    "-g",
+                    "iron-ore",
+                    "-u",
+                    "10",
+                    "--auto-state",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stderr(predicate::str::contains("auto-state: docked ALI-1"));
+
+            nav_mock.assert();
+            dock_mock.assert();
+            sell_mock.assert();
+
+            Ok(())
+        }
+    }
+
+    mod test_mine_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use predicates::prelude::predicate;
+
+        use super::{logged_in_user_dir, ship_json};
+
+        /// A ship in orbit at the X1-DF55-69207D asteroid field
+        const NAV: &str = r#"{
+            "systemSymbol": "X1-DF55",
+            "waypointSymbol": "X1-DF55-69207D",
+            "route": {
+                "destination": {"symbol": "X1-DF55-69207D", "type": "ASTEROID_FIELD", "systemSymbol": "X1-DF55", "x": 10, "y": 20},
+                "origin": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                "departureTime": "2023-08-01T10:00:00.000Z",
+                "arrival": "2023-08-01T10:00:30.000Z"
+            },
+            "status": "IN_ORBIT",
+            "flightMode": "CRUISE"
+        }"#;
+        const EMPTY_CARGO: &str = r#"{"capacity": 60, "units": 0, "inventory": []}"#;
+
+        #[test]
+        fn mines_until_full_then_sells_all_but_kept_goods() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let cooldown = r#"{"shipSymbol": "ALI-1", "totalSeconds": 70, "remainingSeconds": 0, "expiration": "2023-08-01T10:01:10.000Z"}"#;
+            let cargo = r#"{"capacity": 60, "units": 60, "inventory": [
+                {"symbol": "IRON_ORE", "name": "Iron Ore", "description": "Iron ore", "units": 50},
+                {"symbol": "ICE_WATER", "name": "Ice Water", "description": "Ice water", "units": 10}
+            ]}"#;
+            let agent = r#"{"symbol": "Fake_Agent", "headquarters": "X1-DF55-20250Z", "credits": 600, "startingFaction": "COSMIC"}"#;
+            let transaction = r#"{"waypointSymbol": "X1-DF55-20250Z", "shipSymbol": "ALI-1", "tradeSymbol": "IRON_ORE", "type": "SELL", "units": 50, "pricePerUnit": 10, "totalPrice": 500, "timestamp": "2023-08-01T10:05:00.000Z"}"#;
+
+            let mut server = mockito::Server::new();
+            server
+                .mock("GET", "/my/ships/ALI-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {}}}"#, ship_json(NAV, 1200)))
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/nav")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {NAV}}}"#))
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/cooldown")
+                .with_status(204)
+                .create();
+            let extract_mock = server
+                .mock("POST", "/my/ships/ALI-1/extract")
+                .with_status(201)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"cooldown": {cooldown}, "extraction": {{"shipSymbol": "ALI-1", "yield": {{"symbol": "IRON_ORE", "units": 8}}}}, "cargo": {cargo}}}}}"#
+                ))
+                .create();
+            let navigate_mock = server
+                .mock("POST", "/my/ships/ALI-1/navigate")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"fuel": {{"current": 1190, "capacity": 1200}}, "nav": {}}}}}"#,
+                    NAV.replace("IN_ORBIT", "IN_TRANSIT")
+                ))
+                .create();
+            let dock_mock = server
+                .mock("POST", "/my/ships/ALI-1/dock")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"nav": {}}}}}"#,
+                    NAV.replace("IN_ORBIT", "DOCKED")
+                ))
+                .create();
+            server
+                .mock("GET", "/systems/X1-DF55/waypoints/X1-DF55-20250Z/market")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {}}}"#,
+                    include_str!("fixtures/market.json")
+                ))
+                .create();
+            // the hold starts out empty, and is full once back at the market
+            server
+                .mock("GET", "/my/ships/ALI-1/cargo")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {EMPTY_CARGO}}}"#))
+                .expect(1)
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/cargo")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {cargo}}}"#))
+                .create();
+            let sell_mock = server
+                .mock("POST", "/my/ships/ALI-1/sell")
+                .match_body(mockito::Matcher::PartialJsonString(
+                    r#"{"symbol": "IRON_ORE", "units": 50}"#.to_string(),
+                ))
+                .with_status(201)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"agent": {agent}, "cargo": {cargo}, "transaction": {transaction}}}}}"#
+                ))
+                .expect(1)
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship",
+                    "mine",
+                    "-s",
+                    "ALI-1",
+                    "--at",
+                    "X1-DF55-69207D",
+                    "--market",
+                    "X1-DF55-20250Z",
+                    "--keep",
+                    "ice-water",
+                    "--cycles",
+                    "1",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(
+                    "extracted 8 IRON_ORE (cargo 60/60)",
+                ))
+                .stdout(predicate::str::contains(
+                    "cycle 1: 1 extractions (8 IRON_ORE), income 500, fuel 0",
+                ));
+
+            extract_mock.assert();
+            navigate_mock.assert();
+            dock_mock.assert();
+            sell_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn stops_on_errors_other_than_a_spent_survey() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let mut ship: serde_json::Value = serde_json::from_str(&ship_json(NAV, 1200))?;
+            ship["mounts"]
+                .as_array_mut()
+                .unwrap()
+                .push(serde_json::json!({
+                    "symbol": "MOUNT_SURVEYOR_I",
+                    "name": "Surveyor I",
+                    "strength": 1,
+                    "requirements": {}
+                }));
+            let cooldown = r#"{"shipSymbol": "ALI-1", "totalSeconds": 0, "remainingSeconds": 0}"#;
+            let survey = r#"{"signature": "X1-DF55-69207D-1A2B3C", "symbol": "X1-DF55-69207D",
+                "deposits": [{"symbol": "IRON_ORE"}], "expiration": "2099-01-01T00:00:00.000Z",
+                "size": "LARGE"}"#;
+
+            let mut server = mockito::Server::new();
+            server
+                .mock("GET", "/my/ships/ALI-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {ship}}}"#))
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/nav")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {NAV}}}"#))
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/cooldown")
+                .with_status(204)
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/cargo")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {EMPTY_CARGO}}}"#))
+                .create();
+            let survey_mock = server
+                .mock("POST", "/my/ships/ALI-1/survey")
+                .with_status(201)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"cooldown": {cooldown}, "surveys": [{survey}]}}}}"#
+                ))
+                .expect(1)
+                .create();
+            let extract_mock = server
+                .mock("POST", "/my/ships/ALI-1/extract")
+                .with_status(409)
+                .with_header("content-type", "application/json")
+                .with_body(
+                    r#"{"error": {"message": "Ship action is still on cooldown", "code": 4000}}"#,
+                )
+                .expect(1)
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "ship",
+                    "mine",
+                    "-s",
+                    "ALI-1",
+                    "--at",
+                    "X1-DF55-69207D",
+                    "--market",
+                    "X1-DF55-20250Z",
+                    "--cycles",
+                    "1",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .timeout(std::time::Duration::from_secs(30))
+                .assert()
+                .code(4)
+                .stderr(predicate::str::contains("still on cooldown"));
+
+            survey_mock.assert();
+            extract_mock.assert();
+
+            Ok(())
+        }
+    }
+
+    mod test_contract_run_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use assert_fs::prelude::{FileWriteStr, PathChild};
+        use predicates::prelude::predicate;
+
+        use super::logged_in_user_dir;
+
+        const NAV: &str = r#"{
+            "systemSymbol": "X1-DF55",
+            "waypointSymbol": "X1-DF55-20250Z",
+            "route": {
+                "destination": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                "origin": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                "departureTime": "2023-08-01T10:00:00.000Z",
+                "arrival": "2023-08-01T10:00:00.000Z"
+            },
+            "status": "DOCKED",
+            "flightMode": "CRUISE"
+        }"#;
+
+        fn contract_json(accepted: bool, units_fulfilled: i32) -> String {
+            format!(
+                r#"{{
+                    "id": "CONTRACT-1",
+                    "factionSymbol": "COSMIC",
+                    "type": "PROCUREMENT",
+                    "terms": {{
+                        "deadline": "2030-01-01T00:00:00.000Z",
+                        "payment": {{"onAccepted": 1000, "onFulfilled": 5000}},
+                        "deliver": [{{"tradeSymbol": "IRON_ORE", "destinationSymbol": "X1-DF55-20250Z", "unitsRequired": 10, "unitsFulfilled": {units_fulfilled}}}]
+                    }},
+                    "accepted": {accepted},
+                    "fulfilled": false,
+                    "expiration": "2030-01-01T00:00:00.000Z",
+                    "deadlineToAccept": "2030-01-01T00:00:00.000Z"
+                }}"#
+            )
+        }
+
+        #[test]
+        fn resumes_a_saved_run_and_fulfills_the_contract() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let runs_file = current_user_dir.child("contract_runs.json");
+            runs_file.write_str(
+                r#"[{"contractId": "CONTRACT-1", "shipSymbol": "ALI-1", "buyAt": null, "mineAt": null}]"#,
+            )?;
+            let cargo = r#"{"capacity": 60, "units": 10, "inventory": [
+                {"symbol": "IRON_ORE", "name": "Iron Ore", "description": "Iron ore", "units": 10}
+            ]}"#;
+            let agent = r#"{"symbol": "Fake_Agent", "headquarters": "X1-DF55-20250Z", "credits": 5100, "startingFaction": "COSMIC"}"#;
+
+            let mut server = mockito::Server::new();
+            server
+                .mock("GET", "/my/contracts/CONTRACT-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {}}}"#, contract_json(true, 0)))
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/cargo")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {cargo}}}"#))
+                .create();
+            server
+                .mock("GET", "/my/ships/ALI-1/nav")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {NAV}}}"#))
+                .create();
+            let deliver_mock = server
+                .mock("POST", "/my/contracts/CONTRACT-1/deliver")
+                .match_body(mockito::Matcher::PartialJsonString(
+                    r#"{"shipSymbol": "ALI-1", "tradeSymbol": "IRON_ORE", "units": 10}"#
+                        .to_string(),
+                ))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"cargo": {{"capacity": 60, "units": 0, "inventory": []}}, "contract": {}}}}}"#,
+                    contract_json(true, 10)
+                ))
+                .create();
+            let fulfill_mock = server
+                .mock("POST", "/my/contracts/CONTRACT-1/fulfill")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"agent": {agent}, "contract": {}}}}}"#,
+                    contract_json(true, 10)
+                        .replace(r#""fulfilled": false"#, r#""fulfilled": true"#)
+                ))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["contract", "run", "-c", "CONTRACT-1"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stderr(predicate::str::contains(
+                    "resuming contract run CONTRACT-1 with ALI-1",
+                ))
+                .stdout(predicate::str::contains(
+                    "IRON_ORE -> X1-DF55-20250Z: 10/10 delivered",
+                ))
+                .stdout(predicate::str::contains("fulfilled contract CONTRACT-1"));
+
+            deliver_mock.assert();
+            fulfill_mock.assert();
+            assert_eq!(std::fs::read_to_string(runs_file.path())?.trim(), "[]");
+
+            Ok(())
+        }
+
+        #[test]
+        fn refuses_a_contract_that_has_not_been_accepted() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            let mut server = mockito::Server::new();
+            server
+                .mock("GET", "/my/contracts/CONTRACT-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {}}}"#, contract_json(false, 0)))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["contract", "run", "-c", "CONTRACT-1", "-s", "ALI-1"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .code(2)
+                .stderr(predicate::str::contains("has not been accepted"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn needs_a_ship_without_a_saved_run() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            Command::cargo_bin(PRG)?
+                .args(["contract", "run", "-c", "CONTRACT-1"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", "http://127.0.0.1:1")
+                .assert()
+                .code(2)
+                .stderr(predicate::str::contains("--ship-symbol is required"));
+
+            Ok(())
+        }
+    }
+
+    mod test_ledger_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use predicates::prelude::predicate;
+
+        use super::logged_in_user_dir;
+
+        #[test]
+        fn records_sales_and_reports_them() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let agent = r#"{"symbol": "Fake_Agent", "headquarters": "X1-DF55-20250Z", "credits": 190, "startingFaction": "COSMIC"}"#;
+            let cargo = r#"{"capacity": 30, "units": 0, "inventory": []}"#;
+            let transaction = r#"{"waypointSymbol": "X1-DF55-20250Z", "shipSymbol": "ALI-1", "tradeSymbol": "IRON_ORE", "type": "SELL", "units": 10, "pricePerUnit": 9, "totalPrice": 90, "timestamp": "2023-08-01T10:01:00.000Z"}"#;
+
+            let mut server = mockito::Server::new();
+            server
+                .mock("POST", "/my/ships/ALI-1/sell")
+                .with_status(201)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"agent": {agent}, "cargo": {cargo}, "transaction": {transaction}}}}}"#
+                ))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "--auto-state=false",
+                    "ship",
+                    "cargo",
+                    "sell",
+                    "-s",
+                    "ALI-1",
+                    "-g",
+                    "iron-ore",
+                    "-u",
+                    "10",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success();
+
+            Command::cargo_bin(PRG)?
+                .args(["ledger", "report", "--since", "2023-08-01"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .success()
+                .stdout(predicate::str::is_match(r"ALI-1 +income +90 .* net +90")?)
+                .stdout(predicate::str::is_match(r"IRON_ORE +income +90")?);
+
+            Command::cargo_bin(PRG)?
+                .args(["ledger", "export", "--csv"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .success()
+                .stdout(predicate::str::starts_with(
+                    "timestamp,kind,shipSymbol,waypointSymbol,item,units,pricePerUnit,totalPrice\n",
+                ))
+                .stdout(predicate::str::contains(
+                    "Sell,ALI-1,X1-DF55-20250Z,IRON_ORE,10,9,90",
+                ));
+
+            Ok(())
+        }
+    }
+
+    mod test_run_script_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use assert_fs::prelude::{FileWriteStr, PathChild};
+        use predicates::prelude::predicate;
+
+        use super::logged_in_user_dir;
+
+        const NAV: &str = r#"{
+            "systemSymbol": "X1-DF55",
+            "waypointSymbol": "X1-DF55-20250Z",
+            "route": {
+                "destination": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                "origin": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                "departureTime": "2023-08-01T10:00:00.000Z",
+                "arrival": "2023-08-01T10:00:00.000Z"
+            },
+            "status": "IN_ORBIT",
+            "flightMode": "CRUISE"
+        }"#;
+
+        #[test]
+        fn runs_commands_with_variables_and_continues_on_error() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let script = current_user_dir.child("script.txt");
+            script.write_str(
+                "# park the ship\n\
+                 on-error continue\n\
+                 ship navigate status -s $SHIP\n\
+                 ship no-such-command\n\
+                 wait-until-arrival ${SHIP}\n\
+                 ship dock -s $SHIP\n",
+            )?;
+
+            let mut server = mockito::Server::new();
+            let nav_mock = server
+                .mock("GET", "/my/ships/ALI-1/nav")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(r#"{{"data": {NAV}}}"#))
+                .expect(2)
+                .create();
+            let dock_mock = server
+                .mock("POST", "/my/ships/ALI-1/dock")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {{"nav": {}}}}}"#,
+                    NAV.replace("IN_ORBIT", "DOCKED")
+                ))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args([
+                    "run-script",
+                    script.to_str().unwrap(),
+                    "--var",
+                    "SHIP=ALI-1",
+                ])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .code(2)
+                .stderr(predicate::str::contains("[6] ship dock -s $SHIP"))
+                .stderr(predicate::str::contains("line 4: error:"))
+                .stderr(predicate::str::contains(
+                    "script finished with 1 failed lines",
+                ));
+
+            nav_mock.assert();
+            dock_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn stops_at_the_first_error_by_default() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let script = current_user_dir.child("script.txt");
+            script.write_str("ship dock -s $UNSET\nship dock -s ALI-1\n")?;
+
+            let mut server = mockito::Server::new();
+            let dock_mock = server
+                .mock("POST", "/my/ships/ALI-1/dock")
+                .expect(0)
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["run-script", script.to_str().unwrap()])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .code(2)
+                .stderr(predicate::str::contains("undefined variable `UNSET`"));
+
+            dock_mock.assert();
+
+            Ok(())
+        }
+    }
+
+    mod test_script_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use assert_fs::prelude::{FileWriteStr, PathChild};
+        use predicates::prelude::predicate;
+
+        use super::logged_in_user_dir;
+
+        #[test]
+        fn runs_a_strategy_with_ship_data() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let script = current_user_dir.child("strategy.rhai");
+            script.write_str(
+                r#"
+                let ship = ship("ALI-1");
+                let free = ship.cargo.capacity - ship.cargo.units;
+                print(`${ship.symbol} fuel ${ship.fuel.current} free ${free}`);
+                if ship.nav.status == "DOCKED" {
+                    orbit(ship.symbol);
+                }
+                sleep(0);
+                "#,
+            )?;
+
+            let mut server = mockito::Server::new();
+            let ship_mock = server
+                .mock("GET", "/my/ships/ALI-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(format!(
+                    r#"{{"data": {}}}"#,
+                    include_str!("fixtures/ship.json")
+                ))
+                .create();
+            let orbit_mock = server
+                .mock("POST", "/my/ships/ALI-1/orbit")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"data": {"nav": {
+                    "systemSymbol": "X1-DF55",
+                    "waypointSymbol": "X1-DF55-20250Z",
+                    "route": {
+                        "destination": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                        "origin": {"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 0, "y": 0},
+                        "departureTime": "2023-08-01T10:00:00.000Z",
+                        "arrival": "2023-08-01T10:00:00.000Z"
+                    },
+                    "status": "IN_ORBIT",
+                    "flightMode": "CRUISE"
+                }}}"#)
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["script", "run", script.to_str().unwrap()])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("ALI-1 fuel 1200 free 60"));
+
+            ship_mock.assert();
+            orbit_mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn fails_on_script_errors() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+            let script = current_user_dir.child("strategy.rhai");
+            script.write_str(r#"sell("ALI-1", "NOT_A_GOOD", 1);"#)?;
+
+            let server = mockito::Server::new();
+
+            Command::cargo_bin(PRG)?
+                .args(["script", "run", script.to_str().unwrap()])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .code(1)
+                .stderr(predicate::str::contains("unknown trade good `NOT_A_GOOD`"));
+
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn stops_a_busy_loop_on_ctrl_c() -> TestResult {
+            use std::{
+                io::{BufRead, BufReader, Read},
+                process::{Command as StdCommand, Stdio},
+                thread,
+                time::Duration,
+            };
+
+            let current_user_dir = logged_in_user_dir();
+            let script = current_user_dir.child("strategy.rhai");
+            script.write_str(r#"print("looping"); loop {}"#)?;
+
+            let mut run = StdCommand::new(assert_cmd::cargo::cargo_bin(PRG))
+                .args(["script", "run", script.to_str().unwrap()])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            let mut line = String::new();
+            BufReader::new(run.stdout.take().unwrap()).read_line(&mut line)?;
+            assert!(line.contains("looping"));
+            // give the Ctrl-C handler time to be hooked
+            thread::sleep(Duration::from_millis(500));
+
+            StdCommand::new("kill")
+                .args(["-INT", &run.id().to_string()])
+                .status()?;
+            let status = run.wait()?;
+            let mut stderr = String::new();
+            run.stderr.take().unwrap().read_to_string(&mut stderr)?;
+
+            assert_eq!(status.code(), Some(1));
+            assert!(stderr.contains("stopped"));
+
+            Ok(())
+        }
+    }
+
+    mod test_daemon_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use assert_fs::prelude::{FileWriteStr, PathChild};
+        use predicates::prelude::predicate;
+
+        use super::logged_in_user_dir;
+
+        #[test]
+        fn needs_assigned_ships() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            Command::cargo_bin(PRG)?
+                .args(["daemon", "run"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .assert()
+                .code(2)
+                .stderr(predicate::str::contains("no ships assigned"));
+
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn stops_cleanly_on_sigterm() -> TestResult {
+            use std::{
+                io::{BufRead, BufReader, Read},
+                process::{Command, Stdio},
+            };
+
+            let current_user_dir = logged_in_user_dir();
+            current_user_dir
+                .child("daemon.json")
+                .write_str(r#"{"ships": {"ALI-1": {"behaviour": "idle"}}}"#)?;
+
+            let mut daemon = Command::new(assert_cmd::cargo::cargo_bin(PRG))
+                .args(["daemon", "run", "--control", "127.0.0.1:0"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            let mut stdout = BufReader::new(daemon.stdout.take().unwrap());
+            let mut line = String::new();
+            while !line.contains("ALI-1: idle") {
+                line.clear();
+                assert_ne!(stdout.read_line(&mut line)?, 0, "daemon exited early");
+            }
+
+            Command::new("kill")
+                .args(["-TERM", &daemon.id().to_string()])
+                .status()?;
+            let status = daemon.wait()?;
+            let mut rest = String::new();
+            stdout.read_to_string(&mut rest)?;
+            let mut stderr = String::new();
+            daemon.stderr.take().unwrap().read_to_string(&mut stderr)?;
+
+            assert!(status.success());
+            assert!(rest.contains("ALI-1: stopped"));
+            assert!(stderr.contains("daemon stopped"));
+
+            Ok(())
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn is_steered_through_the_control_api() -> TestResult {
+            use std::{
+                io::{BufRead, BufReader},
+                process::{Command as StdCommand, Stdio},
+            };
+
+            let current_user_dir = logged_in_user_dir();
+            let config = current_user_dir.child("daemon.json");
+            config.write_str(
+                r#"{"ships": {"ALI-1": {"behaviour": "idle"}, "ALI-2": {"behaviour": "idle"}}}"#,
+            )?;
+
+            let mut daemon = StdCommand::new(assert_cmd::cargo::cargo_bin(PRG))
+                .args(["daemon", "run", "--control", "127.0.0.1:0"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            let mut stderr = BufReader::new(daemon.stderr.take().unwrap());
+            let mut line = String::new();
+            let addr = loop {
+                line.clear();
+                assert_ne!(stderr.read_line(&mut line)?, 0, "daemon exited early");
+                if let Some(addr) = line.trim().strip_prefix("control API listening on http://") {
+                    break addr.to_owned();
+                }
+            };
+            let daemon_command = |args: &[&str]| {
+                let mut command = Command::cargo_bin(PRG).unwrap();
+                command
+                    .arg("daemon")
+                    .args(args)
+                    .args(["--control", &addr])
+                    .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap());
+                command
+            };
+
+            daemon_command(&["pause", "--ship", "ALI-1"])
+                .assert()
+                .success();
+            daemon_command(&[
+                "assign",
+                "--ship",
+                "ALI-1",
+                "--behaviour",
+                "scout",
+                "-w",
+                "X1-DF55-20250Z",
+            ])
+            .assert()
+            .success();
+            daemon_command(&["assign", "--ship", "ALI-9", "--behaviour", "trade"])
+                .assert()
+                .code(2)
+                .stderr(predicate::str::contains(
+                    "ALI-9 isn't one of the daemon's ships",
+                ));
+            daemon_command(&["assign", "--ship", "ALI-2", "--behaviour", "mine"])
+                .assert()
+                .code(2);
+
+            // the driver notices the pause asynchronously
+            let mut status = String::new();
+            for _ in 0..50 {
+                let output = daemon_command(&["status"]).output()?;
+                status = String::from_utf8(output.stdout)?;
+                if status.contains("paused") && status.contains("running") {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            assert!(status.contains("ALI-1          paused    scout X1-DF55-20250Z"));
+            assert!(status.contains("ALI-2          running   idle"));
+            assert!(std::fs::read_to_string(config.path())?.contains(r#""behaviour": "scout""#));
+
+            daemon_command(&["events"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("ALI-1: paused"));
+
+            StdCommand::new("kill")
+                .args(["-TERM", &daemon.id().to_string()])
+                .status()?;
+            assert!(daemon.wait()?.success());
+
+            Ok(())
+        }
+    }
+
+    mod test_completions_command {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use assert_cmd::Command;
+        use assert_fs::prelude::{FileWriteStr, PathChild};
+        use predicates::prelude::predicate;
+
+        fn cached_symbols_dir() -> assert_fs::TempDir {
+            let current_user_dir = assert_fs::TempDir::new().unwrap();
+            current_user_dir
+                .child("symbols.json")
+                .write_str(
+                    r#"{
+                        "ships": ["ALI-1", "ALI-2"],
+                        "waypoints": ["X1-DF55-20250Z", "X1-DF55-69207D"],
+                        "contracts": ["clkxq7cmv0001s60cwxzxy1hs"]
+                    }"#,
+                )
+                .unwrap();
+            current_user_dir
+        }
+
+        #[test]
+        fn prints_scripts_with_the_symbol_hook() -> TestResult {
+            for (shell, hook) in [
+                ("bash", "complete -F _space_traders_rust_symbols"),
+                ("zsh", "compdef _space_traders_rust_symbols"),
+                (
+                    "fish",
+                    "complete -c space_traders_rust -n '__space_traders_rust_symbols",
+                ),
+            ] {
+                Command::cargo_bin(PRG)?
+                    .args(["completions", shell])
+                    .assert()
+                    .success()
+                    .stdout(predicate::str::contains("ship-symbol"))
+                    .stdout(predicate::str::contains(hook));
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn completes_symbol_options_from_the_cache() -> TestResult {
+            let current_user_dir = cached_symbols_dir();
+            let complete = |words: &[&str]| {
+                let mut cmd = Command::cargo_bin(PRG).unwrap();
+                cmd.env("TEST_CURRENT_USER_DIR", current_user_dir.path())
+                    .args(["completions", "--", PRG])
+                    .args(words);
+                cmd
+            };
+
+            complete(&["ship", "refuel", "-s", "AL"])
+                .assert()
+                .success()
+                .stdout("ALI-1\nALI-2\n");
+            complete(&[
+                "ship",
+                "navigate",
+                "waypoint",
+                "-s",
+                "ALI-1",
+                "--waypoint-symbol",
+                "",
+            ])
+            .assert()
+            .success()
+            .stdout("X1-DF55-20250Z\nX1-DF55-69207D\n");
+            complete(&["contract", "evaluate", "--contract", ""])
+                .assert()
+                .success()
+                .stdout("clkxq7cmv0001s60cwxzxy1hs\n");
+            Ok(())
+        }
+
+        #[test]
+        fn falls_back_for_other_options() -> TestResult {
+            let current_user_dir = cached_symbols_dir();
+
+            // -s is the ship type here, which clap completes statically
+            Command::cargo_bin(PRG)?
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.path())
+                .args(["completions", "--", PRG, "ship", "purchase", "-s", ""])
+                .assert()
+                .code(2)
+                .stdout("");
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod contract_tests {
+
+    mod test_evaluate_contract {
+        use std::collections::HashMap;
+
+        use chrono::{Duration, Utc};
+        use space_traders_rust::{
+            contract::evaluate_contract,
+            domain::{Contract, ShipNavFlightMode, Supply},
+            estimator::fuel_cost,
+            market_history::PriceRecord,
+            trade::TradeShip,
+        };
+
+        fn price(waypoint: &str, good: &str, purchase: u32) -> PriceRecord {
+            PriceRecord {
+                timestamp: Utc::now(),
+                waypoint_symbol: waypoint.to_string(),
+                trade_symbol: good.to_string(),
+                supply: Supply::MODERATE,
+                purchase_price: purchase,
+                sell_price: purchase - 2,
+                trade_volume: 20,
+            }
+        }
+
+        /// 100 IRON to DEST, paying 1000 on accept and 9000 on fulfil
+        fn contract(deadline_in: Duration) -> Contract {
+            serde_json::from_value(serde_json::json!({
+                "id": "CONTRACT",
+                "factionSymbol": "COSMIC",
+                "type": "PROCUREMENT",
+                "terms": {
+                    "deadline": Utc::now() + deadline_in,
+                    "payment": { "onAccepted": 1000, "onFulfilled": 9000 },
+                    "deliver": [{
+                        "tradeSymbol": "IRON",
+                        "destinationSymbol": "DEST",
+                        "unitsRequired": 100,
+                        "unitsFulfilled": 0
+                    }]
+                },
+                "accepted": true,
+                "fulfilled": false,
+                "expiration": Utc::now() + deadline_in,
+                "deadlineToAccept": Utc::now() + deadline_in
+            }))
+            .unwrap()
+        }
+
+        fn coordinates() -> HashMap<String, (i32, i32)> {
+            HashMap::from([
+                ("A".to_string(), (0, 0)),
+                ("DEST".to_string(), (100, 0)),
+                ("FAR".to_string(), (2000, 0)),
+            ])
+        }
+
+        fn ship(location: (i32, i32), cargo_capacity: i32) -> TradeShip {
+            TradeShip {
+                location,
+                cargo_capacity,
+                speed: 30,
+            }
+        }
+
+        #[test]
+        fn estimates_profit_trips_and_fuel() {
+            let prices = vec![price("A", "IRON", 10), price("A", "FUEL", 50)];
+            let ships = vec![("ALI-1".to_string(), ship((0, 0), 40))];
+
+            let evaluation = evaluate_contract(
+                &contract(Duration::days(7)),
+                &prices,
+                &coordinates(),
+                &ships,
+            );
+
+            // A -> DEST, then back and forth twice more
+            let fuel = fuel_cost(ShipNavFlightMode::CRUISE, 100.0) * 5;
+            let fuel_bill = (fuel as i64 + 99) / 100 * 50;
+            assert_eq!(evaluation.trips(), 3);
+            assert_eq!(evaluation.fuel, fuel);
+            assert_eq!(evaluation.purchase_cost(), Some(1000));
+            assert_eq!(evaluation.net_profit(), Some(10000 - 1000 - fuel_bill));
+            assert!(evaluation.meets_deadline());
+        }
+
+        #[test]
+        fn picks_the_ship_that_finishes_first() {
+            let prices = vec![price("A", "IRON", 10)];
+            let ships = vec![
+                ("FAR-AWAY".to_string(), ship((2000, 0), 100)),
+                ("SMALL".to_string(), ship((0, 0), 10)),
+                ("NEARBY".to_string(), ship((0, 0), 50)),
+                ("PROBE".to_string(), ship((0, 0), 0)),
+            ];
+
+            let evaluation = evaluate_contract(
+                &contract(Duration::days(7)),
+                &prices,
+                &coordinates(),
+                &ships,
+            );
+
+            assert_eq!(evaluation.ship_symbol.as_deref(), Some("NEARBY"));
+            assert_eq!(evaluation.trips(), 2);
+        }
+
+        #[test]
+        fn leaves_profit_unknown_without_recorded_prices() {
+            let ships = vec![("ALI-1".to_string(), ship((0, 0), 40))];
+
+            let evaluation =
+                evaluate_contract(&contract(Duration::days(7)), &[], &coordinates(), &ships);
+
+            assert_eq!(evaluation.deliveries[0].buy_at, None);
+            assert_eq!(evaluation.net_profit(), None);
+        }
+
+        #[test]
+        fn shows_purchase_costs_past_u32() {
+            let prices = vec![price("A", "IRON", 50_000_000)];
+            let ships = vec![("ALI-1".to_string(), ship((0, 0), 40))];
+
+            let evaluation = evaluate_contract(
+                &contract(Duration::days(7)),
+                &prices,
+                &coordinates(),
+                &ships,
+            );
+
+            assert_eq!(evaluation.purchase_cost(), Some(5_000_000_000));
+            assert!(evaluation
+                .to_string()
+                .contains("buy at A for 50000000 (5000000000)"));
+        }
+
+        #[test]
+        fn flags_deadlines_the_ships_cannot_make() {
+            let prices = vec![price("FAR", "IRON", 10)];
+            let ships = vec![("ALI-1".to_string(), ship((0, 0), 40))];
+
+            let evaluation = evaluate_contract(
+                &contract(Duration::minutes(5)),
+                &prices,
+                &coordinates(),
+                &ships,
+            );
+
+            assert!(!evaluation.meets_deadline());
+            assert!(evaluation.to_string().contains("not realistic"));
+        }
+
+        #[test]
+        fn is_unrealistic_without_cargo_ships() {
+            let prices = vec![price("A", "IRON", 10)];
+            let ships = vec![("PROBE".to_string(), ship((0, 0), 0))];
+
+            let evaluation = evaluate_contract(
+                &contract(Duration::days(7)),
+                &prices,
+                &coordinates(),
+                &ships,
+            );
+
+            assert_eq!(evaluation.ship_symbol, None);
+            assert!(!evaluation.meets_deadline());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod daemon_tests {
+
+    mod test_assignments {
+        use space_traders_rust::{
+            daemon::{Assignments, Behaviour},
+            domain::TradeSymbol,
+        };
+
+        #[test]
+        fn reads_behaviours_from_the_config_file() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            std::fs::write(
+                dir.path().join("daemon.json"),
+                r#"{"ships": {
+                    "ALI-1": {"behaviour": "mine", "asteroidField": "X1-DF55-17335A", "market": "X1-DF55-20250Z", "keep": ["ICE_WATER"]},
+                    "ALI-2": {"behaviour": "trade"},
+                    "ALI-3": {"behaviour": "haul", "contractId": "clkx", "buyAt": "X1-DF55-20250Z"},
+                    "ALI-4": {"behaviour": "scout"},
+                    "ALI-5": {"behaviour": "idle"}
+                }}"#,
+            )
+            .unwrap();
+
+            let ships = Assignments::open(dir.path()).load().unwrap().ships;
+
+            assert_eq!(
+                ships["ALI-1"],
+                Behaviour::Mine {
+                    asteroid_field: String::from("X1-DF55-17335A"),
+                    market: String::from("X1-DF55-20250Z"),
+                    keep: vec![TradeSymbol::ICE_WATER],
+                }
+            );
+            assert_eq!(ships["ALI-2"], Behaviour::Trade);
+            assert_eq!(
+                ships["ALI-3"].to_string(),
+                "haul contract clkx, buying at X1-DF55-20250Z"
+            );
+            assert_eq!(ships["ALI-4"], Behaviour::Scout { waypoints: vec![] });
+            assert_eq!(ships["ALI-5"], Behaviour::Idle);
+        }
+
+        #[test]
+        fn assigning_keeps_the_other_ships() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let assignments = Assignments::open(dir.path());
+
+            assignments.assign("ALI-1", Behaviour::Trade).unwrap();
+            assignments.assign("ALI-2", Behaviour::Trade).unwrap();
+            assignments.assign("ALI-1", Behaviour::Idle).unwrap();
+
+            let ships = assignments.load().unwrap().ships;
+            assert_eq!(ships.len(), 2);
+            assert_eq!(ships["ALI-1"], Behaviour::Idle);
+            assert_eq!(ships["ALI-2"], Behaviour::Trade);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod dashboard_tests {
+
+    mod test_bar {
+        use space_traders_rust::dashboard::bar;
+
+        #[test]
+        fn fills_in_proportion() {
+            assert_eq!(bar(30, 60, 10), "[#####-----]");
+            assert_eq!(bar(60, 60, 4), "[####]");
+        }
+
+        #[test]
+        fn stays_empty_without_capacity() {
+            assert_eq!(bar(0, 0, 4), "[----]");
+        }
+
+        #[test]
+        fn clamps_overfull_values() {
+            assert_eq!(bar(90, 60, 4), "[####]");
+        }
+    }
+
+    mod test_draw {
+        use ratatui::{backend::TestBackend, Terminal};
+        use space_traders_rust::{
+            dashboard::{render, DashboardState},
+            domain::{Agent, Ship},
+            ledger::{LedgerEntry, LedgerKind},
+        };
+
+        fn screen(state: &mut DashboardState) -> String {
+            let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+            render(&mut terminal, state).unwrap();
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .chunks(buffer.area.width as usize)
+                .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        #[test]
+        fn shows_agent_and_ships() {
+            let ship: Ship = serde_json::from_str(include_str!("fixtures/ship.json")).unwrap();
+            let mut state = DashboardState {
+                agent: Some(Agent {
+                    credits: 12345,
+                    symbol: "Fake_Agent".to_string(),
+                    account_id: None,
+                    ship_count: None,
+                    headquarters: "X1-DF55-20250Z".to_string(),
+                    starting_faction: "COSMIC".to_string(),
+                }),
+                ships: vec![ship],
+                ..Default::default()
+            };
+            state.selected.select(Some(0));
+
+            let screen = screen(&mut state);
+
+            assert!(screen.contains("credits 12345"));
+            assert!(screen.contains("[##########] 1200/1200"));
+            assert!(screen.contains("[----------] 0/60"));
+            assert!(screen.contains("ready"));
+            assert!(screen.contains("no active contracts"));
+        }
+
+        #[test]
+        fn shows_ledger_entries_as_recent_transactions() {
+            let entry = |minute: u32, kind: LedgerKind, item: &str| LedgerEntry {
+                timestamp: format!("2023-06-10T12:{minute:02}:00Z").parse().unwrap(),
+                kind,
+                ship_symbol: "EMBER-1".to_string(),
+                waypoint_symbol: "X1-DF55-20250Z".to_string(),
+                item: item.to_string(),
+                units: 10,
+                price_per_unit: 5,
+                total_price: 50,
+            };
+            let mut state = DashboardState::default();
+            state.seed_log(&[
+                entry(1, LedgerKind::Purchase, "FUEL"),
+                entry(2, LedgerKind::Sell, "IRON_ORE"),
+            ]);
+
+            let screen = screen(&mut state);
+
+            let sold = screen
+                .find("12:02:00 EMBER-1 Sell IRON_ORE x10 = 50")
+                .unwrap();
+            let bought = screen
+                .find("12:01:00 EMBER-1 Purchase FUEL x10 = 50")
+                .unwrap();
+            assert!(sold < bought);
+        }
+    }
+}
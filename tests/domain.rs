@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod domain_tests {
+
+    mod test_timestamps {
+        use chrono::{Duration, TimeZone, Utc};
+        use space_traders_rust::domain::{format_duration, Cooldown, ShipNavRoute};
+
+        fn route_arriving_at(arrival: &str) -> ShipNavRoute {
+            let json = format!(
+                r#"{{
+                    "destination": {{"symbol": "X1-DF55-20250Z", "type": "PLANET", "systemSymbol": "X1-DF55", "x": 10, "y": 20}},
+                    "origin": {{"symbol": "X1-DF55-17335A", "type": "MOON", "systemSymbol": "X1-DF55", "x": 0, "y": 0}},
+                    "departureTime": "2023-08-01T10:00:00.000Z",
+                    "arrival": "{arrival}"
+                }}"#
+            );
+            serde_json::from_str(&json).unwrap()
+        }
+
+        #[test]
+        fn parses_timestamps_as_utc() {
+            let route = route_arriving_at("2023-08-01T10:02:13.000Z");
+            assert_eq!(
+                route.arrival,
+                Utc.with_ymd_and_hms(2023, 8, 1, 10, 2, 13).unwrap()
+            );
+            assert_eq!(route.arrival - route.departure_time, Duration::seconds(133));
+        }
+
+        #[test]
+        fn time_remaining_is_zero_after_arrival() {
+            let route = route_arriving_at("2023-08-01T10:02:13.000Z");
+            assert!(route.has_arrived());
+            assert_eq!(route.time_remaining(), Duration::zero());
+        }
+
+        #[test]
+        fn time_remaining_counts_down_to_arrival() {
+            let arrival = (Utc::now() + Duration::minutes(10)).to_rfc3339();
+            let route = route_arriving_at(&arrival);
+            assert!(!route.has_arrived());
+            assert!(route.time_remaining() > Duration::minutes(9));
+            assert!(route.arrival_description().starts_with("arrives in 9m"));
+        }
+
+        #[test]
+        fn cooldown_without_expiration_is_expired() {
+            let cooldown: Cooldown = serde_json::from_str(
+                r#"{"shipSymbol": "ALI-1", "totalSeconds": 0, "remainingSeconds": 0}"#,
+            )
+            .unwrap();
+            assert!(cooldown.is_expired());
+            assert_eq!(cooldown.time_remaining(), Duration::zero());
+        }
+
+        #[test]
+        fn cooldown_in_the_future_is_not_expired() {
+            let expiration = (Utc::now() + Duration::seconds(70)).to_rfc3339();
+            let cooldown: Cooldown = serde_json::from_str(&format!(
+                r#"{{"shipSymbol": "ALI-1", "totalSeconds": 70, "remainingSeconds": 70, "expiration": "{expiration}"}}"#
+            ))
+            .unwrap();
+            assert!(!cooldown.is_expired());
+            assert!(cooldown.time_remaining() > Duration::seconds(60));
+        }
+
+        #[test]
+        fn formats_durations_compactly() {
+            assert_eq!(format_duration(Duration::seconds(45)), "45s");
+            assert_eq!(format_duration(Duration::seconds(133)), "2m13s");
+            assert_eq!(format_duration(Duration::seconds(3605)), "1h00m05s");
+            assert_eq!(format_duration(Duration::seconds(-5)), "0s");
+        }
+    }
+
+    mod test_unknown_variants {
+        use space_traders_rust::domain::{
+            ShipNavFlightMode, Supply, TradeSymbol, Waypoint, WaypointTraitSymbol, WaypointType,
+        };
+
+        #[test]
+        fn keeps_values_added_on_the_server() {
+            let waypoint: Waypoint = serde_json::from_str(
+                r#"{
+                    "symbol": "X1-DF55-20250Z",
+                    "type": "SPACE_ELEVATOR",
+                    "systemSymbol": "X1-DF55",
+                    "x": 0,
+                    "y": 0,
+                    "orbitals": [],
+                    "faction": {"symbol": "COSMIC"},
+                    "traits": [
+                        {"symbol": "MARKETPLACE", "name": "Marketplace", "description": ""},
+                        {"symbol": "HAUNTED_RUINS", "name": "Haunted Ruins", "description": ""}
+                    ],
+                    "chart": {"submittedBy": "COSMIC", "submittedOn": "2023-07-29T10:00:00Z"}
+                }"#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                waypoint.waypoint_type,
+                WaypointType::Unknown(String::from("SPACE_ELEVATOR"))
+            );
+            assert_eq!(waypoint.traits[0].symbol, WaypointTraitSymbol::MARKETPLACE);
+            assert_eq!(
+                waypoint.traits[1].symbol,
+                WaypointTraitSymbol::Unknown(String::from("HAUNTED_RUINS"))
+            );
+        }
+
+        #[test]
+        fn sends_unknown_values_back_as_they_came() {
+            let supply: Supply = serde_json::from_str(r#""OVERFLOWING""#).unwrap();
+
+            assert_eq!(supply.to_string(), "OVERFLOWING");
+            assert_eq!(serde_json::to_string(&supply).unwrap(), r#""OVERFLOWING""#);
+            assert_eq!(
+                serde_json::to_string(&ShipNavFlightMode::CRUISE).unwrap(),
+                r#""CRUISE""#
+            );
+        }
+
+        #[test]
+        fn parses_unknown_goods_from_cargo() {
+            assert_eq!(
+                TradeSymbol::from_symbol("SPACE_WHALE_OIL"),
+                TradeSymbol::Unknown(String::from("SPACE_WHALE_OIL"))
+            );
+            assert_eq!(TradeSymbol::from_symbol("IRON_ORE"), TradeSymbol::IRON_ORE);
+        }
+
+        #[test]
+        fn still_rejects_values_of_the_wrong_type() {
+            assert!(serde_json::from_str::<WaypointType>("42").is_err());
+        }
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod estimator_tests {
+
+    mod test_hop_estimates {
+        use space_traders_rust::{
+            domain::{ShipNavFlightMode, ShipNavRouteWaypoint, WaypointType},
+            estimator::{estimate_hop, fuel_cost, travel_seconds},
+        };
+
+        #[test]
+        fn fuel_cost_depends_on_flight_mode() {
+            assert_eq!(fuel_cost(ShipNavFlightMode::CRUISE, 49.6), 50);
+            assert_eq!(fuel_cost(ShipNavFlightMode::STEALTH, 49.6), 50);
+            assert_eq!(fuel_cost(ShipNavFlightMode::BURN, 49.6), 100);
+            assert_eq!(fuel_cost(ShipNavFlightMode::DRIFT, 49.6), 1);
+        }
+
+        #[test]
+        fn hops_between_orbitals_are_free() {
+            assert_eq!(fuel_cost(ShipNavFlightMode::BURN, 0.0), 0);
+            assert_eq!(travel_seconds(ShipNavFlightMode::CRUISE, 0.0, 30), 16);
+        }
+
+        #[test]
+        fn faster_engines_travel_faster() {
+            let slow = travel_seconds(ShipNavFlightMode::CRUISE, 100.0, 10);
+            let fast = travel_seconds(ShipNavFlightMode::CRUISE, 100.0, 30);
+            assert_eq!(slow, 265);
+            assert_eq!(fast, 98);
+        }
+
+        #[test]
+        fn estimates_every_flight_mode() {
+            let from = ShipNavRouteWaypoint {
+                symbol: String::from("X1-DF55-20250Z"),
+                waypoint_type: WaypointType::PLANET,
+                system_symbol: String::from("X1-DF55"),
+                x: 0,
+                y: 0,
+            };
+
+            let estimates = estimate_hop(&from, (30, 40), 30);
+
+            assert_eq!(estimates.len(), 4);
+            assert!(estimates.iter().all(|e| e.distance == 50.0));
+            let burn = estimates
+                .iter()
+                .find(|e| e.flight_mode == ShipNavFlightMode::BURN)
+                .unwrap();
+            let drift = estimates
+                .iter()
+                .find(|e| e.flight_mode == ShipNavFlightMode::DRIFT)
+                .unwrap();
+            assert!(burn.travel_seconds < drift.travel_seconds);
+            assert!(burn.fuel_cost > drift.fuel_cost);
+        }
+    }
+}
@@ -0,0 +1,596 @@
+#[cfg(test)]
+mod fake_server_tests {
+    use std::{
+        fs::File,
+        io::{BufWriter, Write},
+    };
+
+    use assert_cmd::Command;
+    use assert_fs::prelude::PathChild;
+    use predicates::prelude::PredicateBooleanExt;
+    use space_traders_rust::{fake_server::FakeServer, UserInfo};
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+    const PRG: &str = "space_traders_rust";
+
+    /// A user directory logged in as the fake world's agent
+    fn logged_in_user_dir(server: &FakeServer) -> assert_fs::TempDir {
+        let current_user_dir = assert_fs::TempDir::new().unwrap();
+        let test_user_info = UserInfo {
+            token: String::from("fake-token-ALI"),
+            agent: server.world().agent.clone(),
+        };
+        let file = File::create(current_user_dir.child("current_user.json")).unwrap();
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &test_user_info).unwrap();
+        writer.flush().unwrap();
+        current_user_dir
+    }
+
+    fn cli(server: &FakeServer, current_user_dir: &assert_fs::TempDir) -> Command {
+        let mut cmd = Command::cargo_bin(PRG).unwrap();
+        cmd.env("TEST_CURRENT_USER_DIR", current_user_dir.path())
+            .env("TEST_API_BASE_URL", server.url());
+        cmd
+    }
+
+    mod test_agent {
+        use super::*;
+
+        #[test]
+        fn registers_and_reports_the_new_agent() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = assert_fs::TempDir::new()?;
+
+            cli(&server, &current_user_dir)
+                .args(["register", "-u", "BOB", "-f", "GALACTIC"])
+                .assert()
+                .success();
+            let saved = std::fs::read_to_string(current_user_dir.child("current_user.json"))?;
+            let user_info: UserInfo = serde_json::from_str(&saved)?;
+            assert_eq!(user_info.token, "fake-token-BOB");
+
+            cli(&server, &current_user_dir)
+                .arg("who-am-i")
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("BOB"))
+                .stdout(predicates::str::contains("GALACTIC"));
+            Ok(())
+        }
+    }
+
+    mod test_ships {
+        use chrono::{Duration, Utc};
+        use space_traders_rust::domain::{Cooldown, Inventory};
+
+        use super::*;
+
+        #[test]
+        fn mines_then_sells_and_refuels_at_home() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "navigate", "waypoint", "-s", "ALI-1"])
+                .args(["-w", "X1-DF55-69207D"])
+                .assert()
+                .success();
+            cli(&server, &current_user_dir)
+                .args(["ship", "extract", "-s", "ALI-1"])
+                .assert()
+                .success();
+            let (mined, fuel_after_trip) = {
+                let world = server.world();
+                let ship = &world.ships[0];
+                assert_eq!(ship.cargo.inventory[0].symbol, "IRON_ORE");
+                (ship.cargo.units, ship.fuel.current)
+            };
+            assert!(mined > 0);
+            assert!(fuel_after_trip < 1200);
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "navigate", "waypoint", "-s", "ALI-1"])
+                .args(["-w", "X1-DF55-20250Z"])
+                .assert()
+                .success();
+            cli(&server, &current_user_dir)
+                .args(["ship", "cargo", "sell", "-s", "ALI-1", "-g", "iron-ore"])
+                .args(["-u", &mined.to_string()])
+                .assert()
+                .success();
+            cli(&server, &current_user_dir)
+                .args(["ship", "refuel", "-s", "ALI-1"])
+                .assert()
+                .success();
+
+            let world = server.world();
+            let ship = &world.ships[0];
+            assert_eq!(ship.cargo.units, 0);
+            assert_eq!(ship.fuel.current, 1200);
+            // the trip back burns as much as the trip out
+            let burned = 2 * (1200 - fuel_after_trip);
+            let fuel_bill = (burned + 99) / 100 * 72;
+            assert_eq!(
+                world.agent.credits,
+                i64::from(100000 + mined * 40 - fuel_bill)
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn sells_the_haul_in_trade_volume_sized_lots() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            for good in server.world().markets[0].trade_goods.iter_mut() {
+                good.trade_volume = 7;
+            }
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "mine", "-s", "ALI-1", "-a", "X1-DF55-69207D"])
+                .args(["-m", "X1-DF55-20250Z", "-n", "1"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("sold 7 IRON_ORE"));
+
+            let world = server.world();
+            let sold = world.markets[0]
+                .transactions
+                .iter()
+                .filter(|t| t.trade_symbol != "FUEL")
+                .count();
+            assert!(sold > 2);
+            assert!(world.ships[0]
+                .cargo
+                .inventory
+                .iter()
+                .all(|item| item.symbol != "IRON_ORE" && item.symbol != "ICE_WATER"));
+            Ok(())
+        }
+
+        fn fill_hold(server: &FakeServer, goods: &[(&str, i32)]) {
+            let mut world = server.world();
+            let cargo = &mut world.ships[0].cargo;
+            for (symbol, units) in goods {
+                cargo.inventory.push(Inventory {
+                    symbol: symbol.to_string(),
+                    name: symbol.to_string(),
+                    description: String::new(),
+                    units: *units,
+                });
+                cargo.units += units;
+            }
+        }
+
+        #[test]
+        fn stops_mining_when_kept_goods_fill_the_hold() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            fill_hold(&server, &[("ICE_WATER", 60)]);
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "mine", "-s", "ALI-1", "-a", "X1-DF55-69207D"])
+                .args(["-m", "X1-DF55-20250Z", "-k", "ice-water"])
+                .timeout(std::time::Duration::from_secs(30))
+                .assert()
+                .code(1)
+                .stderr(predicates::str::contains("no room to mine"));
+
+            let world = server.world();
+            assert_eq!(world.extractions, 0);
+            assert_eq!(world.ships[0].nav.waypoint_symbol, "X1-DF55-20250Z");
+            assert_eq!(world.ships[0].cargo.units, 60);
+            Ok(())
+        }
+
+        #[test]
+        fn jettisons_what_the_market_does_not_trade() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            fill_hold(&server, &[("ICE_WATER", 50), ("COPPER_ORE", 10)]);
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "mine", "-s", "ALI-1", "-a", "X1-DF55-69207D"])
+                .args(["-m", "X1-DF55-20250Z", "-k", "ice-water", "-n", "1"])
+                .timeout(std::time::Duration::from_secs(30))
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("jettisoned 10 COPPER_ORE"));
+
+            let world = server.world();
+            assert!(world.extractions > 0);
+            let ship = &world.ships[0];
+            assert!(ship.cargo.units < ship.cargo.capacity);
+            assert!(ship
+                .cargo
+                .inventory
+                .iter()
+                .any(|item| item.symbol == "ICE_WATER" && item.units >= 50));
+            Ok(())
+        }
+
+        #[test]
+        fn shows_market_prices_only_with_a_ship_present() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .args(["waypoint", "market", "-w", "X1-DF55-20250Z"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("purchase_price: 46"));
+            cli(&server, &current_user_dir)
+                .args(["waypoint", "market", "-w", "X1-DF55-58932B"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("purchase_price").not());
+            Ok(())
+        }
+
+        #[test]
+        fn records_every_market_fetched_in_the_history() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .args(["route", "plan", "-s", "ALI-1", "--to", "X1-DF55-17335A"])
+                .assert()
+                .success();
+            cli(&server, &current_user_dir)
+                .args(["market", "history", "-w", "X1-DF55-20250Z", "-g", "fuel"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("buy"));
+            Ok(())
+        }
+
+        #[test]
+        fn purchases_a_ship_at_the_shipyard() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "purchase", "-s", "ship-probe"])
+                .args(["-w", "X1-DF55-20250Z"])
+                .assert()
+                .success();
+            cli(&server, &current_user_dir)
+                .args(["ship", "list"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("ALI-2"));
+            assert_eq!(server.world().agent.credits, 80000);
+            cli(&server, &current_user_dir)
+                .args(["ledger", "report"])
+                .assert()
+                .success()
+                .stdout(predicates::str::is_match(r"(?m)^per ship:\n  ALI-2 ")?);
+            assert_eq!(server.world().ships[1].cooldown.ship_symbol, "ALI-2");
+            Ok(())
+        }
+
+        #[test]
+        fn reports_the_running_cooldown_with_the_ship() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            server.world().cooldowns.insert(
+                "ALI-1".to_string(),
+                Cooldown {
+                    ship_symbol: "ALI-1".to_string(),
+                    total_seconds: 70,
+                    remaining_seconds: 70,
+                    expiration: Some(Utc::now() + Duration::seconds(70)),
+                },
+            );
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "list"])
+                .assert()
+                .success()
+                .stdout(
+                    predicates::str::contains("total_seconds: 70")
+                        .and(predicates::str::contains("total_seconds: 0").not()),
+                );
+            Ok(())
+        }
+
+        #[test]
+        fn caches_symbols_for_completion() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            for command in [
+                &["ship", "list"][..],
+                &["contract", "list"],
+                &["waypoint", "list"],
+            ] {
+                cli(&server, &current_user_dir)
+                    .args(command)
+                    .assert()
+                    .success();
+            }
+            cli(&server, &current_user_dir)
+                .args(["completions", "--", PRG, "ship", "dock", "-s", ""])
+                .assert()
+                .success()
+                .stdout("ALI-1\n");
+            cli(&server, &current_user_dir)
+                .args(["completions", "--", PRG, "contract", "accept", "-c", ""])
+                .assert()
+                .success()
+                .stdout("clkxq7cmv0001s60cwxzxy1hs\n");
+            cli(&server, &current_user_dir)
+                .args(["completions", "--", PRG, "waypoint", "market", "-w", ""])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("X1-DF55-69207D"));
+            Ok(())
+        }
+
+        #[test]
+        fn rejects_navigating_without_fuel() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            server.world().ships[0].fuel.current = 0;
+
+            cli(&server, &current_user_dir)
+                .args(["ship", "navigate", "waypoint", "-s", "ALI-1"])
+                .args(["-w", "X1-DF55-58932B"])
+                .assert()
+                .failure();
+            assert_eq!(
+                server.world().ships[0].nav.waypoint_symbol,
+                "X1-DF55-20250Z"
+            );
+            Ok(())
+        }
+    }
+
+    mod test_systems {
+        use super::*;
+        use space_traders_rust::domain::{WaypointTrait, WaypointTraitSymbol};
+
+        #[test]
+        fn exports_the_system_with_its_jump_gate_links() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            let output = current_user_dir.child("system.dot");
+
+            cli(&server, &current_user_dir)
+                .args(["system", "export", "--format", "dot", "--jump-gates", "-o"])
+                .arg(output.path())
+                .assert()
+                .success();
+            let dot = std::fs::read_to_string(output.path())?;
+            assert!(dot.contains("\"X1-DF55-20250Z\" -- \"X1-DF55-17335A\""));
+            assert!(dot.contains("\"X1-DF55-96410E\" -- \"X1-KM22-93271E\""));
+            assert!(dot.contains("\"ALI-1\" -- \"X1-DF55-20250Z\""));
+            Ok(())
+        }
+
+        #[test]
+        fn tolerates_values_added_on_the_server() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            for waypoint in server.world().waypoints.iter_mut() {
+                waypoint.traits.push(WaypointTrait {
+                    symbol: WaypointTraitSymbol::Unknown(String::from("HAUNTED_RUINS")),
+                    name: String::from("Haunted Ruins"),
+                    description: String::new(),
+                });
+            }
+
+            let listed = cli(&server, &current_user_dir)
+                .args(["waypoint", "list"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("HAUNTED_RUINS"));
+            let stderr = String::from_utf8(listed.get_output().stderr.clone())?;
+            assert_eq!(
+                stderr
+                    .matches("warning: unknown WaypointTraitSymbol `HAUNTED_RUINS`")
+                    .count(),
+                1
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn maps_ships_where_they_are() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .args(["system", "map"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains(
+                    "1 ALI-1        DOCKED at X1-DF55-20250Z",
+                ))
+                .stdout(predicates::str::contains("@   X1-DF55-96410E"));
+            Ok(())
+        }
+    }
+
+    mod test_contracts {
+        use super::*;
+        use space_traders_rust::domain::Inventory;
+
+        const CONTRACT_ID: &str = "clkxq7cmv0001s60cwxzxy1hs";
+
+        #[test]
+        fn accepts_delivers_and_fulfills() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            {
+                let mut world = server.world();
+                let cargo = &mut world.ships[0].cargo;
+                cargo.units = 30;
+                cargo.inventory.push(Inventory {
+                    symbol: String::from("IRON_ORE"),
+                    name: String::from("Iron Ore"),
+                    description: String::new(),
+                    units: 30,
+                });
+            }
+
+            cli(&server, &current_user_dir)
+                .args(["contract", "fulfill", "-c", CONTRACT_ID])
+                .assert()
+                .failure();
+            cli(&server, &current_user_dir)
+                .args(["contract", "accept", "-c", CONTRACT_ID])
+                .assert()
+                .success();
+            cli(&server, &current_user_dir)
+                .args(["contract", "deliver", "-s", "ALI-1", "-c", CONTRACT_ID])
+                .args(["-t", "iron-ore", "-u", "30"])
+                .assert()
+                .success();
+            cli(&server, &current_user_dir)
+                .args(["contract", "fulfill", "-c", CONTRACT_ID])
+                .assert()
+                .success();
+
+            let world = server.world();
+            assert!(world.contracts[0].fulfilled);
+            assert_eq!(world.ships[0].cargo.units, 0);
+            assert_eq!(world.agent.credits, 125000);
+            Ok(())
+        }
+
+        #[test]
+        fn evaluates_against_recorded_prices() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .args(["contract", "evaluate", "-c", CONTRACT_ID])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("no recorded market sells it"))
+                .stdout(predicates::str::contains("net profit   unknown"));
+            cli(&server, &current_user_dir)
+                .args(["waypoint", "market", "-w", "X1-DF55-20250Z"])
+                .assert()
+                .success();
+            // 30 IRON_ORE bought at 46 for a contract paying 25000, with ALI-1 already there
+            cli(&server, &current_user_dir)
+                .args(["contract", "evaluate", "--contract", CONTRACT_ID])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains(
+                    "buy at X1-DF55-20250Z for 46 (1380)",
+                ))
+                .stdout(predicates::str::contains("net profit     23620"))
+                .stdout(predicates::str::contains("ALI-1 needs 1 trip(s)"))
+                .stdout(predicates::str::contains(": realistic"));
+            Ok(())
+        }
+    }
+
+    mod test_shell {
+        use super::*;
+
+        #[test]
+        fn fills_in_the_symbols_set_with_use() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .arg("shell")
+                .write_stdin(concat!(
+                    "use ship ALI-1\n",
+                    "use waypoint X1-DF55-58932B\n",
+                    "ship orbit\n",
+                    "ship navigate waypoint\n",
+                    "use\n",
+                    "exit\n",
+                ))
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("waypoint: X1-DF55-58932B"));
+            assert_eq!(
+                server.world().ships[0].nav.waypoint_symbol,
+                "X1-DF55-58932B"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn keeps_going_after_a_failed_command() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+
+            cli(&server, &current_user_dir)
+                .arg("shell")
+                .write_stdin("ship orbit\nship frobnicate\nuse contract clkxq7cmv0001s60cwxzxy1hs\ncontract accept\n")
+                .assert()
+                .success()
+                .stderr(predicates::str::contains("required"))
+                .stderr(predicates::str::contains("frobnicate"));
+            assert!(server.world().contracts[0].accepted);
+            assert!(current_user_dir.child("shell_history.txt").exists());
+            Ok(())
+        }
+    }
+
+    mod test_daemon {
+        use super::*;
+
+        #[cfg(unix)]
+        #[test]
+        fn streams_what_the_ships_do_as_events() -> TestResult {
+            use std::{
+                io::{BufRead, BufReader},
+                process::{Command as StdCommand, Stdio},
+            };
+
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir(&server);
+            std::fs::write(
+                current_user_dir.child("daemon.json"),
+                r#"{"ships": {"ALI-1": {"behaviour": "scout", "waypoints": ["X1-DF55-20250Z"]}}}"#,
+            )?;
+
+            let mut daemon = StdCommand::new(assert_cmd::cargo::cargo_bin(PRG))
+                .args(["daemon", "run", "--control", "127.0.0.1:0"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.path())
+                .env("TEST_API_BASE_URL", server.url())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            let mut stderr = BufReader::new(daemon.stderr.take().unwrap());
+            let mut line = String::new();
+            let addr = loop {
+                line.clear();
+                assert_ne!(stderr.read_line(&mut line)?, 0, "daemon exited early");
+                if let Some(addr) = line.trim().strip_prefix("control API listening on http://") {
+                    break addr.to_owned();
+                }
+            };
+
+            let mut events = String::new();
+            for _ in 0..50 {
+                let output = cli(&server, &current_user_dir)
+                    .args(["daemon", "events", "--control", &addr])
+                    .output()?;
+                events = String::from_utf8(output.stdout)?;
+                if events.contains("recorded") {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            StdCommand::new("kill")
+                .args(["-TERM", &daemon.id().to_string()])
+                .status()?;
+            assert!(daemon.wait()?.success());
+
+            assert!(
+                events.contains("ALI-1: recorded 3 prices at X1-DF55-20250Z"),
+                "{events}"
+            );
+            Ok(())
+        }
+    }
+}
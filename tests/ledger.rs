@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod ledger_tests {
+
+    mod test_ledger {
+        use chrono::{Duration, Utc};
+        use space_traders_rust::{
+            domain::MarketTransaction,
+            ledger::{parse_since, report, Ledger, LedgerEntry, LedgerKind},
+        };
+
+        fn transaction(
+            kind: &str,
+            trade_symbol: &str,
+            units: i32,
+            total_price: i32,
+        ) -> MarketTransaction {
+            serde_json::from_str(&format!(
+                r#"{{"waypointSymbol": "X1-DF55-20250Z", "shipSymbol": "ALI-1", "tradeSymbol": "{trade_symbol}", "type": "{kind}", "units": {units}, "pricePerUnit": 1, "totalPrice": {total_price}, "timestamp": "2023-08-01T10:00:00.000Z"}}"#
+            ))
+            .unwrap()
+        }
+
+        #[test]
+        fn classifies_market_transactions() {
+            let sell =
+                LedgerEntry::from_market_transaction(&transaction("SELL", "IRON_ORE", 10, 90));
+            let fuel =
+                LedgerEntry::from_market_transaction(&transaction("PURCHASE", "FUEL", 5, 50));
+            let buy = LedgerEntry::from_market_transaction(&transaction("PURCHASE", "IRON", 2, 40));
+
+            assert_eq!(sell.kind, LedgerKind::Sell);
+            assert_eq!(fuel.kind, LedgerKind::Fuel);
+            assert_eq!(buy.kind, LedgerKind::Purchase);
+        }
+
+        #[test]
+        fn appends_and_reads_back_entries() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let ledger = Ledger::open(dir.path());
+            let entry =
+                LedgerEntry::from_market_transaction(&transaction("SELL", "IRON_ORE", 10, 90));
+
+            assert!(ledger.all().unwrap().is_empty());
+            ledger.append(&entry).unwrap();
+            ledger.append(&entry).unwrap();
+
+            assert_eq!(ledger.all().unwrap(), vec![entry.clone(), entry]);
+        }
+
+        #[test]
+        fn reports_per_ship_and_per_good() {
+            let mut entries = vec![
+                LedgerEntry::from_market_transaction(&transaction("SELL", "IRON_ORE", 10, 90)),
+                LedgerEntry::from_market_transaction(&transaction("PURCHASE", "FUEL", 5, 50)),
+                LedgerEntry::from_market_transaction(&transaction("PURCHASE", "IRON_ORE", 2, 10)),
+            ];
+            let mut other_ship =
+                LedgerEntry::from_market_transaction(&transaction("SELL", "ICE_WATER", 3, 30));
+            other_ship.ship_symbol = "ALI-2".to_string();
+            entries.push(other_ship);
+
+            let report = report(&entries, None);
+
+            assert_eq!(report.total.income, 120);
+            assert_eq!(report.total.fuel, 50);
+            assert_eq!(report.total.purchases, 10);
+            assert_eq!(report.total.net(), 60);
+            assert_eq!(report.per_ship["ALI-1"].net(), 30);
+            assert_eq!(report.per_ship["ALI-2"].net(), 30);
+            assert_eq!(report.per_good["IRON_ORE"].net(), 80);
+            assert!(!report.per_good.contains_key("FUEL"));
+        }
+
+        #[test]
+        fn leaves_out_entries_before_since() {
+            let mut recent =
+                LedgerEntry::from_market_transaction(&transaction("SELL", "IRON_ORE", 10, 90));
+            recent.timestamp = Utc::now();
+            let old =
+                LedgerEntry::from_market_transaction(&transaction("SELL", "IRON_ORE", 10, 90));
+
+            let report = report(&[old, recent], Some(Utc::now() - Duration::hours(1)));
+
+            assert_eq!(report.total.income, 90);
+        }
+
+        #[test]
+        fn parses_since_in_several_forms() {
+            let day_ago = parse_since("1d").unwrap();
+            assert!(
+                (Utc::now() - Duration::days(1) - day_ago)
+                    .num_seconds()
+                    .abs()
+                    < 5
+            );
+            assert_eq!(
+                parse_since("2023-08-01").unwrap().to_rfc3339(),
+                "2023-08-01T00:00:00+00:00"
+            );
+            assert_eq!(
+                parse_since("2023-08-01T10:00:00Z").unwrap().to_rfc3339(),
+                "2023-08-01T10:00:00+00:00"
+            );
+            assert!(parse_since("yesterday").is_err());
+            assert!(parse_since("").is_err());
+        }
+
+        #[test]
+        fn rejects_odd_units_and_spans_out_of_range() {
+            assert!(parse_since("5µ").is_err());
+            assert!(parse_since("µ").is_err());
+            assert!(parse_since("99999999999999d").is_err());
+            assert!(parse_since("9999999999999w").is_err());
+            assert!(parse_since("-99999999999999s").is_err());
+        }
+    }
+}
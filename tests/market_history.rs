@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod market_history_tests {
+
+    mod test_market_history {
+        use space_traders_rust::{domain::Market, market_history::MarketHistory};
+
+        const MARKET: &str = include_str!("fixtures/market.json");
+
+        #[test]
+        fn starts_out_empty() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let history = MarketHistory::open(dir.path());
+
+            assert!(history.all().unwrap().is_empty());
+        }
+
+        #[test]
+        fn records_every_trade_good() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let history = MarketHistory::open(dir.path());
+            let market: Market = serde_json::from_str(MARKET).unwrap();
+
+            assert_eq!(history.record(&market).unwrap(), 2);
+            assert_eq!(history.record(&market).unwrap(), 2);
+
+            let iron = history.history("X1-DF55-20250Z", "IRON_ORE").unwrap();
+            assert_eq!(iron.len(), 2);
+            assert_eq!(iron[0].sell_price, 40);
+            assert!(iron[0].timestamp <= iron[1].timestamp);
+            assert_eq!(history.latest().unwrap().len(), 2);
+        }
+
+        #[test]
+        fn skips_markets_without_prices() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let history = MarketHistory::open(dir.path());
+            let mut market: Market = serde_json::from_str(MARKET).unwrap();
+            market.trade_goods.clear();
+
+            assert_eq!(history.record(&market).unwrap(), 0);
+            assert!(history.all().unwrap().is_empty());
+        }
+
+        #[test]
+        fn records_each_transaction_once() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let history = MarketHistory::open(dir.path());
+            let mut market: Market = serde_json::from_str(MARKET).unwrap();
+            history.record(&market).unwrap();
+
+            let mut newer = market.transactions[0].clone();
+            newer.timestamp += chrono::Duration::minutes(5);
+            newer.units = 3;
+            market.transactions.push(newer);
+            market.trade_goods.clear();
+            history.record(&market).unwrap();
+
+            let transactions = history.transactions("X1-DF55-20250Z").unwrap();
+            assert_eq!(transactions.len(), 2);
+            assert_eq!(transactions[1].units, 3);
+            assert!(history.transactions("X1-DF55-17335A").unwrap().is_empty());
+        }
+
+        #[test]
+        fn records_transactions_made_in_the_same_second() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let mut market: Market = serde_json::from_str(MARKET).unwrap();
+            market.trade_goods.clear();
+            MarketHistory::open(dir.path()).record(&market).unwrap();
+
+            let mut other_ship = market.transactions[0].clone();
+            other_ship.ship_symbol = String::from("OTHER-2");
+            market.transactions.push(other_ship);
+            let history = MarketHistory::open(dir.path());
+            history.record(&market).unwrap();
+            history.record(&market).unwrap();
+
+            let transactions = history.transactions("X1-DF55-20250Z").unwrap();
+            assert_eq!(transactions.len(), 2);
+            assert_eq!(transactions[1].ship_symbol, "OTHER-2");
+        }
+
+        #[test]
+        fn exports_csv_with_a_header() {
+            let dir = assert_fs::TempDir::new().unwrap();
+            let history = MarketHistory::open(dir.path());
+            let market: Market = serde_json::from_str(MARKET).unwrap();
+            history.record(&market).unwrap();
+
+            let mut csv = Vec::new();
+            history.export_csv(&mut csv).unwrap();
+            let csv = String::from_utf8(csv).unwrap();
+            let lines: Vec<_> = csv.lines().collect();
+
+            assert_eq!(
+                lines[0],
+                "timestamp,waypointSymbol,tradeSymbol,supply,purchasePrice,sellPrice,tradeVolume"
+            );
+            assert_eq!(lines.len(), 3);
+            assert!(lines[2].ends_with(",X1-DF55-20250Z,IRON_ORE,SCARCE,46,40,60"));
+        }
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod notes_tests {
+
+    mod test_capture {
+        use space_traders_rust::notes;
+
+        // one test, as the capture is process wide
+        #[test]
+        fn collects_notes_only_while_held() {
+            let mut capture = notes::capture();
+            notes::note(String::from("auto-state: docked ALI-1 at X1-DF55-20250Z"));
+            notes::note(String::from("warning: unknown Supply `OVERFLOWING`"));
+
+            assert_eq!(
+                capture.drain(),
+                [
+                    "auto-state: docked ALI-1 at X1-DF55-20250Z",
+                    "warning: unknown Supply `OVERFLOWING`"
+                ]
+            );
+            assert!(capture.drain().is_empty());
+
+            drop(capture);
+            notes::note(String::from("printed on stderr"));
+            assert!(notes::capture().drain().is_empty());
+        }
+    }
+}
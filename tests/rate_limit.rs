@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod rate_limit_tests {
+
+    mod test_acquire {
+        use std::time::{Duration, Instant};
+
+        use space_traders_rust::rate_limit::RateLimiter;
+
+        #[tokio::test]
+        async fn lets_a_burst_through_immediately() {
+            let limiter = RateLimiter::new(1.0, 3);
+            let started = Instant::now();
+
+            for _ in 0..3 {
+                limiter.acquire().await;
+            }
+
+            assert!(started.elapsed() < Duration::from_millis(100));
+        }
+
+        #[tokio::test]
+        async fn paces_requests_beyond_the_burst() {
+            let limiter = RateLimiter::new(20.0, 1);
+            let started = Instant::now();
+
+            for _ in 0..3 {
+                limiter.acquire().await;
+            }
+
+            // one free token, then two more at 50ms each
+            assert!(started.elapsed() >= Duration::from_millis(90));
+        }
+    }
+}
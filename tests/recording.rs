@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod recording_tests {
+    use std::{
+        fs::File,
+        io::{BufWriter, Write},
+    };
+
+    use assert_cmd::Command;
+    use assert_fs::prelude::PathChild;
+    use space_traders_rust::{domain::Agent, fake_server::FakeServer, recording, UserInfo};
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+    const PRG: &str = "space_traders_rust";
+    const SESSION: &str = "tests/fixtures/recordings/session";
+    /// Nothing listens here, so any request that isn't replayed fails
+    const UNREACHABLE_API: &str = "http://127.0.0.1:9";
+
+    fn logged_in_user_dir() -> assert_fs::TempDir {
+        let current_user_dir = assert_fs::TempDir::new().unwrap();
+        let test_user_info = UserInfo {
+            token: String::from("REDACTED"),
+            agent: Agent {
+                credits: 100000,
+                symbol: "ALI".to_string(),
+                account_id: None,
+                ship_count: Some(1),
+                headquarters: "X1-DF55-20250Z".to_string(),
+                starting_faction: "COSMIC".to_string(),
+            },
+        };
+        let file = File::create(current_user_dir.child("current_user.json")).unwrap();
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &test_user_info).unwrap();
+        writer.flush().unwrap();
+        current_user_dir
+    }
+
+    fn cli(current_user_dir: &assert_fs::TempDir, api_base_url: &str) -> Command {
+        let mut cmd = Command::cargo_bin(PRG).unwrap();
+        cmd.env("TEST_CURRENT_USER_DIR", current_user_dir.path())
+            .env("TEST_API_BASE_URL", api_base_url);
+        cmd
+    }
+
+    mod test_replay {
+        use super::*;
+        use space_traders_rust::{api::ApiSuccessResponse, domain::*};
+
+        #[test]
+        fn answers_commands_from_the_recorded_session() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            cli(&current_user_dir, UNREACHABLE_API)
+                .args(["--replay", SESSION, "ship", "list"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("ALI-1"));
+            cli(&current_user_dir, UNREACHABLE_API)
+                .args(["--replay", SESSION, "ship", "navigate", "waypoint"])
+                .args(["-s", "ALI-1", "-w", "X1-DF55-69207D"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("IN_TRANSIT"));
+            cli(&current_user_dir, UNREACHABLE_API)
+                .args(["--replay", SESSION, "waypoint", "market"])
+                .args(["-w", "X1-DF55-20250Z"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("ICE_WATER"));
+            cli(&current_user_dir, UNREACHABLE_API)
+                .args(["--replay", SESSION, "contract", "list"])
+                .assert()
+                .success()
+                .stdout(predicates::str::contains("clkxq7cmv0001s60cwxzxy1hs"));
+            Ok(())
+        }
+
+        #[test]
+        fn fails_requests_that_were_not_recorded() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            cli(&current_user_dir, UNREACHABLE_API)
+                .args(["--replay", SESSION, "waypoint", "get"])
+                .args(["-w", "X1-DF55-58932B"])
+                .assert()
+                .failure()
+                .stderr(predicates::str::contains(
+                    "no recorded response left for GET /systems/X1-DF55/waypoints/X1-DF55-58932B",
+                ));
+            Ok(())
+        }
+
+        /// Catches `domain` changes that would break on recorded payloads
+        #[test]
+        fn recorded_payloads_deserialize_into_domain_types() -> TestResult {
+            fn parse<T: serde::de::DeserializeOwned>(body: &serde_json::Value) {
+                serde_json::from_value::<ApiSuccessResponse<T>>(body.clone()).unwrap();
+            }
+
+            for exchange in recording::load(std::path::Path::new(SESSION))? {
+                let body = &exchange.response.body;
+                let path = exchange.request.path.split('?').next().unwrap();
+                match (exchange.request.method.as_str(), path) {
+                    ("POST", "/register") => parse::<RegisterResponse>(body),
+                    ("GET", "/my/agent") => parse::<Agent>(body),
+                    ("GET", "/my/ships") => parse::<Vec<Ship>>(body),
+                    ("GET", "/my/contracts") => parse::<Vec<Contract>>(body),
+                    ("GET", path) if path.ends_with("/market") => parse::<Market>(body),
+                    ("GET", path) if path.ends_with("/shipyard") => parse::<Shipyard>(body),
+                    ("GET", path) if path.ends_with("/waypoints") => parse::<Vec<Waypoint>>(body),
+                    ("GET", path) if path.ends_with("/nav") => parse::<ShipNav>(body),
+                    ("POST", path) if path.ends_with("/navigate") => {
+                        parse::<ShipNavigateResponse>(body)
+                    }
+                    ("POST", path) if path.ends_with("/extract") => {
+                        parse::<ExtractResourceResponse>(body)
+                    }
+                    _ => parse::<serde_json::Value>(body),
+                }
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn loads_exchanges_in_numeric_order_past_9999() -> TestResult {
+            let recordings = assert_fs::TempDir::new()?;
+            for (number, path) in [(10000, "/my/ships"), (9999, "/my/agent")] {
+                let exchange = serde_json::json!({
+                    "request": { "method": "GET", "path": path },
+                    "response": { "status": 200, "body": null }
+                });
+                let name = format!(
+                    "{number:04}-GET-{}.json",
+                    path.trim_matches('/').replace('/', "_")
+                );
+                std::fs::write(recordings.child(name), exchange.to_string())?;
+            }
+
+            let paths: Vec<_> = recording::load(recordings.path())?
+                .into_iter()
+                .map(|exchange| exchange.request.path)
+                .collect();
+            assert_eq!(paths, ["/my/agent", "/my/ships"]);
+            Ok(())
+        }
+    }
+
+    mod test_record {
+        use super::*;
+
+        #[test]
+        fn replays_what_it_recorded() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir();
+            let recordings = assert_fs::TempDir::new()?;
+            let dir = recordings.path().to_str().unwrap();
+
+            let recorded = cli(&current_user_dir, &server.url())
+                .args(["--record", dir, "ship", "list"])
+                .assert()
+                .success();
+            drop(server);
+            cli(&current_user_dir, UNREACHABLE_API)
+                .args(["--replay", dir, "ship", "list"])
+                .assert()
+                .success()
+                .stdout(recorded.get_output().stdout.clone());
+            Ok(())
+        }
+
+        #[test]
+        fn keeps_the_token_out_of_recordings() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = assert_fs::TempDir::new()?;
+            let recordings = assert_fs::TempDir::new()?;
+
+            cli(&current_user_dir, &server.url())
+                .args(["--record", recordings.path().to_str().unwrap()])
+                .args(["register", "-u", "BOB"])
+                .assert()
+                .success();
+            for exchange in recording::load(recordings.path())? {
+                let recorded = serde_json::to_string(&exchange)?;
+                assert!(!recorded.contains("fake-token-BOB"));
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn records_registering_from_the_shell() -> TestResult {
+            let server = FakeServer::start()?;
+            let current_user_dir = logged_in_user_dir();
+            let recordings = assert_fs::TempDir::new()?;
+
+            cli(&current_user_dir, &server.url())
+                .args(["--record", recordings.path().to_str().unwrap(), "shell"])
+                .write_stdin("register -u BOB\nexit\n")
+                .assert()
+                .success();
+            let exchanges = recording::load(recordings.path())?;
+            assert!(exchanges
+                .iter()
+                .any(|exchange| exchange.request.path == "/register"));
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod route_tests {
+
+    mod test_plan_route {
+        use space_traders_rust::{
+            domain::ShipNavFlightMode,
+            route::{plan_route, RoutePoint, RouteStep, ShipRouteSpec},
+        };
+
+        fn point(symbol: &str, x: i32, y: i32, sells_fuel: bool) -> RoutePoint {
+            RoutePoint {
+                symbol: symbol.to_string(),
+                x,
+                y,
+                sells_fuel,
+            }
+        }
+
+        fn ship(fuel_current: i32, fuel_capacity: i32) -> ShipRouteSpec {
+            ShipRouteSpec {
+                speed: 30,
+                fuel_current,
+                fuel_capacity,
+            }
+        }
+
+        #[test]
+        fn flies_directly_when_the_tank_allows() {
+            let points = vec![point("A", 0, 0, true), point("B", 30, 40, false)];
+
+            let plan = plan_route(&points, "A", "B", ship(400, 400)).unwrap();
+
+            assert_eq!(plan.steps.len(), 1);
+            let leg = plan.legs().next().unwrap();
+            assert_eq!(leg.flight_mode, ShipNavFlightMode::BURN);
+            assert_eq!(leg.distance, 50.0);
+            assert_eq!(leg.fuel_cost, 100);
+            assert_eq!(plan.total_fuel(), 100);
+        }
+
+        #[test]
+        fn falls_back_to_cruise_to_stay_within_fuel() {
+            let points = vec![point("A", 0, 0, false), point("B", 30, 40, false)];
+
+            let plan = plan_route(&points, "A", "B", ship(60, 100)).unwrap();
+
+            let leg = plan.legs().next().unwrap();
+            assert_eq!(leg.flight_mode, ShipNavFlightMode::CRUISE);
+            assert_eq!(leg.fuel_cost, 50);
+        }
+
+        #[test]
+        fn inserts_a_refuel_stop_when_a_direct_hop_is_impossible() {
+            let points = vec![
+                point("A", 0, 0, false),
+                point("FUEL", 60, 0, true),
+                point("B", 120, 0, false),
+            ];
+
+            let plan = plan_route(&points, "A", "B", ship(80, 80)).unwrap();
+
+            let refuels: Vec<_> = plan
+                .steps
+                .iter()
+                .filter(|step| matches!(step, RouteStep::Refuel { .. }))
+                .collect();
+            assert_eq!(refuels.len(), 1);
+            assert!(matches!(
+                refuels[0],
+                RouteStep::Refuel { waypoint_symbol, units: 60 } if waypoint_symbol == "FUEL"
+            ));
+            assert!(plan
+                .legs()
+                .all(|leg| leg.flight_mode == ShipNavFlightMode::CRUISE));
+        }
+
+        #[test]
+        fn drifts_when_nothing_else_fits() {
+            let points = vec![point("A", 0, 0, false), point("B", 100, 0, false)];
+
+            let plan = plan_route(&points, "A", "B", ship(5, 100)).unwrap();
+
+            let leg = plan.legs().next().unwrap();
+            assert_eq!(leg.flight_mode, ShipNavFlightMode::DRIFT);
+            assert_eq!(leg.fuel_cost, 1);
+        }
+
+        #[test]
+        fn ships_without_a_tank_travel_for_free() {
+            let points = vec![point("A", 0, 0, false), point("B", 100, 0, false)];
+
+            let plan = plan_route(&points, "A", "B", ship(0, 0)).unwrap();
+
+            assert_eq!(plan.total_fuel(), 0);
+            assert_eq!(
+                plan.legs().next().unwrap().flight_mode,
+                ShipNavFlightMode::BURN
+            );
+        }
+
+        #[test]
+        fn returns_none_for_an_unknown_destination() {
+            let points = vec![point("A", 0, 0, false)];
+
+            assert!(plan_route(&points, "A", "Z", ship(100, 100)).is_none());
+        }
+
+        #[test]
+        fn returns_none_with_an_empty_tank() {
+            let points = vec![point("A", 0, 0, false), point("B", 10, 0, false)];
+
+            assert!(plan_route(&points, "A", "B", ship(0, 100)).is_none());
+        }
+    }
+}
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod script_tests {
+
+    mod test_parse_line {
+        use std::collections::HashMap;
+
+        use space_traders_rust::script::{parse_line, parse_var, Step};
+
+        fn vars() -> HashMap<String, String> {
+            HashMap::from([("SHIP".to_string(), "ALI-1".to_string())])
+        }
+
+        fn command(words: &[&str]) -> Option<Step> {
+            Some(Step::Command(words.iter().map(|w| w.to_string()).collect()))
+        }
+
+        #[test]
+        fn skips_blank_lines_and_comments() {
+            assert_eq!(parse_line("", &vars()), Ok(None));
+            assert_eq!(parse_line("   # dock first", &vars()), Ok(None));
+        }
+
+        #[test]
+        fn expands_variables() {
+            assert_eq!(
+                parse_line("ship dock -s $SHIP # trailing comment", &vars()),
+                Ok(command(&["ship", "dock", "-s", "ALI-1"]))
+            );
+            assert_eq!(
+                parse_line("ship dock -s ${SHIP}", &vars()),
+                Ok(command(&["ship", "dock", "-s", "ALI-1"]))
+            );
+        }
+
+        #[test]
+        fn honours_quotes() {
+            assert_eq!(
+                parse_line(r#"register -u "my agent" -f '$SHIP'"#, &vars()),
+                Ok(command(&["register", "-u", "my agent", "-f", "$SHIP"]))
+            );
+            assert!(parse_line("register -u \"open", &vars()).is_err());
+        }
+
+        #[test]
+        fn rejects_undefined_variables() {
+            assert_eq!(
+                parse_line("ship dock -s $OTHER", &vars()),
+                Err("undefined variable `OTHER`".to_string())
+            );
+        }
+
+        #[test]
+        fn parses_script_steps() {
+            assert_eq!(
+                parse_line("set FIELD=X1-DF55-69207D", &vars()),
+                Ok(Some(Step::Set(
+                    "FIELD".to_string(),
+                    "X1-DF55-69207D".to_string()
+                )))
+            );
+            assert_eq!(
+                parse_line("on-error continue", &vars()),
+                Ok(Some(Step::OnError {
+                    continue_on_error: true
+                }))
+            );
+            assert_eq!(
+                parse_line("wait-until-arrival $SHIP", &vars()),
+                Ok(Some(Step::WaitUntilArrival("ALI-1".to_string())))
+            );
+            assert_eq!(
+                parse_line("wait-cooldown $SHIP", &vars()),
+                Ok(Some(Step::WaitCooldown("ALI-1".to_string())))
+            );
+            assert!(parse_line("wait-cooldown", &vars()).is_err());
+        }
+
+        #[test]
+        fn parses_vars() {
+            assert_eq!(
+                parse_var("SHIP=ALI-1"),
+                Ok(("SHIP".to_string(), "ALI-1".to_string()))
+            );
+            assert!(parse_var("1SHIP=ALI-1").is_err());
+            assert!(parse_var("SHIP").is_err());
+        }
+    }
+}
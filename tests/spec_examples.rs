@@ -0,0 +1,283 @@
+#[cfg(test)]
+mod spec_examples_tests {
+    //! Payloads following the SpaceTraders OpenAPI spec, in tests/fixtures/spec
+
+    use serde::de::DeserializeOwned;
+    use space_traders_rust::api::ApiSuccessResponse;
+
+    fn parse<T: DeserializeOwned>(json: &str) -> ApiSuccessResponse<T> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    mod test_payloads {
+        use super::parse;
+        use space_traders_rust::domain::{
+            ActivityLevel, Agent, Contract, CreateSurveyResponse, Deposit, DepositSize,
+            ExtractResourceResponse, FactionSymbol, JumpGate, Market, MarketTradeGoodType,
+            MarketTransactionType, PurchaseShipResponse, Ship, ShipFrameSymbol, ShipModuleSymbol,
+            ShipMountSymbol, ShipNavStatus, ShipNavigateResponse, ShipRefuelResponse, ShipRole,
+            ShipType, Shipyard, Supply, TradeSymbol, Waypoint, WaypointModifierSymbol,
+            WaypointTraitSymbol, WaypointType,
+        };
+
+        #[test]
+        fn parses_the_agent() {
+            let agent = parse::<Agent>(include_str!("fixtures/spec/agent.json")).data;
+
+            assert_eq!(
+                agent.account_id.as_deref(),
+                Some("cl0hok34m0003ks0jjql5q8f2")
+            );
+            assert_eq!(agent.credits, 150000);
+            assert_eq!(agent.ship_count, Some(2));
+        }
+
+        #[test]
+        fn parses_charted_and_uncharted_waypoints() {
+            let res = parse::<Vec<Waypoint>>(include_str!("fixtures/spec/waypoints.json"));
+            let [planet, station, asteroid] = res.data.as_slice() else {
+                panic!("expected three waypoints");
+            };
+
+            assert_eq!(planet.waypoint_type, WaypointType::PLANET);
+            assert_eq!(
+                planet.faction.as_ref().unwrap().symbol,
+                FactionSymbol::COSMIC
+            );
+            assert_eq!(planet.orbitals[0].symbol, "X1-DF55-A2");
+            assert_eq!(planet.traits[1].symbol, WaypointTraitSymbol::SHIPYARD);
+            assert!(planet.chart.is_some());
+            assert!(!planet.is_under_construction);
+
+            assert_eq!(station.waypoint_type, WaypointType::FUEL_STATION);
+            assert_eq!(station.orbits.as_deref(), Some("X1-DF55-A1"));
+
+            assert_eq!(asteroid.waypoint_type, WaypointType::ENGINEERED_ASTEROID);
+            assert!(asteroid.faction.is_none());
+            assert!(asteroid.chart.is_none());
+            assert_eq!(
+                asteroid.modifiers[0].symbol,
+                WaypointModifierSymbol::UNSTABLE
+            );
+            assert_eq!(res.meta.unwrap().total, 3);
+        }
+
+        #[test]
+        fn parses_a_ship() {
+            let ship = parse::<Ship>(include_str!("fixtures/spec/ship.json")).data;
+
+            assert_eq!(ship.nav.status, ShipNavStatus::DOCKED);
+            assert_eq!(ship.frame.symbol, ShipFrameSymbol::FRAME_FRIGATE);
+            assert_eq!(ship.frame.requirements.slots, None);
+            assert_eq!(ship.frame.integrity, Some(1.0));
+            assert_eq!(ship.nav.route.origin.symbol, "X1-DF55-20250Z");
+            assert_eq!(ship.cooldown.remaining_seconds, 0);
+            assert_eq!(ship.modules.len(), 3);
+            assert_eq!(ship.mounts[2].symbol, ShipMountSymbol::MOUNT_SURVEYOR_I);
+            assert_eq!(ship.mounts[2].deposits.as_ref().unwrap().len(), 11);
+            assert_eq!(ship.cargo.inventory[0].symbol, "ANTIMATTER");
+            assert_eq!(ship.fuel.consumed.unwrap().amount, 0);
+        }
+
+        #[test]
+        fn parses_a_contract() {
+            let contract = parse::<Contract>(include_str!("fixtures/spec/contract.json")).data;
+
+            assert_eq!(contract.contract_type, "PROCUREMENT");
+            assert_eq!(contract.terms.payment.on_fulfilled, 13380);
+            assert_eq!(contract.terms.deliver[0].trade_symbol, "ALUMINUM_ORE");
+            assert!(contract.deadline_to_accept.is_some());
+        }
+
+        #[test]
+        fn parses_a_market_with_a_ship_present() {
+            let market = parse::<Market>(include_str!("fixtures/spec/market.json")).data;
+
+            assert_eq!(market.exchange[0].symbol, TradeSymbol::FUEL);
+            assert_eq!(
+                market.transactions[0].transaction_type,
+                MarketTransactionType::PURCHASE
+            );
+            assert_eq!(market.trade_goods[0].supply, Supply::HIGH);
+            assert_eq!(
+                market.trade_goods[1].trade_type,
+                MarketTradeGoodType::IMPORT
+            );
+            assert_eq!(market.trade_goods[1].activity, Some(ActivityLevel::WEAK));
+            assert_eq!(market.trade_goods[2].activity, None);
+        }
+
+        #[test]
+        fn parses_a_shipyard_without_a_ship_present() {
+            let shipyard = parse::<Shipyard>(include_str!("fixtures/spec/shipyard.json")).data;
+
+            let ship_types: Vec<_> = shipyard.ship_types.iter().map(|t| &t.ship_type).collect();
+            assert_eq!(
+                ship_types[2..],
+                [
+                    &ShipType::SHIP_SIPHON_DRONE,
+                    &ShipType::SHIP_SURVEYOR,
+                    &ShipType::SHIP_BULK_FREIGHTER
+                ]
+            );
+            assert_eq!(shipyard.modifications_fee, 1000);
+            assert!(shipyard.ships.is_empty());
+            assert!(shipyard.transactions.is_empty());
+        }
+
+        #[test]
+        fn parses_a_ship_purchase() {
+            let res =
+                parse::<PurchaseShipResponse>(include_str!("fixtures/spec/purchase_ship.json"))
+                    .data;
+
+            assert_eq!(res.ship.registration.role, ShipRole::HARVESTER);
+            assert_eq!(
+                res.ship.modules[1].symbol,
+                ShipModuleSymbol::MODULE_GAS_PROCESSOR_I
+            );
+            assert_eq!(
+                res.ship.mounts[0].symbol,
+                ShipMountSymbol::MOUNT_GAS_SIPHON_I
+            );
+            assert_eq!(res.transaction.ship_type, ShipType::SHIP_SIPHON_DRONE);
+            assert_eq!(res.agent.credits, 128540);
+        }
+
+        #[test]
+        fn parses_a_survey() {
+            let res = parse::<CreateSurveyResponse>(include_str!("fixtures/spec/survey.json")).data;
+            let survey = &res.surveys[0];
+
+            assert_eq!(survey.size, DepositSize::MODERATE);
+            assert_eq!(survey.deposits[1].symbol, Deposit::ALUMINUM_ORE);
+            assert_eq!(res.cooldown.total_seconds, 70);
+        }
+
+        #[test]
+        fn parses_a_jump_gate() {
+            let gate = parse::<JumpGate>(include_str!("fixtures/spec/jump_gate.json")).data;
+
+            assert_eq!(gate.symbol, "X1-DF55-I73");
+            assert_eq!(gate.connections, ["X1-KM22-C39", "X1-QB91-I62"]);
+        }
+
+        #[test]
+        fn parses_ship_action_responses() {
+            let refuel =
+                parse::<ShipRefuelResponse>(include_str!("fixtures/spec/refuel.json")).data;
+            assert_eq!(refuel.agent.credits, 137800);
+            assert_eq!(refuel.fuel.current, 1200);
+            assert_eq!(refuel.transaction.total_price, 12200);
+
+            let extract =
+                parse::<ExtractResourceResponse>(include_str!("fixtures/spec/extract.json")).data;
+            assert_eq!(
+                extract.extraction.extraction_yield.symbol,
+                TradeSymbol::ALUMINUM_ORE
+            );
+            assert_eq!(extract.cargo.units, 32);
+
+            let navigate =
+                parse::<ShipNavigateResponse>(include_str!("fixtures/spec/navigate.json")).data;
+            assert_eq!(navigate.nav.status, ShipNavStatus::IN_TRANSIT);
+            assert_eq!(navigate.nav.route.origin.symbol, "X1-DF55-20250Z");
+            assert_eq!(
+                navigate.nav.route.destination.waypoint_type,
+                WaypointType::ASTEROID_FIELD
+            );
+        }
+    }
+
+    mod test_commands {
+        type TestResult = Result<(), Box<dyn std::error::Error>>;
+        const PRG: &str = "space_traders_rust";
+
+        use std::{
+            fs::File,
+            io::{BufWriter, Write},
+        };
+
+        use assert_cmd::Command;
+        use assert_fs::prelude::{FileTouch, PathChild};
+        use predicates::prelude::predicate;
+        use space_traders_rust::{domain::Agent, UserInfo};
+
+        fn logged_in_user_dir() -> assert_fs::TempDir {
+            let current_user_dir = assert_fs::TempDir::new().unwrap();
+            let current_user_file = current_user_dir.child("current_user.json");
+            current_user_file.touch().unwrap();
+
+            let test_user_info = UserInfo {
+                token: String::from("fake_token"),
+                agent: Agent {
+                    account_id: None,
+                    credits: 150000,
+                    symbol: "EMBER".to_string(),
+                    ship_count: None,
+                    headquarters: "X1-DF55-20250Z".to_string(),
+                    starting_faction: "COSMIC".to_string(),
+                },
+            };
+            let file = File::create(current_user_file).unwrap();
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, &test_user_info).unwrap();
+            writer.flush().unwrap();
+            current_user_dir
+        }
+
+        #[test]
+        fn waypoint_list_shows_uncharted_waypoints() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            let mut server = mockito::Server::new();
+            let mock = server
+                .mock("GET", "/systems/X1-DF55/waypoints")
+                .match_query(mockito::Matcher::Any)
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(include_str!("fixtures/spec/waypoints.json"))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["waypoint", "list"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("X1-DF55-A1"))
+                .stdout(predicate::str::contains("X1-DF55-DE5F"))
+                .stdout(predicate::str::contains("UNCHARTED"));
+
+            mock.assert();
+
+            Ok(())
+        }
+
+        #[test]
+        fn ship_status_shows_the_ship() -> TestResult {
+            let current_user_dir = logged_in_user_dir();
+
+            let mut server = mockito::Server::new();
+            let mock = server
+                .mock("GET", "/my/ships/EMBER-1")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(include_str!("fixtures/spec/ship.json"))
+                .create();
+
+            Command::cargo_bin(PRG)?
+                .args(["ship", "status", "--ship-symbol", "EMBER-1"])
+                .env("TEST_CURRENT_USER_DIR", current_user_dir.to_str().unwrap())
+                .env("TEST_API_BASE_URL", server.url())
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("FRAME_FRIGATE"))
+                .stdout(predicate::str::contains("MOUNT_SURVEYOR_I"));
+
+            mock.assert();
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod system_map_tests {
+    use chrono::{DateTime, Duration, Utc};
+    use space_traders_rust::{
+        domain::{ShipNav, Waypoint},
+        system_map::{ShipPosition, SystemMap},
+    };
+
+    fn waypoint(symbol: &str, waypoint_type: &str, x: i32, y: i32, traits: &[&str]) -> Waypoint {
+        let traits: Vec<_> = traits
+            .iter()
+            .map(|symbol| serde_json::json!({ "symbol": symbol, "name": "", "description": "" }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol,
+            "type": waypoint_type,
+            "systemSymbol": "X1-TEST",
+            "x": x,
+            "y": y,
+            "orbitals": [],
+            "faction": { "symbol": "COSMIC" },
+            "traits": traits,
+            "chart": { "submittedBy": "COSMIC", "submittedOn": "2023-07-29T00:00:00Z" }
+        }))
+        .unwrap()
+    }
+
+    fn nav(status: &str, departure_time: DateTime<Utc>, arrival: DateTime<Utc>) -> ShipNav {
+        serde_json::from_value(serde_json::json!({
+            "systemSymbol": "X1-TEST",
+            "waypointSymbol": "X1-TEST-B",
+            "route": {
+                "origin": {
+                    "symbol": "X1-TEST-A", "type": "PLANET", "systemSymbol": "X1-TEST",
+                    "x": 0, "y": 0
+                },
+                "destination": {
+                    "symbol": "X1-TEST-B", "type": "MOON", "systemSymbol": "X1-TEST",
+                    "x": 100, "y": -40
+                },
+                "departureTime": departure_time,
+                "arrival": arrival
+            },
+            "status": status,
+            "flightMode": "CRUISE"
+        }))
+        .unwrap()
+    }
+
+    mod test_ship_position {
+        use super::*;
+
+        #[test]
+        fn places_ships_in_transit_along_their_route() {
+            let now = Utc::now();
+            let nav = nav(
+                "IN_TRANSIT",
+                now - Duration::minutes(1),
+                now + Duration::minutes(3),
+            );
+
+            let position = ShipPosition::from_nav("ALI-1", &nav, now);
+
+            assert_eq!((position.x, position.y), (25.0, -10.0));
+            assert_eq!(position.location, "IN_TRANSIT X1-TEST-A -> X1-TEST-B, 25%");
+        }
+
+        #[test]
+        fn places_arrived_ships_at_the_destination() {
+            let now = Utc::now();
+            let nav = nav(
+                "IN_TRANSIT",
+                now - Duration::minutes(4),
+                now - Duration::minutes(1),
+            );
+
+            let position = ShipPosition::from_nav("ALI-1", &nav, now);
+
+            assert_eq!((position.x, position.y), (100.0, -40.0));
+            assert_eq!(position.location, "IN_ORBIT at X1-TEST-B");
+        }
+    }
+
+    mod test_render {
+        use super::*;
+
+        fn grid_rows(rendered: &str) -> Vec<&str> {
+            rendered
+                .lines()
+                .filter(|line| line.starts_with('|'))
+                .collect()
+        }
+
+        #[test]
+        fn puts_the_largest_y_in_the_top_row() {
+            let map = SystemMap {
+                system_symbol: String::from("X1-TEST"),
+                waypoints: vec![
+                    waypoint("X1-TEST-A", "PLANET", 0, 0, &["MARKETPLACE", "SHIPYARD"]),
+                    waypoint("X1-TEST-B", "ASTEROID_FIELD", 100, 100, &[]),
+                ],
+                ships: vec![],
+                jump_gates: vec![],
+            };
+
+            let rendered = map.to_string();
+            let rows = grid_rows(&rendered);
+
+            assert!(rows.first().unwrap().ends_with("*  |"));
+            assert!(rows.last().unwrap().starts_with("|OS "));
+            assert!(rendered.contains("O$S X1-TEST-A"));
+        }
+
+        #[test]
+        fn shows_planets_over_their_orbitals_and_ships_on_top() {
+            let now = Utc::now();
+            let map = SystemMap {
+                system_symbol: String::from("X1-TEST"),
+                waypoints: vec![
+                    waypoint("X1-TEST-M", "MOON", 10, 10, &[]),
+                    waypoint("X1-TEST-A", "PLANET", 10, 10, &[]),
+                    waypoint("X1-TEST-S", "ORBITAL_STATION", 10, 10, &["MARKETPLACE"]),
+                    waypoint("X1-TEST-B", "MOON", 100, -40, &[]),
+                ],
+                ships: vec![
+                    ShipPosition::from_nav("ALI-1", &nav("DOCKED", now, now), now),
+                    ShipPosition::from_nav("ALI-2", &nav("IN_ORBIT", now, now), now),
+                ],
+                jump_gates: vec![],
+            };
+
+            let rendered = map.to_string();
+            let rows = grid_rows(&rendered);
+
+            assert!(rows.first().unwrap().starts_with("|O$ "));
+            assert!(rows.last().unwrap().ends_with("o +|"));
+            assert!(rendered.contains("2 ALI-2        IN_ORBIT at X1-TEST-B"));
+        }
+
+        #[test]
+        fn says_so_when_the_system_is_empty() {
+            let map = SystemMap {
+                system_symbol: String::from("X1-TEST"),
+                waypoints: vec![],
+                ships: vec![],
+                jump_gates: vec![],
+            };
+
+            assert_eq!(map.to_string(), "No waypoints or ships in X1-TEST");
+        }
+    }
+
+    mod test_export {
+        use super::*;
+        use space_traders_rust::{
+            domain::JumpGate,
+            system_export::{export, ExportFormat},
+        };
+
+        /// A planet with a moon and station at its coordinates, a jump gate leading to X1-OUT-G
+        /// and a ship docked at the planet
+        fn system() -> SystemMap {
+            let now = Utc::now();
+            let mut planet = waypoint("X1-TEST-A", "PLANET", 0, 0, &["MARKETPLACE", "SHIPYARD"]);
+            planet.orbitals = serde_json::from_value(serde_json::json!([
+                { "symbol": "X1-TEST-M" },
+                { "symbol": "X1-TEST-S" }
+            ]))
+            .unwrap();
+            let jump_gate: JumpGate = serde_json::from_value(serde_json::json!({
+                "symbol": "X1-TEST-G",
+                "connections": ["X1-OUT-G"]
+            }))
+            .unwrap();
+            SystemMap {
+                system_symbol: String::from("X1-TEST"),
+                waypoints: vec![
+                    planet,
+                    waypoint("X1-TEST-M", "MOON", 0, 0, &[]),
+                    waypoint("X1-TEST-S", "ORBITAL_STATION", 0, 0, &["MARKETPLACE"]),
+                    waypoint("X1-TEST-G", "JUMP_GATE", 100, -40, &[]),
+                ],
+                ships: vec![ShipPosition::from_nav(
+                    "ALI-1",
+                    &serde_json::from_value(serde_json::json!({
+                        "systemSymbol": "X1-TEST",
+                        "waypointSymbol": "X1-TEST-A",
+                        "route": {
+                            "origin": {
+                                "symbol": "X1-TEST-A", "type": "PLANET",
+                                "systemSymbol": "X1-TEST", "x": 0, "y": 0
+                            },
+                            "destination": {
+                                "symbol": "X1-TEST-A", "type": "PLANET",
+                                "systemSymbol": "X1-TEST", "x": 0, "y": 0
+                            },
+                            "departureTime": now,
+                            "arrival": now
+                        },
+                        "status": "DOCKED",
+                        "flightMode": "CRUISE"
+                    }))
+                    .unwrap(),
+                    now,
+                )],
+                jump_gates: vec![(String::from("X1-TEST-G"), jump_gate)],
+            }
+        }
+
+        fn exported(format: ExportFormat) -> String {
+            let mut out = Vec::new();
+            export(&system(), format, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        }
+
+        #[test]
+        fn draws_waypoints_traits_ships_and_jump_links_as_svg() {
+            let svg = exported(ExportFormat::Svg);
+
+            assert!(svg.starts_with("<svg "));
+            assert!(svg.trim_end().ends_with("</svg>"));
+            assert_eq!(svg.matches("<circle ").count(), 4);
+            assert!(svg.contains("MARKETPLACE, SHIPYARD"));
+            assert!(svg.contains(">ALI-1</text>"));
+            assert!(svg.contains("X1-OUT-G (X1-OUT)"));
+        }
+
+        #[test]
+        fn fans_out_orbitals_sharing_their_parents_coordinates() {
+            let svg = exported(ExportFormat::Svg);
+            let centers: Vec<&str> = svg
+                .lines()
+                .filter(|line| line.starts_with("<circle "))
+                .map(|line| line.split(" r=").next().unwrap())
+                .collect();
+
+            let mut distinct = centers.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), centers.len());
+        }
+
+        #[test]
+        fn links_orbitals_ships_and_connected_systems_in_dot() {
+            let dot = exported(ExportFormat::Dot);
+
+            assert!(dot.starts_with("graph \"X1-TEST\" {"));
+            assert!(dot.contains("\"X1-TEST-A\" -- \"X1-TEST-M\" [style=dashed"));
+            assert!(dot.contains("\"X1-TEST-A\" -- \"X1-TEST-S\" [style=dashed"));
+            assert!(dot.contains("\"ALI-1\" -- \"X1-TEST-A\" [style=dotted]"));
+            assert!(dot.contains("\"X1-TEST-G\" -- \"X1-OUT-G\" [style=bold"));
+            assert!(dot.contains("MARKETPLACE, SHIPYARD"));
+        }
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod trade_tests {
+
+    mod test_find_trade_routes {
+        use std::collections::HashMap;
+
+        use chrono::Utc;
+        use space_traders_rust::{
+            domain::Supply,
+            market_history::PriceRecord,
+            trade::{find_trade_routes, TradeRouteSort, TradeShip},
+        };
+
+        fn price(waypoint: &str, good: &str, purchase: u32, sell: u32, volume: u32) -> PriceRecord {
+            PriceRecord {
+                timestamp: Utc::now(),
+                waypoint_symbol: waypoint.to_string(),
+                trade_symbol: good.to_string(),
+                supply: Supply::MODERATE,
+                purchase_price: purchase,
+                sell_price: sell,
+                trade_volume: volume,
+            }
+        }
+
+        fn coordinates() -> HashMap<String, (i32, i32)> {
+            HashMap::from([
+                ("A".to_string(), (0, 0)),
+                ("NEAR".to_string(), (10, 0)),
+                ("FAR".to_string(), (300, 0)),
+            ])
+        }
+
+        const SHIP: TradeShip = TradeShip {
+            location: (0, 0),
+            cargo_capacity: 40,
+            speed: 30,
+        };
+
+        #[test]
+        fn pairs_cheap_markets_with_expensive_ones() {
+            let prices = vec![
+                price("A", "IRON", 10, 8, 100),
+                price("NEAR", "IRON", 25, 20, 100),
+            ];
+
+            let routes = find_trade_routes(&prices, &coordinates(), SHIP, TradeRouteSort::Unit);
+
+            assert_eq!(routes.len(), 1);
+            assert_eq!(routes[0].buy_waypoint, "A");
+            assert_eq!(routes[0].sell_waypoint, "NEAR");
+            assert_eq!(routes[0].profit_per_unit(), 10);
+            assert_eq!(routes[0].profit_per_trip(), 400);
+        }
+
+        #[test]
+        fn caps_trip_size_by_trade_volume() {
+            let prices = vec![
+                price("A", "IRON", 10, 8, 100),
+                price("NEAR", "IRON", 25, 20, 15),
+            ];
+
+            let routes = find_trade_routes(&prices, &coordinates(), SHIP, TradeRouteSort::Trip);
+
+            assert_eq!(routes[0].units_per_trip, 15);
+            assert_eq!(routes[0].profit_per_trip(), 150);
+        }
+
+        #[test]
+        fn ranks_by_profit_per_minute_with_travel_time() {
+            let prices = vec![
+                price("A", "IRON", 10, 8, 100),
+                price("NEAR", "IRON", 25, 20, 100),
+                price("A", "GOLD", 10, 8, 100),
+                price("FAR", "GOLD", 40, 30, 100),
+            ];
+
+            let by_unit = find_trade_routes(&prices, &coordinates(), SHIP, TradeRouteSort::Unit);
+            let by_minute =
+                find_trade_routes(&prices, &coordinates(), SHIP, TradeRouteSort::Minute);
+
+            assert_eq!(by_unit[0].trade_symbol, "GOLD");
+            assert_eq!(by_minute[0].trade_symbol, "IRON");
+        }
+
+        #[test]
+        fn ignores_markets_outside_the_system() {
+            let prices = vec![
+                price("A", "IRON", 10, 8, 100),
+                price("ELSEWHERE", "IRON", 25, 20, 100),
+            ];
+
+            let routes = find_trade_routes(&prices, &coordinates(), SHIP, TradeRouteSort::Unit);
+
+            assert!(routes.is_empty());
+        }
+    }
+}